@@ -1,7 +1,9 @@
 use core::fmt;
 use soroban_sdk::contracterror;
 
-#[contracterror]
+/// Contract errors. The enum isn't exported in the contract spec, which would cap it at 50 cases;
+/// clients see the numeric codes below.
+#[contracterror(export = false)]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 #[repr(u32)]
 pub enum PaymentError {
@@ -41,7 +43,6 @@ pub enum PaymentError {
     InvalidStatus = 27,
     EmptySignersList = 28,
     DuplicateSigner = 29,
-    InvalidPaymentId = 30,
 
     // Refund specific errors
     RefundNotFound = 31,
@@ -55,8 +56,37 @@ pub enum PaymentError {
     InvalidQueryLimit = 37,
     InvalidDateRange = 38,
     InvalidCursor = 39,
-    QueryTooComplex = 40,
     UnauthorizedQuery = 41,
+
+    // Merchant signing key errors
+    SigningKeyNotFound = 42,
+    SigningKeyAlreadyRegistered = 43,
+    SigningKeyInactive = 44,
+    InvalidKeyValidity = 45,
+    TooManySigningKeys = 46,
+
+    // Order signing format errors
+    UnsupportedSchemaVersion = 47,
+    LegacySignatureExpired = 48,
+
+    // Order uniqueness errors
+    DuplicateOrderId = 49,
+
+    // Fee schedule errors
+    InvalidFeeSchedule = 50,
+    InvalidBeneficiary = 51,
+
+    // Split payment errors
+    InvalidSplit = 52,
+
+    // Volume limit errors
+    MerchantVolumeLimitExceeded = 53,
+    TokenVolumeLimitExceeded = 54,
+    PayerVelocityLimitExceeded = 55,
+    PayerMerchantLimitExceeded = 56,
+
+    // Scheduling errors
+    NotDue = 57,
 }
 
 impl fmt::Display for PaymentError {
@@ -108,7 +138,6 @@ impl fmt::Display for PaymentError {
             PaymentError::InvalidStatus => write!(f, "Invalid payment status"),
             PaymentError::EmptySignersList => write!(f, "Signers list cannot be empty"),
             PaymentError::DuplicateSigner => write!(f, "Duplicate signer in list"),
-            PaymentError::InvalidPaymentId => write!(f, "Invalid payment ID"),
 
             // Refund errors
             PaymentError::RefundNotFound => write!(f, "Refund not found"),
@@ -123,11 +152,70 @@ impl fmt::Display for PaymentError {
             PaymentError::InvalidDateRange => {
                 write!(f, "Invalid date range (end must be >= start)")
             }
-            PaymentError::InvalidCursor => write!(f, "Invalid cursor (order_id not found)"),
-            PaymentError::QueryTooComplex => {
-                write!(f, "Query too complex (would exceed gas limits)")
-            }
+            PaymentError::InvalidCursor => write!(f, "Invalid cursor (payment not found)"),
             PaymentError::UnauthorizedQuery => write!(f, "Unauthorized query access"),
+
+            // Merchant signing key errors
+            PaymentError::SigningKeyNotFound => {
+                write!(f, "Signing key not registered for merchant")
+            }
+            PaymentError::SigningKeyAlreadyRegistered => {
+                write!(f, "Signing key already registered")
+            }
+            PaymentError::SigningKeyInactive => {
+                write!(f, "Signing key is revoked or outside its validity window")
+            }
+            PaymentError::InvalidKeyValidity => write!(f, "Invalid signing key validity window"),
+            PaymentError::TooManySigningKeys => {
+                write!(f, "Too many signing keys registered (max 10)")
+            }
+
+            // Order signing format errors
+            PaymentError::UnsupportedSchemaVersion => {
+                write!(f, "Unsupported order schema version")
+            }
+            PaymentError::LegacySignatureExpired => {
+                write!(f, "Legacy order signature format is no longer accepted")
+            }
+
+            // Order uniqueness errors
+            PaymentError::DuplicateOrderId => {
+                write!(f, "Order ID already used by a different payment")
+            }
+
+            // Fee schedule errors
+            PaymentError::InvalidFeeSchedule => write!(f, "Fee schedule bounds are invalid"),
+            PaymentError::InvalidBeneficiary => {
+                write!(
+                    f,
+                    "Fee beneficiaries must be unique with shares totalling 100%"
+                )
+            }
+
+            // Split payment errors
+            PaymentError::InvalidSplit => {
+                write!(
+                    f,
+                    "Split recipients must be unique with shares totalling at most 100%"
+                )
+            }
+
+            // Volume limit errors
+            PaymentError::MerchantVolumeLimitExceeded => {
+                write!(f, "Merchant volume limit exceeded for this window")
+            }
+            PaymentError::TokenVolumeLimitExceeded => {
+                write!(f, "Token volume limit exceeded for this window")
+            }
+            PaymentError::PayerVelocityLimitExceeded => {
+                write!(f, "Payer velocity limit exceeded for this window")
+            }
+            PaymentError::PayerMerchantLimitExceeded => {
+                write!(f, "Payer limit with this merchant exceeded for this window")
+            }
+
+            // Scheduling errors
+            PaymentError::NotDue => write!(f, "Not due yet"),
         }
     }
 }
@@ -1,6 +1,7 @@
 use crate::error::PaymentError;
-use crate::types::PaymentQueryFilter;
-use soroban_sdk::String;
+use crate::storage::PaymentKey;
+use crate::types::{AdminCouncil, FeeBeneficiary, FeeSchedule, PaymentQueryFilter, SplitRecipient};
+use soroban_sdk::{Address, Env, String, Vec};
 
 // Validation constants
 pub const MIN_NAME_LENGTH: u32 = 1;
@@ -8,6 +9,160 @@ pub const MAX_NAME_LENGTH: u32 = 100;
 pub const MAX_DESCRIPTION_LENGTH: u32 = 500;
 pub const MAX_CONTACT_INFO_LENGTH: u32 = 200;
 pub const DEFAULT_TRANSACTION_LIMIT: i128 = 1_000_000_000_000; // 1 trillion stroops (100,000 XLM)
+pub const LIMIT_REFERENCE_DECIMALS: u32 = 7; // Decimals `max_transaction_limit` is expressed in
+pub const MAX_SIGNING_KEYS_PER_MERCHANT: u32 = 10;
+
+// Fee constants
+pub const BASIS_POINTS_DENOMINATOR: i128 = 10_000;
+pub const MAX_FEE_RATE_BPS: u32 = 1_000; // 10%
+pub const MAX_FEE_BENEFICIARIES: u32 = 10;
+
+// Escrow constants
+pub const MAX_ESCROW_RELEASE_TIMEOUT: u64 = 90 * 24 * 60 * 60; // 90 days
+
+// Dispute constants
+pub const DISPUTE_WINDOW: u64 = 60 * 24 * 60 * 60; // 60 days after payment
+pub const DISPUTE_EVIDENCE_PERIOD: u64 = 7 * 24 * 60 * 60; // 7 days after opening
+pub const MAX_DISPUTE_EVIDENCE: u32 = 5; // per side
+
+// Generated order ID prefixes
+pub const SUBSCRIPTION_ORDER_PREFIX: &[u8] = b"SUB-";
+pub const INVOICE_ORDER_PREFIX: &[u8] = b"INV-";
+pub const CAPTURE_ORDER_PREFIX: &[u8] = b"AUTH-";
+
+// Authorization hold constants
+pub const MAX_AUTHORIZATION_PERIOD: u64 = 30 * 24 * 60 * 60; // 30 days
+
+// Order signing format constants
+pub const ORDER_DOMAIN_TAG: &[u8] = b"paystell:payment_order";
+pub const LEGACY_ORDER_SCHEMA_VERSION: u32 = 0;
+pub const TYPED_ORDER_SCHEMA_VERSION: u32 = 1;
+pub const SPLIT_ORDER_SCHEMA_VERSION: u32 = 2;
+pub const SWAP_ORDER_SCHEMA_VERSION: u32 = 3;
+pub const ORDER_SCHEMA_VERSION: u32 = 4;
+
+// Split payment constants
+pub const MAX_SPLIT_RECIPIENTS: u32 = 10;
+
+// Rolling reserve constants
+pub const MAX_ROLLING_RESERVE_RATE_BPS: u32 = 5_000; // 50%
+pub const MAX_ROLLING_RESERVE_PERIOD: u64 = 180 * 24 * 60 * 60; // 180 days
+
+// Admin governance constants
+pub const ADMIN_HANDOVER_WINDOW: u64 = 7 * 24 * 60 * 60; // 7 days to accept a handover
+pub const COUNCIL_PROPOSAL_WINDOW: u64 = 7 * 24 * 60 * 60; // 7 days to approve a proposal
+
+// Volume limit constants
+pub const MAX_VOLUME_LIMITS_PER_SCOPE: u32 = 4;
+pub const MAX_VOLUME_LIMIT_WINDOW: u64 = 31 * 24 * 60 * 60; // 31 days
+pub const VOLUME_WARNING_BPS: i128 = 8_000; // 80% of a cap
+pub const SECONDS_PER_LEDGER: u64 = 5; // Average ledger close time
+
+// Settlement schedule periods
+pub const DAILY_SETTLEMENT_PERIOD: u64 = 24 * 60 * 60;
+pub const WEEKLY_SETTLEMENT_PERIOD: u64 = 7 * 24 * 60 * 60;
+
+// Relayed payment constants
+pub const RELAY_DOMAIN_TAG: &[u8] = b"paystell:relayed_payment";
+pub const MAX_RELAY_FEE_BPS: u32 = 100; // 1% of the settled amount
+
+// Fiat pricing constants
+pub const FIAT_RATE_SCALE: i128 = 100_000_000_000_000; // rates carry 14 decimals
+pub const BATCH_DIGEST_DOMAIN_TAG: &[u8] = b"paystell:payment_batch";
+
+/// Validates a fee schedule's rate and per-transaction bounds
+pub fn validate_fee_schedule(schedule: &FeeSchedule) -> Result<(), PaymentError> {
+    if schedule.rate_bps > MAX_FEE_RATE_BPS {
+        return Err(PaymentError::InvalidFeeRate);
+    }
+    if schedule.fixed_fee < 0 || schedule.min_fee < 0 {
+        return Err(PaymentError::InvalidFeeSchedule);
+    }
+    if let Some(max_fee) = schedule.max_fee {
+        if max_fee < schedule.min_fee {
+            return Err(PaymentError::InvalidFeeSchedule);
+        }
+    }
+    Ok(())
+}
+
+/// Validates an admin council: unique members and a threshold between 1 and their number
+pub fn validate_admin_council(council: &AdminCouncil) -> Result<(), PaymentError> {
+    if council.members.is_empty() {
+        return Err(PaymentError::EmptySignersList);
+    }
+    if council.threshold == 0 || council.threshold > council.members.len() {
+        return Err(PaymentError::InvalidThreshold);
+    }
+    for (i, member) in council.members.iter().enumerate() {
+        if council
+            .members
+            .iter()
+            .skip(i + 1)
+            .any(|other| other == member)
+        {
+            return Err(PaymentError::DuplicateSigner);
+        }
+    }
+    Ok(())
+}
+
+/// Validates an order's revenue splits: unique recipients whose shares total at most 100%
+pub fn validate_split_recipients(
+    merchant: &Address,
+    splits: &Vec<SplitRecipient>,
+) -> Result<(), PaymentError> {
+    if splits.len() > MAX_SPLIT_RECIPIENTS {
+        return Err(PaymentError::InvalidSplit);
+    }
+
+    let mut total: i128 = 0;
+    for (i, split) in splits.iter().enumerate() {
+        if split.share_bps == 0 || split.recipient == *merchant {
+            return Err(PaymentError::InvalidSplit);
+        }
+        for other in splits.iter().skip(i + 1) {
+            if other.recipient == split.recipient {
+                return Err(PaymentError::InvalidSplit);
+            }
+        }
+        total += i128::from(split.share_bps);
+    }
+
+    if total > BASIS_POINTS_DENOMINATOR {
+        return Err(PaymentError::InvalidSplit);
+    }
+    Ok(())
+}
+
+/// Validates fee beneficiaries: unique addresses whose shares total 100%.
+/// An empty list is valid and sends all fees to the token's collector.
+pub fn validate_fee_beneficiaries(beneficiaries: &Vec<FeeBeneficiary>) -> Result<(), PaymentError> {
+    if beneficiaries.is_empty() {
+        return Ok(());
+    }
+    if beneficiaries.len() > MAX_FEE_BENEFICIARIES {
+        return Err(PaymentError::InvalidBeneficiary);
+    }
+
+    let mut total: i128 = 0;
+    for (i, beneficiary) in beneficiaries.iter().enumerate() {
+        if beneficiary.share_bps == 0 {
+            return Err(PaymentError::InvalidBeneficiary);
+        }
+        for other in beneficiaries.iter().skip(i + 1) {
+            if other.address == beneficiary.address {
+                return Err(PaymentError::InvalidBeneficiary);
+            }
+        }
+        total += i128::from(beneficiary.share_bps);
+    }
+
+    if total != BASIS_POINTS_DENOMINATOR {
+        return Err(PaymentError::InvalidBeneficiary);
+    }
+    Ok(())
+}
 
 /// Validates merchant name length
 pub fn validate_name(name: &String) -> Result<(), PaymentError> {
@@ -42,6 +197,30 @@ pub fn validate_transaction_limit(limit: i128) -> Result<(), PaymentError> {
     Ok(())
 }
 
+/// Scales a limit expressed in `LIMIT_REFERENCE_DECIMALS` decimals to a token with `decimals`
+pub fn scale_transaction_limit(limit: i128, decimals: u32) -> i128 {
+    if decimals >= LIMIT_REFERENCE_DECIMALS {
+        10i128
+            .checked_pow(decimals - LIMIT_REFERENCE_DECIMALS)
+            .map_or(i128::MAX, |factor| limit.saturating_mul(factor))
+    } else {
+        limit / 10i128.pow(LIMIT_REFERENCE_DECIMALS - decimals)
+    }
+}
+
+/// Validates a signing key validity window
+pub fn validate_key_validity(
+    valid_from: u64,
+    valid_until: Option<u64>,
+) -> Result<(), PaymentError> {
+    if let Some(until) = valid_until {
+        if until < valid_from {
+            return Err(PaymentError::InvalidKeyValidity);
+        }
+    }
+    Ok(())
+}
+
 // Query validation constants
 pub const MAX_QUERY_LIMIT: u32 = 100;
 pub const MAX_DATE_RANGE_DAYS: u64 = 365; // Maximum 1 year range
@@ -97,18 +276,16 @@ pub fn validate_amount_range(
     Ok(())
 }
 
-/// Validates cursor (order_id) exists
+/// Validates cursor (merchant, order_id) names one of the payments being queried
 pub fn validate_cursor(
     _env: &soroban_sdk::Env,
-    cursor: &Option<String>,
-    storage: &crate::storage::Storage,
+    cursor: &Option<PaymentKey>,
+    keys: &Vec<PaymentKey>,
 ) -> Result<(), PaymentError> {
-    if let Some(ref order_id) = cursor {
-        // Check if payment exists
-        storage
-            .get_payment(order_id)
-            .map(|_| ())
-            .map_err(|_| PaymentError::InvalidCursor)?;
+    if let Some(ref key) = cursor {
+        if !keys.contains(key) {
+            return Err(PaymentError::InvalidCursor);
+        }
     }
     Ok(())
 }
@@ -119,3 +296,43 @@ pub fn validate_query_filter(filter: &PaymentQueryFilter) -> Result<(), PaymentE
     validate_amount_range(filter.amount_min, filter.amount_max)?;
     Ok(())
 }
+
+/// Token amount for a fiat `amount` at `rate` (scaled by `FIAT_RATE_SCALE`), rounded up
+/// so the merchant never receives less than the fiat value
+pub fn fiat_to_token_amount(amount: i128, rate: i128) -> Result<i128, PaymentError> {
+    if amount <= 0 || rate <= 0 {
+        return Err(PaymentError::InvalidAmount);
+    }
+    let scaled = amount
+        .checked_mul(FIAT_RATE_SCALE)
+        .ok_or(PaymentError::InvalidAmount)?;
+    Ok((scaled + rate - 1) / rate)
+}
+
+/// Order ID recorded for a contract-initiated payment: `<prefix><id>-<sequence>`
+pub fn sequential_order_id(env: &Env, prefix: &[u8], id: u64, sequence: u32) -> String {
+    let mut buf = [0u8; 48];
+    buf[..prefix.len()].copy_from_slice(prefix);
+    let mut len = write_decimal(&mut buf, prefix.len(), id);
+    buf[len] = b'-';
+    len = write_decimal(&mut buf, len + 1, sequence as u64);
+    String::from_bytes(env, &buf[..len])
+}
+
+/// Write `value` in decimal at `buf[start..]`, returning the end position
+fn write_decimal(buf: &mut [u8], start: usize, mut value: u64) -> usize {
+    let mut digits = [0u8; 20];
+    let mut count = 0;
+    loop {
+        digits[count] = b'0' + (value % 10) as u8;
+        count += 1;
+        value /= 10;
+        if value == 0 {
+            break;
+        }
+    }
+    for i in 0..count {
+        buf[start + i] = digits[count - 1 - i];
+    }
+    start + count
+}
@@ -0,0 +1,35 @@
+//! Interfaces of external contracts the payment contract calls into.
+
+use soroban_sdk::{contractclient, Address, Env, Vec};
+
+use crate::types::{OracleAsset, PriceData};
+
+/// Swap router used to settle cross-token payments.
+///
+/// The caller transfers `max_amount_in` of `token_in` to the router before invoking it.
+/// The router must deliver exactly `amount_out` of `token_out` to `recipient`, return the
+/// unused input to `recipient`, and report how much input it consumed.
+#[allow(dead_code)] // only the generated client is called
+#[contractclient(name = "SwapRouterClient")]
+pub trait SwapRouter {
+    fn swap_exact_out(
+        env: Env,
+        token_in: Address,
+        token_out: Address,
+        amount_out: i128,
+        max_amount_in: i128,
+        recipient: Address,
+    ) -> i128;
+}
+
+/// SEP-40 price oracle used to convert fiat-denominated orders
+#[allow(dead_code)] // only the generated client is called
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracle {
+    /// Number of decimals prices are quoted with
+    fn decimals(env: Env) -> u32;
+    /// Most recent price of `asset`
+    fn lastprice(env: Env, asset: OracleAsset) -> Option<PriceData>;
+    /// Up to `records` most recent prices of `asset`, newest first
+    fn prices(env: Env, asset: OracleAsset, records: u32) -> Option<Vec<PriceData>>;
+}
@@ -2,36 +2,63 @@
 
 mod error;
 mod helper;
+mod interfaces;
 mod storage;
 mod types;
 
+#[cfg(any(test, feature = "testutils"))]
+pub mod testutils;
+
 #[cfg(test)]
 mod test;
 
 use soroban_sdk::{
-    contract, contractimpl, log, panic_with_error, token, xdr::ToXdr, Address, Bytes, BytesN, Env,
-    Map, String, Symbol, Vec,
+    contract, contractimpl, log, token, xdr::ToXdr, Address, Bytes, BytesN, Env, Map, String,
+    Symbol, Vec,
 };
 
 // Note: In Soroban, we use the standard Vec from soroban_sdk, not alloc::vec
 use crate::{
     error::PaymentError,
     helper::{
-        validate_contact_info, validate_cursor, validate_description, validate_name,
-        validate_query_filter, validate_query_limit, validate_transaction_limit,
-        DEFAULT_TRANSACTION_LIMIT,
+        fiat_to_token_amount, scale_transaction_limit, sequential_order_id, validate_admin_council,
+        validate_contact_info, validate_cursor, validate_description, validate_fee_beneficiaries,
+        validate_fee_schedule, validate_key_validity, validate_name, validate_query_filter,
+        validate_query_limit, validate_split_recipients, validate_transaction_limit,
+        ADMIN_HANDOVER_WINDOW, BASIS_POINTS_DENOMINATOR, BATCH_DIGEST_DOMAIN_TAG,
+        CAPTURE_ORDER_PREFIX, COUNCIL_PROPOSAL_WINDOW, DEFAULT_TRANSACTION_LIMIT,
+        DISPUTE_EVIDENCE_PERIOD, DISPUTE_WINDOW, FIAT_RATE_SCALE, INVOICE_ORDER_PREFIX,
+        LEGACY_ORDER_SCHEMA_VERSION, MAX_AUTHORIZATION_PERIOD, MAX_DISPUTE_EVIDENCE,
+        MAX_ESCROW_RELEASE_TIMEOUT, MAX_RELAY_FEE_BPS, MAX_ROLLING_RESERVE_PERIOD,
+        MAX_ROLLING_RESERVE_RATE_BPS, MAX_SIGNING_KEYS_PER_MERCHANT, MAX_VOLUME_LIMITS_PER_SCOPE,
+        MAX_VOLUME_LIMIT_WINDOW, ORDER_DOMAIN_TAG, ORDER_SCHEMA_VERSION, RELAY_DOMAIN_TAG,
+        SPLIT_ORDER_SCHEMA_VERSION, SUBSCRIPTION_ORDER_PREFIX, SWAP_ORDER_SCHEMA_VERSION,
+        TYPED_ORDER_SCHEMA_VERSION, VOLUME_WARNING_BPS,
     },
-    storage::Storage,
+    interfaces::{PriceOracleClient, SwapRouterClient},
+    storage::{PaymentKey, Storage},
     types::{
+        authorization_topic, council_topic, dispute_topic, escrow_topic, invoice_topic,
         limits_updated_topic, merchant_deactivated_topic, merchant_registered_topic,
-        multisig_topic, profile_updated_topic, BatchMerchantRegistration, BatchPayment,
-        BatchTokenAddition, Fee, GasEstimate, LimitsUpdatedEvent, Merchant, MerchantCategory,
-        MerchantDeactivatedEvent, MerchantRegisteredEvent, MultiSigPayment,
+        multisig_topic, oracle_topic, payment_topic, profile_updated_topic, reserve_topic,
+        role_topic, settlement_topic, signing_key_topic, subscription_topic, swap_topic,
+        volume_limit_topic, AdminCouncil, AdminHandover, AuthorizationStatus,
+        BatchMerchantRegistration, BatchMode, BatchOrderResult, BatchPayment, BatchSignatures,
+        BatchTokenAddition, CouncilAction, CouncilProposal, Dispute, DisputeResolvedEvent,
+        DisputeRuling, DisputeStatus, EscrowReleasedEvent, EscrowStatus, Fee, FeeBeneficiary,
+        FeeMode, FeeSchedule, FeeScope, GasEstimate, Invoice, InvoiceStatus, InvoiceTerms,
+        LimitScope, LimitsUpdatedEvent, Merchant, MerchantCategory, MerchantDeactivatedEvent,
+        MerchantRegisteredEvent, MerchantSigningKey, MultiSigPayment,
         MultiSigPaymentCancelledEvent, MultiSigPaymentExecutedEvent, MultiSigPaymentInitiatedEvent,
-        MultiSigPaymentRecord, MultiSigSignatureAddedEvent, NonceTracker, PaymentOrder,
+        MultiSigPaymentRecord, MultiSigSignatureAddedEvent, NonceTracker, OracleAsset,
+        OracleConfig, OrderStatus, PaymentAuthorization, PaymentOrder, PaymentProcessedEvent,
         PaymentQueryFilter, PaymentQueryResult, PaymentRecord, PaymentRecordStatus, PaymentStats,
         PaymentStatus, ProfileUpdateData, ProfileUpdatedEvent, RefundRequest, RefundStatus,
-        SortField, SortOrder,
+        RelayedPayment, ReserveScope, ReserveTranche, Role, RollingReserveConfig, SettlementConfig,
+        SettlementSchedule, SigningKeyRegisteredEvent, SigningKeyRevokedEvent,
+        SigningKeyRotatedEvent, SortField, SortOrder, SplitPayout, SplitRecipient, Subscription,
+        SubscriptionPlan, SubscriptionStatus, TokenFeeInfo, VolumeCounter, VolumeLimit,
+        VolumeLimitEvent, VolumeUsage,
     },
 };
 
@@ -63,19 +90,151 @@ pub trait PaymentProcessingTrait {
         env: Env,
         merchant: Address,
         max_transaction_limit: i128,
+        token_limits: Map<Address, i128>,
     ) -> Result<(), PaymentError>;
+    fn get_transaction_limit(
+        env: Env,
+        merchant: Address,
+        token: Address,
+    ) -> Result<i128, PaymentError>;
 
     fn deactivate_merchant(env: Env, merchant: Address) -> Result<(), PaymentError>;
 
-    // Fee Management Operations
+    // Signing Key Management Operations
+    fn register_signing_key(
+        env: Env,
+        merchant: Address,
+        public_key: BytesN<32>,
+        valid_from: Option<u64>,
+        valid_until: Option<u64>,
+    ) -> Result<(), PaymentError>;
+
+    fn rotate_signing_key(
+        env: Env,
+        merchant: Address,
+        old_key: BytesN<32>,
+        new_key: BytesN<32>,
+        grace_period: u64,
+    ) -> Result<(), PaymentError>;
+
+    fn revoke_signing_key(
+        env: Env,
+        merchant: Address,
+        public_key: BytesN<32>,
+    ) -> Result<(), PaymentError>;
+
+    fn get_signing_keys(
+        env: Env,
+        merchant: Address,
+    ) -> Result<Vec<MerchantSigningKey>, PaymentError>;
+
+    // Role Management Operations
     fn set_admin(env: Env, admin: Address) -> Result<(), PaymentError>;
+    fn grant_role(
+        env: Env,
+        admin: Address,
+        account: Address,
+        role: Role,
+    ) -> Result<(), PaymentError>;
+    fn revoke_role(
+        env: Env,
+        admin: Address,
+        account: Address,
+        role: Role,
+    ) -> Result<(), PaymentError>;
+    fn has_role(env: Env, account: Address, role: Role) -> bool;
+    fn get_role_members(env: Env, role: Role) -> Vec<Address>;
+    fn propose_super_admin(
+        env: Env,
+        admin: Address,
+        new_admin: Address,
+    ) -> Result<(), PaymentError>;
+    fn accept_super_admin(env: Env, new_admin: Address) -> Result<(), PaymentError>;
+    fn get_pending_super_admin(env: Env) -> Option<AdminHandover>;
+
+    // Admin Council Operations
+    fn set_admin_council(
+        env: Env,
+        admin: Address,
+        council: AdminCouncil,
+    ) -> Result<(), PaymentError>;
+    fn get_admin_council(env: Env) -> Option<AdminCouncil>;
+    fn propose_council_action(
+        env: Env,
+        member: Address,
+        action: CouncilAction,
+    ) -> Result<u64, PaymentError>;
+    fn approve_council_action(
+        env: Env,
+        member: Address,
+        proposal_id: u64,
+    ) -> Result<bool, PaymentError>;
+    fn get_council_proposal(env: Env, proposal_id: u64) -> Result<CouncilProposal, PaymentError>;
+    fn get_open_council_proposals(env: Env) -> Vec<CouncilProposal>;
+
+    // Fee Management Operations
     fn set_fee(
         env: Env,
-        fee_rate: u64,
+        admin: Address,
+        fee_rate_bps: u32,
         fee_collector: Address,
         fee_token: Address,
     ) -> Result<(), PaymentError>;
     fn get_fee_info(env: Env) -> Result<(u64, Address, Address), PaymentError>;
+    fn set_fee_schedule(
+        env: Env,
+        admin: Address,
+        schedule: FeeSchedule,
+    ) -> Result<(), PaymentError>;
+    fn set_fee_override(
+        env: Env,
+        admin: Address,
+        scope: FeeScope,
+        schedule: Option<FeeSchedule>,
+    ) -> Result<(), PaymentError>;
+    fn get_fee_override(env: Env, scope: FeeScope) -> Option<FeeSchedule>;
+    fn get_effective_fee_schedule(
+        env: Env,
+        merchant: Address,
+        token: Address,
+    ) -> Result<FeeSchedule, PaymentError>;
+    fn set_token_fee(
+        env: Env,
+        admin: Address,
+        token: Address,
+        fee_collector: Address,
+        schedule: FeeSchedule,
+    ) -> Result<(), PaymentError>;
+    fn remove_token_fee(env: Env, admin: Address, token: Address) -> Result<(), PaymentError>;
+    fn get_token_fee_info(env: Env, token: Address) -> Result<TokenFeeInfo, PaymentError>;
+
+    // Fee Custody Operations
+    fn set_fee_custody(env: Env, admin: Address, enabled: bool) -> Result<(), PaymentError>;
+    fn is_fee_custody_enabled(env: Env) -> bool;
+    fn set_fee_beneficiaries(
+        env: Env,
+        admin: Address,
+        beneficiaries: Vec<FeeBeneficiary>,
+    ) -> Result<(), PaymentError>;
+    fn get_fee_beneficiaries(env: Env) -> Vec<FeeBeneficiary>;
+    fn withdraw_fees(env: Env, beneficiary: Address, token: Address) -> Result<i128, PaymentError>;
+    fn get_fee_balance(env: Env, beneficiary: Address, token: Address) -> i128;
+    fn get_fee_balances(env: Env, beneficiary: Address) -> Map<Address, i128>;
+    fn get_accrued_fees(env: Env, token: Address) -> i128;
+    fn set_merchant_fee_mode(
+        env: Env,
+        merchant: Address,
+        fee_mode: FeeMode,
+    ) -> Result<(), PaymentError>;
+    fn get_merchant_fee_mode(env: Env, merchant: Address) -> FeeMode;
+
+    // Order Signing Format Operations
+    fn set_legacy_signature_deadline(
+        env: Env,
+        admin: Address,
+        deadline: u64,
+    ) -> Result<(), PaymentError>;
+    fn get_legacy_signature_deadline(env: Env) -> Option<u64>;
 
     // Payment Processing Operations
     fn process_payment_with_signature(
@@ -84,7 +243,13 @@ pub trait PaymentProcessingTrait {
         order: PaymentOrder,
         signature: BytesN<64>,
         merchant_public_key: BytesN<32>,
-    ) -> Result<(), PaymentError>;
+    ) -> Result<PaymentRecord, PaymentError>;
+
+    fn get_order_status(
+        env: Env,
+        merchant: Address,
+        order_id: String,
+    ) -> Result<OrderStatus, PaymentError>;
 
     // Batch Operations for Gas Optimization
     fn batch_register_merchants(
@@ -92,7 +257,10 @@ pub trait PaymentProcessingTrait {
         batch: BatchMerchantRegistration,
     ) -> Result<(), PaymentError>;
     fn batch_add_tokens(env: Env, batch: BatchTokenAddition) -> Result<(), PaymentError>;
-    fn batch_process_payments(env: Env, batch: BatchPayment) -> Result<(), PaymentError>;
+    fn batch_process_payments(
+        env: Env,
+        batch: BatchPayment,
+    ) -> Result<Vec<BatchOrderResult>, PaymentError>;
 
     // Gas Estimation Functions
     fn estimate_gas_for_payment(env: Env, order: PaymentOrder)
@@ -163,11 +331,234 @@ pub trait PaymentProcessingTrait {
     fn unpause(env: Env, admin: Address) -> Result<(), PaymentError>;
     fn is_paused(env: &Env) -> bool;
 
+    // Escrow Operations
+    fn set_merchant_escrow(
+        env: Env,
+        admin: Address,
+        merchant: Address,
+        release_timeout: Option<u64>,
+    ) -> Result<(), PaymentError>;
+    fn get_merchant_escrow(env: Env, merchant: Address) -> Option<u64>;
+    fn confirm_delivery(
+        env: Env,
+        payer: Address,
+        merchant: Address,
+        order_id: String,
+    ) -> Result<(), PaymentError>;
+    fn release_escrow(env: Env, merchant: Address, order_id: String) -> Result<(), PaymentError>;
+    fn dispute_escrow(
+        env: Env,
+        payer: Address,
+        merchant: Address,
+        order_id: String,
+    ) -> Result<(), PaymentError>;
+    fn resolve_escrow_dispute(
+        env: Env,
+        admin: Address,
+        merchant: Address,
+        order_id: String,
+        refund_amount: i128,
+    ) -> Result<(), PaymentError>;
+
+    // Dispute and Arbitration Operations
+    fn add_arbitrator(env: Env, admin: Address, arbitrator: Address) -> Result<(), PaymentError>;
+    fn remove_arbitrator(env: Env, admin: Address, arbitrator: Address)
+        -> Result<(), PaymentError>;
+    fn get_arbitrators(env: Env) -> Vec<Address>;
+    fn deposit_reserve(
+        env: Env,
+        merchant: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<(), PaymentError>;
+    fn withdraw_reserve(
+        env: Env,
+        merchant: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<(), PaymentError>;
+    fn get_merchant_reserve(env: Env, merchant: Address, token: Address) -> i128;
+    fn open_dispute(
+        env: Env,
+        payer: Address,
+        merchant: Address,
+        order_id: String,
+        amount: i128,
+        reason: String,
+    ) -> Result<(), PaymentError>;
+    fn submit_dispute_evidence(
+        env: Env,
+        caller: Address,
+        merchant: Address,
+        order_id: String,
+        evidence_hash: BytesN<32>,
+    ) -> Result<(), PaymentError>;
+    fn resolve_dispute(
+        env: Env,
+        arbitrator: Address,
+        merchant: Address,
+        order_id: String,
+        ruling: DisputeRuling,
+    ) -> Result<(), PaymentError>;
+    fn get_dispute(env: Env, merchant: Address, order_id: String) -> Result<Dispute, PaymentError>;
+
+    // Subscription Operations
+    fn create_subscription_plan(
+        env: Env,
+        merchant: Address,
+        token: Address,
+        amount: i128,
+        period: u64,
+        trial_period: u64,
+        max_cycles: u32,
+    ) -> Result<u64, PaymentError>;
+    fn set_subscription_plan_active(
+        env: Env,
+        merchant: Address,
+        plan_id: u64,
+        active: bool,
+    ) -> Result<(), PaymentError>;
+    fn get_subscription_plan(env: Env, plan_id: u64) -> Result<SubscriptionPlan, PaymentError>;
+    fn subscribe(env: Env, payer: Address, plan_id: u64) -> Result<u64, PaymentError>;
+    fn charge_due(env: Env, subscription_id: u64) -> Result<PaymentRecord, PaymentError>;
+    fn cancel_subscription(
+        env: Env,
+        caller: Address,
+        subscription_id: u64,
+    ) -> Result<(), PaymentError>;
+    fn pause_subscription(
+        env: Env,
+        caller: Address,
+        subscription_id: u64,
+    ) -> Result<(), PaymentError>;
+    fn resume_subscription(
+        env: Env,
+        caller: Address,
+        subscription_id: u64,
+    ) -> Result<(), PaymentError>;
+    fn get_subscription(env: Env, subscription_id: u64) -> Result<Subscription, PaymentError>;
+    fn get_merchant_subscriptions(env: Env, merchant: Address) -> Vec<Subscription>;
+    fn get_payer_subscriptions(env: Env, payer: Address) -> Vec<Subscription>;
+
+    // Invoice Operations
+    fn create_invoice(
+        env: Env,
+        merchant: Address,
+        terms: InvoiceTerms,
+    ) -> Result<u64, PaymentError>;
+    fn pay_invoice(
+        env: Env,
+        payer: Address,
+        invoice_id: u64,
+        amount: i128,
+    ) -> Result<PaymentRecord, PaymentError>;
+    fn cancel_invoice(env: Env, merchant: Address, invoice_id: u64) -> Result<(), PaymentError>;
+    fn get_invoice(env: Env, invoice_id: u64) -> Result<Invoice, PaymentError>;
+
+    // Authorize and Capture Operations
+    fn authorize_payment(
+        env: Env,
+        payer: Address,
+        merchant: Address,
+        token: Address,
+        max_amount: i128,
+        expires_at: u64,
+    ) -> Result<u64, PaymentError>;
+    fn capture_payment(
+        env: Env,
+        merchant: Address,
+        authorization_id: u64,
+        amount: i128,
+        final_capture: bool,
+    ) -> Result<PaymentRecord, PaymentError>;
+    fn void_authorization(
+        env: Env,
+        merchant: Address,
+        authorization_id: u64,
+    ) -> Result<(), PaymentError>;
+    fn release_expired_authorization(env: Env, authorization_id: u64) -> Result<(), PaymentError>;
+    fn get_authorization(
+        env: Env,
+        authorization_id: u64,
+    ) -> Result<PaymentAuthorization, PaymentError>;
+
+    // Cross-token Payment Operations
+    fn set_swap_router(env: Env, admin: Address, router: Address) -> Result<(), PaymentError>;
+    fn get_swap_router(env: Env) -> Option<Address>;
+
+    // Fiat-denominated Order Operations
+    fn set_price_oracle(env: Env, admin: Address, config: OracleConfig)
+        -> Result<(), PaymentError>;
+    fn get_price_oracle(env: Env) -> Option<OracleConfig>;
+
+    // Relayed Payment Operations
+    fn set_payer_relay_key(
+        env: Env,
+        payer: Address,
+        public_key: Option<BytesN<32>>,
+    ) -> Result<(), PaymentError>;
+    fn get_payer_relay_key(env: Env, payer: Address) -> Option<BytesN<32>>;
+    fn process_relayed_payment(
+        env: Env,
+        relayer: Address,
+        order: PaymentOrder,
+        signature: BytesN<64>,
+        merchant_public_key: BytesN<32>,
+        relay: RelayedPayment,
+    ) -> Result<PaymentRecord, PaymentError>;
+
+    // Merchant Balance Operations
+    fn set_settlement_config(
+        env: Env,
+        merchant: Address,
+        payout_address: Address,
+        schedule: SettlementSchedule,
+        min_payout: i128,
+    ) -> Result<(), PaymentError>;
+    fn disable_settlement(env: Env, merchant: Address) -> Result<(), PaymentError>;
+    fn get_settlement_config(env: Env, merchant: Address) -> Option<SettlementConfig>;
+    fn get_merchant_balance(env: Env, merchant: Address, token: Address) -> i128;
+    fn withdraw_balance(
+        env: Env,
+        merchant: Address,
+        token: Address,
+        amount: i128,
+        to: Address,
+    ) -> Result<(), PaymentError>;
+    fn settle(env: Env, merchant: Address, token: Address) -> Result<i128, PaymentError>;
+
+    // Rolling Reserve Operations
+    fn set_rolling_reserve(
+        env: Env,
+        admin: Address,
+        scope: ReserveScope,
+        config: Option<RollingReserveConfig>,
+    ) -> Result<(), PaymentError>;
+    fn get_rolling_reserve(env: Env, scope: ReserveScope) -> Option<RollingReserveConfig>;
+    fn release_rolling_reserve(
+        env: Env,
+        merchant: Address,
+        token: Address,
+    ) -> Result<i128, PaymentError>;
+    fn get_rolling_reserve_balance(env: Env, merchant: Address, token: Address) -> i128;
+    fn get_reserve_tranches(env: Env, merchant: Address) -> Vec<ReserveTranche>;
+
+    // Volume Limit Operations
+    fn set_volume_limits(
+        env: Env,
+        admin: Address,
+        scope: LimitScope,
+        limits: Vec<VolumeLimit>,
+    ) -> Result<(), PaymentError>;
+    fn get_volume_limits(env: Env, scope: LimitScope) -> Vec<VolumeLimit>;
+    fn get_volume_usage(env: Env, counter: VolumeCounter, window: u64) -> VolumeUsage;
+
     // Refund Management Operations
     fn initiate_refund(
         env: Env,
         caller: Address,
         refund_id: String,
+        merchant: Address,
         order_id: String,
         amount: i128,
         reason: String,
@@ -182,7 +573,7 @@ pub trait PaymentProcessingTrait {
     fn get_merchant_payment_history(
         env: Env,
         merchant: Address,
-        cursor: Option<String>,
+        cursor: Option<PaymentKey>,
         limit: u32,
         filter: Option<PaymentQueryFilter>,
         sort_field: Option<SortField>,
@@ -192,7 +583,7 @@ pub trait PaymentProcessingTrait {
     fn get_payer_payment_history(
         env: Env,
         payer: Address,
-        cursor: Option<String>,
+        cursor: Option<PaymentKey>,
         limit: u32,
         filter: Option<PaymentQueryFilter>,
         sort_field: Option<SortField>,
@@ -202,6 +593,7 @@ pub trait PaymentProcessingTrait {
     fn get_payment_by_id(
         env: Env,
         caller: Address,
+        merchant: Address,
         order_id: String,
     ) -> Result<PaymentRecord, PaymentError>;
 
@@ -216,6 +608,7 @@ pub trait PaymentProcessingTrait {
     fn update_payment_status(
         env: Env,
         caller: Address,
+        merchant: Address,
         order_id: String,
         refunded_amount: i128,
     ) -> Result<(), PaymentError>;
@@ -223,6 +616,7 @@ pub trait PaymentProcessingTrait {
     fn archive_payment_record(
         env: Env,
         admin: Address,
+        merchant: Address,
         order_id: String,
     ) -> Result<(), PaymentError>;
 
@@ -240,75 +634,453 @@ pub struct PaymentProcessingContract;
 
 #[contractimpl]
 impl PaymentProcessingTrait for PaymentProcessingContract {
+    // Role Management Operations
     fn set_admin(env: Env, admin: Address) -> Result<(), PaymentError> {
         let storage = Storage::new(&env);
 
-        if let Some(current_admin) = storage.get_admin() {
-            // Existing admin must authorize the change
-            current_admin.require_auth();
-        } else {
-            // First-time setup: new admin must authorize themselves
-            admin.require_auth();
+        // First-time setup only: later changes go through the two-step handover
+        if storage.get_admin().is_some() {
+            return Err(PaymentError::NotAuthorized);
         }
+        admin.require_auth();
         storage.set_admin(&admin);
+
+        env.events().publish(
+            (role_topic(&env), Symbol::new(&env, "super_admin_set")),
+            admin,
+        );
+
         Ok(())
     }
 
-    fn set_fee(
+    fn grant_role(
         env: Env,
-        fee_rate: u64,
-        fee_collector: Address,
-        fee_token: Address,
+        admin: Address,
+        account: Address,
+        role: Role,
     ) -> Result<(), PaymentError> {
+        Self::require_admin_access(&env, &admin)?;
+
         let storage = Storage::new(&env);
-        let fee = Fee {
-            fee_rate,
-            fee_collector,
-            fee_token,
-        };
-        let admin = storage.get_admin().ok_or(PaymentError::AdminNotFound)?;
-        admin.require_auth();
-        storage.set_fee_info(&fee, &admin)?;
-        Ok(())
+        Self::require_no_council(&storage)?;
+        Self::apply_grant_role(&env, &storage, account, role, admin)
     }
 
-    fn get_fee_info(env: Env) -> Result<(u64, Address, Address), PaymentError> {
+    fn revoke_role(
+        env: Env,
+        admin: Address,
+        account: Address,
+        role: Role,
+    ) -> Result<(), PaymentError> {
+        Self::require_admin_access(&env, &admin)?;
+
         let storage = Storage::new(&env);
-        let rate = storage.get_fee_rate();
-        let collector = storage
-            .get_fee_collector()
-            .ok_or(PaymentError::AdminNotFound)?;
-        let token = storage.get_fee_token().ok_or(PaymentError::InvalidToken)?;
-        Ok((rate, collector, token))
+        Self::require_no_council(&storage)?;
+        Self::apply_revoke_role(&env, &storage, account, role, admin)
     }
 
-    fn register_merchant(
+    fn has_role(env: Env, account: Address, role: Role) -> bool {
+        Self::holds_role(&Storage::new(&env), &account, role)
+    }
+
+    fn get_role_members(env: Env, role: Role) -> Vec<Address> {
+        Storage::new(&env).get_role_members(role)
+    }
+
+    fn propose_super_admin(
         env: Env,
-        merchant_address: Address,
-        name: String,
-        description: String,
-        contact_info: String,
-        category: MerchantCategory,
+        admin: Address,
+        new_admin: Address,
     ) -> Result<(), PaymentError> {
-        if Self::is_paused(&env) {
-            return Err(PaymentError::ContractPaused);
-        }
-        // Verify authorization
-        merchant_address.require_auth();
+        Self::require_admin_access(&env, &admin)?;
 
         let storage = Storage::new(&env);
+        Self::require_no_council(&storage)?;
+        Self::apply_propose_super_admin(&env, &storage, new_admin, admin);
 
-        // Check if merchant already exists
-        if storage.merchant_exists(&merchant_address) {
-            return Err(PaymentError::MerchantAlreadyExists);
-        }
+        Ok(())
+    }
 
-        // Validate profile data
-        validate_name(&name)?;
-        validate_description(&description)?;
-        validate_contact_info(&contact_info)?;
+    fn accept_super_admin(env: Env, new_admin: Address) -> Result<(), PaymentError> {
+        new_admin.require_auth();
 
-        let current_time = env.ledger().timestamp();
+        let storage = Storage::new(&env);
+        let handover = storage
+            .get_pending_super_admin()
+            .ok_or(PaymentError::NotAuthorized)?;
+        if handover.new_admin != new_admin {
+            return Err(PaymentError::NotAuthorized);
+        }
+        if env.ledger().timestamp() > handover.expires_at {
+            return Err(PaymentError::PaymentExpired);
+        }
+        let previous = storage.get_admin().ok_or(PaymentError::AdminNotFound)?;
+
+        storage.set_admin(&new_admin);
+        storage.set_pending_super_admin(&None);
+
+        env.events().publish(
+            (role_topic(&env), Symbol::new(&env, "super_admin_accepted")),
+            (previous, new_admin),
+        );
+
+        Ok(())
+    }
+
+    fn get_pending_super_admin(env: Env) -> Option<AdminHandover> {
+        Storage::new(&env).get_pending_super_admin()
+    }
+
+    // Admin Council Operations
+    fn set_admin_council(
+        env: Env,
+        admin: Address,
+        council: AdminCouncil,
+    ) -> Result<(), PaymentError> {
+        Self::require_admin_access(&env, &admin)?;
+
+        // Once in place, the council changes only through its own proposals
+        let storage = Storage::new(&env);
+        Self::require_no_council(&storage)?;
+        validate_admin_council(&council)?;
+        storage.set_admin_council(&Some(council.clone()));
+
+        env.events().publish(
+            (council_topic(&env), Symbol::new(&env, "configured")),
+            council,
+        );
+
+        Ok(())
+    }
+
+    fn get_admin_council(env: Env) -> Option<AdminCouncil> {
+        Storage::new(&env).get_admin_council()
+    }
+
+    fn propose_council_action(
+        env: Env,
+        member: Address,
+        action: CouncilAction,
+    ) -> Result<u64, PaymentError> {
+        member.require_auth();
+
+        let storage = Storage::new(&env);
+        let council = storage
+            .get_admin_council()
+            .ok_or(PaymentError::InvalidStatus)?;
+        if !council.members.contains(&member) {
+            return Err(PaymentError::NotASigner);
+        }
+        if let CouncilAction::UpdateCouncil(new_council) = &action {
+            validate_admin_council(new_council)?;
+        }
+
+        let now = env.ledger().timestamp();
+        let mut proposal = CouncilProposal {
+            id: storage.next_council_proposal_id(),
+            action,
+            proposer: member.clone(),
+            approvals: Vec::from_array(&env, [member.clone()]),
+            created_at: now,
+            expires_at: now.saturating_add(COUNCIL_PROPOSAL_WINDOW),
+            executed: false,
+        };
+
+        env.events().publish(
+            (council_topic(&env), Symbol::new(&env, "proposed")),
+            (proposal.id, member.clone(), proposal.action.clone()),
+        );
+
+        // The proposer's approval counts, so a 1-of-N council acts right away
+        Self::execute_if_approved(&env, &storage, &council, &mut proposal, &member)?;
+        storage.save_council_proposal(&proposal);
+
+        Ok(proposal.id)
+    }
+
+    fn approve_council_action(
+        env: Env,
+        member: Address,
+        proposal_id: u64,
+    ) -> Result<bool, PaymentError> {
+        member.require_auth();
+
+        let storage = Storage::new(&env);
+        let council = storage
+            .get_admin_council()
+            .ok_or(PaymentError::InvalidStatus)?;
+        if !council.members.contains(&member) {
+            return Err(PaymentError::NotASigner);
+        }
+
+        let mut proposal = storage.get_council_proposal(proposal_id)?;
+        if proposal.executed {
+            return Err(PaymentError::AlreadyExecuted);
+        }
+        if env.ledger().timestamp() > proposal.expires_at {
+            return Err(PaymentError::PaymentExpired);
+        }
+        if proposal.approvals.contains(&member) {
+            return Err(PaymentError::AlreadySigned);
+        }
+        proposal.approvals.push_back(member.clone());
+
+        env.events().publish(
+            (council_topic(&env), Symbol::new(&env, "approved")),
+            (proposal_id, member.clone()),
+        );
+
+        Self::execute_if_approved(&env, &storage, &council, &mut proposal, &member)?;
+        storage.save_council_proposal(&proposal);
+
+        Ok(proposal.executed)
+    }
+
+    fn get_council_proposal(env: Env, proposal_id: u64) -> Result<CouncilProposal, PaymentError> {
+        Storage::new(&env).get_council_proposal(proposal_id)
+    }
+
+    fn get_open_council_proposals(env: Env) -> Vec<CouncilProposal> {
+        let now = env.ledger().timestamp();
+        let mut open = Vec::new(&env);
+        for (_, proposal) in Storage::new(&env).get_council_proposals_map().iter() {
+            if !proposal.executed && now <= proposal.expires_at {
+                open.push_back(proposal);
+            }
+        }
+        open
+    }
+
+    // Fee Management Operations
+
+    fn set_fee(
+        env: Env,
+        admin: Address,
+        fee_rate_bps: u32,
+        fee_collector: Address,
+        fee_token: Address,
+    ) -> Result<(), PaymentError> {
+        Self::require_role(&env, &admin, Role::FeeManager)?;
+
+        let storage = Storage::new(&env);
+        Self::require_no_council(&storage)?;
+        Self::apply_set_fee(&storage, fee_rate_bps, fee_collector, fee_token)
+    }
+
+    fn get_fee_info(env: Env) -> Result<(u64, Address, Address), PaymentError> {
+        let storage = Storage::new(&env);
+        let rate = storage
+            .get_fee_schedule()
+            .map(|schedule| u64::from(schedule.rate_bps))
+            .unwrap_or(0);
+        let collector = storage
+            .get_fee_collector()
+            .ok_or(PaymentError::AdminNotFound)?;
+        let token = storage.get_fee_token().ok_or(PaymentError::InvalidToken)?;
+        Ok((rate, collector, token))
+    }
+
+    fn set_fee_schedule(
+        env: Env,
+        admin: Address,
+        schedule: FeeSchedule,
+    ) -> Result<(), PaymentError> {
+        Self::require_role(&env, &admin, Role::FeeManager)?;
+
+        let storage = Storage::new(&env);
+        Self::require_no_council(&storage)?;
+        Self::apply_fee_schedule(&storage, schedule)
+    }
+
+    fn set_fee_override(
+        env: Env,
+        admin: Address,
+        scope: FeeScope,
+        schedule: Option<FeeSchedule>,
+    ) -> Result<(), PaymentError> {
+        Self::require_role(&env, &admin, Role::FeeManager)?;
+
+        let storage = Storage::new(&env);
+        Self::require_no_council(&storage)?;
+        Self::apply_fee_override(&env, &storage, scope, schedule)
+    }
+
+    fn get_fee_override(env: Env, scope: FeeScope) -> Option<FeeSchedule> {
+        Storage::new(&env).get_fee_override(&scope)
+    }
+
+    fn get_effective_fee_schedule(
+        env: Env,
+        merchant: Address,
+        token: Address,
+    ) -> Result<FeeSchedule, PaymentError> {
+        let storage = Storage::new(&env);
+        let merchant = storage.get_merchant(&merchant)?;
+        Ok(storage.resolve_fee_schedule(&merchant, &token))
+    }
+
+    fn set_token_fee(
+        env: Env,
+        admin: Address,
+        token: Address,
+        fee_collector: Address,
+        schedule: FeeSchedule,
+    ) -> Result<(), PaymentError> {
+        Self::require_role(&env, &admin, Role::FeeManager)?;
+
+        let storage = Storage::new(&env);
+        Self::require_no_council(&storage)?;
+        Self::apply_token_fee(&env, &storage, token, fee_collector, schedule)
+    }
+
+    fn remove_token_fee(env: Env, admin: Address, token: Address) -> Result<(), PaymentError> {
+        Self::require_role(&env, &admin, Role::FeeManager)?;
+
+        let storage = Storage::new(&env);
+        Self::require_no_council(&storage)?;
+        Self::apply_remove_token_fee(&env, &storage, token);
+
+        Ok(())
+    }
+
+    fn get_token_fee_info(env: Env, token: Address) -> Result<TokenFeeInfo, PaymentError> {
+        Storage::new(&env)
+            .get_token_fee_info(&token)
+            .ok_or(PaymentError::AdminNotFound)
+    }
+
+    fn set_fee_custody(env: Env, admin: Address, enabled: bool) -> Result<(), PaymentError> {
+        Self::require_role(&env, &admin, Role::FeeManager)?;
+
+        let storage = Storage::new(&env);
+        Self::require_no_council(&storage)?;
+        Self::apply_fee_custody(&env, &storage, enabled);
+
+        Ok(())
+    }
+
+    fn is_fee_custody_enabled(env: Env) -> bool {
+        Storage::new(&env).is_fee_custody_enabled()
+    }
+
+    fn set_fee_beneficiaries(
+        env: Env,
+        admin: Address,
+        beneficiaries: Vec<FeeBeneficiary>,
+    ) -> Result<(), PaymentError> {
+        Self::require_role(&env, &admin, Role::FeeManager)?;
+
+        let storage = Storage::new(&env);
+        Self::require_no_council(&storage)?;
+        Self::apply_fee_beneficiaries(&env, &storage, beneficiaries)
+    }
+
+    fn get_fee_beneficiaries(env: Env) -> Vec<FeeBeneficiary> {
+        Storage::new(&env).get_fee_beneficiaries()
+    }
+
+    fn withdraw_fees(env: Env, beneficiary: Address, token: Address) -> Result<i128, PaymentError> {
+        beneficiary.require_auth();
+
+        let storage = Storage::new(&env);
+        let amount = storage.get_fee_balance(&beneficiary, &token);
+        if amount <= 0 {
+            return Err(PaymentError::InsufficientBalance);
+        }
+
+        // Update the ledger before transferring out
+        storage.adjust_fee_balance(&beneficiary, &token, -amount);
+        token::Client::new(&env, &token).transfer(
+            &env.current_contract_address(),
+            &beneficiary,
+            &amount,
+        );
+
+        env.events()
+            .publish(("fees_withdrawn",), (beneficiary, token, amount));
+
+        Ok(amount)
+    }
+
+    fn get_fee_balance(env: Env, beneficiary: Address, token: Address) -> i128 {
+        Storage::new(&env).get_fee_balance(&beneficiary, &token)
+    }
+
+    fn get_fee_balances(env: Env, beneficiary: Address) -> Map<Address, i128> {
+        Storage::new(&env).get_fee_balances(&beneficiary)
+    }
+
+    fn get_accrued_fees(env: Env, token: Address) -> i128 {
+        Storage::new(&env).get_accrued_fees(&token)
+    }
+
+    fn set_merchant_fee_mode(
+        env: Env,
+        merchant: Address,
+        fee_mode: FeeMode,
+    ) -> Result<(), PaymentError> {
+        merchant.require_auth();
+
+        let storage = Storage::new(&env);
+        storage.get_merchant(&merchant)?;
+        storage.set_fee_mode(&merchant, &fee_mode);
+
+        env.events()
+            .publish(("merchant_fee_mode_set",), (merchant, fee_mode));
+
+        Ok(())
+    }
+
+    fn get_merchant_fee_mode(env: Env, merchant: Address) -> FeeMode {
+        Storage::new(&env).get_fee_mode(&merchant)
+    }
+
+    fn set_legacy_signature_deadline(
+        env: Env,
+        admin: Address,
+        deadline: u64,
+    ) -> Result<(), PaymentError> {
+        Self::require_admin_access(&env, &admin)?;
+
+        let storage = Storage::new(&env);
+        Self::require_no_council(&storage)?;
+        Self::apply_legacy_signature_deadline(&env, &storage, deadline, admin);
+
+        Ok(())
+    }
+
+    fn get_legacy_signature_deadline(env: Env) -> Option<u64> {
+        let storage = Storage::new(&env);
+        storage.get_legacy_signature_deadline()
+    }
+
+    fn register_merchant(
+        env: Env,
+        merchant_address: Address,
+        name: String,
+        description: String,
+        contact_info: String,
+        category: MerchantCategory,
+    ) -> Result<(), PaymentError> {
+        if Self::is_paused(&env) {
+            return Err(PaymentError::ContractPaused);
+        }
+        // Verify authorization
+        merchant_address.require_auth();
+
+        let storage = Storage::new(&env);
+
+        // Check if merchant already exists
+        if storage.merchant_exists(&merchant_address) {
+            return Err(PaymentError::MerchantAlreadyExists);
+        }
+
+        // Validate profile data
+        validate_name(&name)?;
+        validate_description(&description)?;
+        validate_contact_info(&contact_info)?;
+
+        let current_time = env.ledger().timestamp();
 
         // Create new merchant record
         let merchant = Merchant {
@@ -322,6 +1094,7 @@ impl PaymentProcessingTrait for PaymentProcessingContract {
             last_activity_timestamp: current_time,
             category: category.clone(),
             max_transaction_limit: DEFAULT_TRANSACTION_LIMIT,
+            token_limits: Map::new(&env),
         };
 
         storage.save_merchant(&merchant_address, &merchant);
@@ -428,6 +1201,7 @@ impl PaymentProcessingTrait for PaymentProcessingContract {
         env: Env,
         merchant: Address,
         max_transaction_limit: i128,
+        token_limits: Map<Address, i128>,
     ) -> Result<(), PaymentError> {
         // Verify authorization - only merchant can set their own limits
         merchant.require_auth();
@@ -440,10 +1214,14 @@ impl PaymentProcessingTrait for PaymentProcessingContract {
             return Err(PaymentError::MerchantInactive);
         }
 
-        // Validate transaction limit
+        // Validate transaction limits
         validate_transaction_limit(max_transaction_limit)?;
+        for (_, limit) in token_limits.iter() {
+            validate_transaction_limit(limit)?;
+        }
 
         merchant_data.max_transaction_limit = max_transaction_limit;
+        merchant_data.token_limits = token_limits.clone();
 
         // Update last activity timestamp
         let current_time = env.ledger().timestamp();
@@ -457,6 +1235,7 @@ impl PaymentProcessingTrait for PaymentProcessingContract {
             LimitsUpdatedEvent {
                 merchant,
                 max_transaction_limit,
+                token_limits,
                 timestamp: current_time,
             },
         );
@@ -464,6 +1243,21 @@ impl PaymentProcessingTrait for PaymentProcessingContract {
         Ok(())
     }
 
+    fn get_transaction_limit(
+        env: Env,
+        merchant: Address,
+        token: Address,
+    ) -> Result<i128, PaymentError> {
+        let storage = Storage::new(&env);
+        let merchant_data = storage.get_merchant(&merchant)?;
+        Ok(Self::transaction_limit(
+            &env,
+            &storage,
+            &merchant_data,
+            &token,
+        ))
+    }
+
     fn deactivate_merchant(env: Env, merchant: Address) -> Result<(), PaymentError> {
         // Verify authorization - only merchant can deactivate their own account
         merchant.require_auth();
@@ -492,114 +1286,230 @@ impl PaymentProcessingTrait for PaymentProcessingContract {
         Ok(())
     }
 
-    fn process_payment_with_signature(
+    fn register_signing_key(
         env: Env,
-        payer: Address,
-        order: PaymentOrder,
-        _signature: BytesN<64>,
-        _merchant_public_key: BytesN<32>,
+        merchant: Address,
+        public_key: BytesN<32>,
+        valid_from: Option<u64>,
+        valid_until: Option<u64>,
     ) -> Result<(), PaymentError> {
+        // Verify authorization - only merchant can register their own keys
+        merchant.require_auth();
+
         if Self::is_paused(&env) {
             return Err(PaymentError::ContractPaused);
         }
 
-        // Verify authorization from payer
-        payer.require_auth();
-
-        // Verify the order hasn't expired (optimized timestamp check)
-        if env.ledger().timestamp() > order.expiration as u64 {
-            return Err(PaymentError::OrderExpired);
-        }
-
         let storage = Storage::new(&env);
-
-        // Verify merchant exists and is active
-        let mut merchant = storage.get_merchant(&order.merchant_address)?;
-        if !merchant.active {
+        let merchant_data = storage.get_merchant(&merchant)?;
+        if !merchant_data.active {
             return Err(PaymentError::MerchantInactive);
         }
 
-        // Verify token is supported by merchant (optimized lookup)
-        if !merchant.supports_token(&order.token) {
-            return Err(PaymentError::InvalidToken);
-        }
+        let current_time = env.ledger().timestamp();
+        let valid_from = valid_from.unwrap_or(current_time);
+        validate_key_validity(valid_from, valid_until)?;
+
+        let mut keys = Self::prune_signing_keys(&env, storage.get_signing_keys(&merchant));
+        Self::push_signing_key(
+            &storage,
+            &merchant,
+            &mut keys,
+            MerchantSigningKey {
+                public_key: public_key.clone(),
+                registered_at: current_time,
+                valid_from,
+                valid_until,
+                revoked_at: None,
+            },
+        )?;
+        storage.save_signing_keys(&merchant, &keys);
 
-        // Verify transaction limit
-        if i128::from(order.amount) > merchant.max_transaction_limit {
-            return Err(PaymentError::TransactionLimitExceeded);
-        }
+        env.events().publish(
+            (signing_key_topic(&env), Symbol::new(&env, "registered")),
+            SigningKeyRegisteredEvent {
+                merchant,
+                public_key,
+                valid_from,
+                valid_until,
+                timestamp: current_time,
+            },
+        );
 
-        // Verify the nonce hasn't been used
-        if storage.is_nonce_used(&order.merchant_address, order.nonce) {
-            return Err(PaymentError::NonceAlreadyUsed);
-        }
+        Ok(())
+    }
 
-        // Optimized message construction using pre-allocated bytes
-        let _message = create_optimized_message(&env, &order);
-        // Verify signature
-        #[cfg(not(test))]
-        env.crypto()
-            .ed25519_verify(&_merchant_public_key, &_message, &_signature);
+    fn rotate_signing_key(
+        env: Env,
+        merchant: Address,
+        old_key: BytesN<32>,
+        new_key: BytesN<32>,
+        grace_period: u64,
+    ) -> Result<(), PaymentError> {
+        merchant.require_auth();
 
-        // Get fee information
-        let fee_collector = storage
-            .get_fee_collector()
-            .ok_or(PaymentError::AdminNotFound)?;
+        if Self::is_paused(&env) {
+            return Err(PaymentError::ContractPaused);
+        }
+
+        let storage = Storage::new(&env);
+        let merchant_data = storage.get_merchant(&merchant)?;
+        if !merchant_data.active {
+            return Err(PaymentError::MerchantInactive);
+        }
 
-        let fee_token = storage.get_fee_token().ok_or(PaymentError::InvalidToken)?;
+        // Old key must currently be usable to be rotated out
+        storage.require_active_signing_key(&merchant, &old_key)?;
 
-        // Ensure fee token matches payment token
-        if fee_token != order.token {
-            return Err(PaymentError::InvalidToken);
+        let current_time = env.ledger().timestamp();
+        let grace_until = current_time.saturating_add(grace_period);
+
+        let mut keys = Self::prune_signing_keys(&env, storage.get_signing_keys(&merchant));
+        let mut old_key_valid_until = grace_until;
+        for i in 0..keys.len() {
+            let mut key = keys.get(i).unwrap();
+            if key.public_key == old_key {
+                // Never extend an existing expiry through rotation
+                old_key_valid_until = match key.valid_until {
+                    Some(until) if until < grace_until => until,
+                    _ => grace_until,
+                };
+                key.valid_until = Some(old_key_valid_until);
+                keys.set(i, key);
+                break;
+            }
         }
 
-        let fee_amount = storage.calculate_fee(order.amount as i128);
+        Self::push_signing_key(
+            &storage,
+            &merchant,
+            &mut keys,
+            MerchantSigningKey {
+                public_key: new_key.clone(),
+                registered_at: current_time,
+                valid_from: current_time,
+                valid_until: None,
+                revoked_at: None,
+            },
+        )?;
+        storage.save_signing_keys(&merchant, &keys);
 
-        if fee_amount < 0 {
-            return Err(PaymentError::InvalidAmount);
+        env.events().publish(
+            (signing_key_topic(&env), Symbol::new(&env, "rotated")),
+            SigningKeyRotatedEvent {
+                merchant,
+                old_key,
+                new_key,
+                old_key_valid_until,
+                timestamp: current_time,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn revoke_signing_key(
+        env: Env,
+        merchant: Address,
+        public_key: BytesN<32>,
+    ) -> Result<(), PaymentError> {
+        // Revocation is allowed while paused or inactive so compromised keys can always be disabled
+        merchant.require_auth();
+
+        let storage = Storage::new(&env);
+        storage.get_merchant(&merchant)?;
+
+        let current_time = env.ledger().timestamp();
+        let mut keys = storage.get_signing_keys(&merchant);
+        let mut found = false;
+        for i in 0..keys.len() {
+            let mut key = keys.get(i).unwrap();
+            if key.public_key == public_key {
+                if key.revoked_at.is_some() {
+                    return Err(PaymentError::SigningKeyInactive);
+                }
+                key.revoked_at = Some(current_time);
+                keys.set(i, key);
+                found = true;
+                break;
+            }
         }
-        let merchant_amount = (order.amount as i128) - fee_amount;
 
-        // Process the payment using Stellar token contract
-        let payment_token_client = token::Client::new(&env, &order.token);
+        if !found {
+            return Err(PaymentError::SigningKeyNotFound);
+        }
+        storage.save_signing_keys(&merchant, &keys);
+        storage.mark_signing_key_revoked(&merchant, &public_key);
 
-        // Transfer merchant amount first
-        payment_token_client.transfer(&payer, &order.merchant_address, &merchant_amount);
+        env.events().publish(
+            (signing_key_topic(&env), Symbol::new(&env, "revoked")),
+            SigningKeyRevokedEvent {
+                merchant,
+                public_key,
+                timestamp: current_time,
+            },
+        );
 
-        // Then transfer fee if applicable
-        if fee_amount > 0 {
-            let fee_token_client = token::Client::new(&env, &fee_token);
-            fee_token_client.transfer(&payer, &fee_collector, &fee_amount);
-            env.events().publish(
-                ("fee_collected",),
-                (fee_collector.clone(), fee_amount, order.order_id.clone()),
-            );
+        Ok(())
+    }
+
+    fn get_signing_keys(
+        env: Env,
+        merchant: Address,
+    ) -> Result<Vec<MerchantSigningKey>, PaymentError> {
+        let storage = Storage::new(&env);
+        storage.get_merchant(&merchant)?;
+        Ok(storage.get_signing_keys(&merchant))
+    }
+
+    fn process_payment_with_signature(
+        env: Env,
+        payer: Address,
+        order: PaymentOrder,
+        signature: BytesN<64>,
+        merchant_public_key: BytesN<32>,
+    ) -> Result<PaymentRecord, PaymentError> {
+        if Self::is_paused(&env) {
+            return Err(PaymentError::ContractPaused);
         }
 
-        // Record used nonce (optimized bitmap storage)
-        storage.mark_nonce_used(&order.merchant_address, order.nonce);
+        // Verify authorization from payer
+        payer.require_auth();
 
-        // Update merchant's last activity timestamp
-        merchant.last_activity_timestamp = env.ledger().timestamp();
-        storage.save_merchant(&order.merchant_address, &merchant);
+        let storage = Storage::new(&env);
+        Self::settle_order(
+            &env,
+            &storage,
+            &payer,
+            &order,
+            &merchant_public_key,
+            Some(&signature),
+            false,
+        )
+    }
 
-        // Record payment history
-        let payment_record = PaymentRecord {
-            order_id: order.order_id.clone(),
-            merchant_address: order.merchant_address.clone(),
-            payer_address: payer.clone(),
-            token: order.token.clone(),
-            amount: order.amount as i128,
-            paid_at: env.ledger().timestamp(),
-            refunded_amount: 0,
+    fn get_order_status(
+        env: Env,
+        merchant: Address,
+        order_id: String,
+    ) -> Result<OrderStatus, PaymentError> {
+        let storage = Storage::new(&env);
+        storage.get_merchant(&merchant)?;
+
+        let (record, archived) = match storage.find_order_payment(&merchant, &order_id) {
+            Some(found) => found,
+            None => return Ok(OrderStatus::Unpaid),
         };
-        storage.save_payment(&payment_record);
 
-        // Maintain payment indices for efficient querying
-        storage.save_merchant_payment_index(&order.merchant_address, &order.order_id);
-        storage.save_payer_payment_index(&payer, &order.order_id);
+        if archived {
+            return Ok(OrderStatus::Expired);
+        }
 
-        Ok(())
+        Ok(match record.get_status() {
+            PaymentRecordStatus::FullyRefunded => OrderStatus::Refunded,
+            PaymentRecordStatus::PartiallyRefunded => OrderStatus::PartiallyRefunded,
+            _ => OrderStatus::Paid,
+        })
     }
 
     // Batch Operations for Gas Optimization
@@ -625,7 +1535,8 @@ impl PaymentProcessingTrait for PaymentProcessingContract {
                 registration_timestamp: env.ledger().timestamp(),
                 last_activity_timestamp: env.ledger().timestamp(),
                 category: MerchantCategory::Other,
-                max_transaction_limit: 1000000, // Default limit
+                max_transaction_limit: DEFAULT_TRANSACTION_LIMIT,
+                token_limits: Map::new(&env),
             };
             storage.save_merchant(&merchant_address, &merchant);
         }
@@ -661,7 +1572,10 @@ impl PaymentProcessingTrait for PaymentProcessingContract {
         Ok(())
     }
 
-    fn batch_process_payments(env: Env, batch: BatchPayment) -> Result<(), PaymentError> {
+    fn batch_process_payments(
+        env: Env,
+        batch: BatchPayment,
+    ) -> Result<Vec<BatchOrderResult>, PaymentError> {
         if Self::is_paused(&env) {
             return Err(PaymentError::ContractPaused);
         }
@@ -669,88 +1583,99 @@ impl PaymentProcessingTrait for PaymentProcessingContract {
 
         let storage = Storage::new(&env);
 
-        // Validate signature count matches order count
-        if batch.signatures.len() != batch.orders.len() {
-            return Err(PaymentError::InvalidSignature);
-        }
-
-        let mut seen: Map<Address, Vec<u32>> = Map::new(&env);
-
-        for order in batch.orders.iter() {
-            if env.ledger().timestamp() > order.expiration as u64 {
-                return Err(PaymentError::OrderExpired);
-            }
-
-            let merchant = storage.get_merchant(&order.merchant_address)?;
-            if !merchant.active {
-                return Err(PaymentError::MerchantNotFound);
-            }
-
-            if !merchant.supports_token(&order.token) {
-                return Err(PaymentError::InvalidToken);
+        // Signatures are verified before any order settles. A bad signature traps, which
+        // aborts the whole batch even in best-effort mode.
+        let verified = match &batch.signatures {
+            BatchSignatures::PerOrder(signatures) => {
+                if signatures.len() != batch.orders.len() {
+                    return Err(PaymentError::InvalidSignature);
+                }
+                Self::verify_batch_order_signatures(&env, &storage, &batch, signatures)?
             }
-
-            if storage.is_nonce_used(&order.merchant_address, order.nonce) {
-                return Err(PaymentError::NonceAlreadyUsed);
+            BatchSignatures::PerMerchant(signatures) => {
+                Self::verify_batch_digests(&env, &storage, &batch, signatures)?;
+                Vec::new(&env)
             }
+        };
 
-            let mut merchant_nonces = seen
-                .get(order.merchant_address.clone())
-                .unwrap_or(Vec::new(&env));
-            if merchant_nonces.iter().any(|n| n == order.nonce) {
-                return Err(PaymentError::NonceAlreadyUsed);
-            }
-            merchant_nonces.push_back(order.nonce);
-            seen.set(order.merchant_address.clone(), merchant_nonces);
-        }
+        let mut results = Vec::new(&env);
+        let mut seen_orders: Map<PaymentKey, bool> = Map::new(&env);
+        let mut settled = 0u32;
 
         for (idx, order) in batch.orders.iter().enumerate() {
-            let message = create_optimized_message(&env, &order);
-
-            #[cfg(not(test))]
-            {
-                let sig = batch
-                    .signatures
-                    .get(idx as u32)
-                    .ok_or(PaymentError::InvalidSignature)?;
-                env.crypto()
-                    .ed25519_verify(&batch.merchant_public_key, &message, &sig);
-            }
+            // Orders verified up front aren't verified again; the rest fail a cheap check first
+            let signature = match &batch.signatures {
+                BatchSignatures::PerOrder(signatures)
+                    if !verified.get(idx as u32).unwrap_or(false) =>
+                {
+                    Some(
+                        signatures
+                            .get(idx as u32)
+                            .ok_or(PaymentError::InvalidSignature)?,
+                    )
+                }
+                _ => None,
+            };
 
-            let token_client = token::Client::new(&env, &order.token);
-            token_client.transfer(
-                &batch.payer,
-                &order.merchant_address,
-                &(order.amount as i128),
-            );
-        }
+            // Repeating an order ID inside one batch is never an idempotent retry
+            let key = (order.merchant_address.clone(), order.order_id.clone());
+            let outcome = if seen_orders.contains_key(key.clone()) {
+                Err(PaymentError::DuplicateOrderId)
+            } else {
+                seen_orders.set(key, true);
+                match batch
+                    .merchant_public_keys
+                    .get(order.merchant_address.clone())
+                {
+                    Some(merchant_public_key) => Self::settle_order(
+                        &env,
+                        &storage,
+                        &batch.payer,
+                        &order,
+                        &merchant_public_key,
+                        signature.as_ref(),
+                        false,
+                    ),
+                    None => Err(PaymentError::SigningKeyNotFound),
+                }
+            };
 
-        for (merchant, nonces) in seen.iter() {
-            // Convert Soroban Vec to standard Vec for batch operation - no hardcoded limit
-            let mut nonces_vec = Vec::new(&env);
-            for nonce in nonces.iter() {
-                nonces_vec.push_back(nonce);
-            }
-            // Convert to array for batch operation
-            let mut nonces_array = [0u32; 1000]; // Increased limit to 1000
-            let mut i = 0;
-            for nonce in nonces_vec.iter() {
-                if i < 1000 {
-                    nonces_array[i] = nonce;
-                    i += 1;
+            match outcome {
+                Ok(_) => {
+                    settled += 1;
+                    results.push_back(BatchOrderResult {
+                        order_id: order.order_id.clone(),
+                        success: true,
+                        error_code: None,
+                    });
+                }
+                Err(e) => {
+                    if batch.mode == BatchMode::Atomic {
+                        return Err(e);
+                    }
+                    log!(
+                        &env,
+                        "BatchPayment: order_id={} failed with error={:?}",
+                        order.order_id,
+                        e
+                    );
+                    results.push_back(BatchOrderResult {
+                        order_id: order.order_id.clone(),
+                        success: false,
+                        error_code: Some(e as u32),
+                    });
                 }
             }
-
-            storage.batch_mark_nonces_used(&merchant, &nonces_array[..i]);
         }
 
         log!(
             &env,
             "payments_batch_processed",
             batch.payer,
-            batch.orders.len()
+            batch.orders.len(),
+            settled
         );
-        Ok(())
+        Ok(results)
     }
 
     // Gas Estimation Functions
@@ -1142,22 +2067,33 @@ impl PaymentProcessingTrait for PaymentProcessingContract {
 
     // Pause Management Operations
     fn set_pause_admin(env: Env, admin: Address, new_admin: Address) -> Result<(), PaymentError> {
-        admin.require_auth();
+        // Kept for existing integrations: makes `new_admin` the only pause guardian, as the
+        // single pause admin it replaces was
+        Self::require_admin_access(&env, &admin)?;
+
         let storage = Storage::new(&env);
-        let _ = storage.set_pause_admin_internal(admin, new_admin);
+        Self::require_no_council(&storage)?;
+        for guardian in storage.get_role_members(Role::PauseGuardian).iter() {
+            if guardian != new_admin {
+                Self::apply_revoke_role(
+                    &env,
+                    &storage,
+                    guardian,
+                    Role::PauseGuardian,
+                    admin.clone(),
+                )?;
+            }
+        }
+        if !storage.has_role(Role::PauseGuardian, &new_admin) {
+            Self::apply_grant_role(&env, &storage, new_admin, Role::PauseGuardian, admin)?;
+        }
+
         Ok(())
     }
 
     fn pause(env: Env, admin: Address) -> Result<(), PaymentError> {
-        admin.require_auth();
+        Self::require_role(&env, &admin, Role::PauseGuardian)?;
         let storage = Storage::new(&env);
-        let pause_admin = storage
-            .get_pause_admin()
-            .unwrap_or_else(|_| panic_with_error!(env, PaymentError::AdminNotFound));
-
-        if pause_admin != admin {
-            return Err(PaymentError::NotAuthorized);
-        }
         if Self::is_paused(&env) {
             return Err(PaymentError::AlreadyPaused);
         }
@@ -1170,15 +2106,8 @@ impl PaymentProcessingTrait for PaymentProcessingContract {
     }
 
     fn pause_for_duration(env: Env, admin: Address, duration: u64) -> Result<(), PaymentError> {
-        admin.require_auth();
+        Self::require_role(&env, &admin, Role::PauseGuardian)?;
         let storage = Storage::new(&env);
-        let pause_admin = storage
-            .get_pause_admin()
-            .unwrap_or_else(|_| panic_with_error!(env, PaymentError::AdminNotFound));
-
-        if pause_admin != admin {
-            return Err(PaymentError::NotAuthorized);
-        }
         if Self::is_paused(&env) {
             return Err(PaymentError::AlreadyPaused);
         }
@@ -1193,15 +2122,8 @@ impl PaymentProcessingTrait for PaymentProcessingContract {
     }
 
     fn unpause(env: Env, admin: Address) -> Result<(), PaymentError> {
-        admin.require_auth();
+        Self::require_role(&env, &admin, Role::PauseGuardian)?;
         let storage = Storage::new(&env);
-        let pause_admin = storage
-            .get_pause_admin()
-            .unwrap_or_else(|_| panic_with_error!(env, PaymentError::AdminNotFound));
-
-        if pause_admin != admin {
-            return Err(PaymentError::NotAuthorized);
-        }
         storage.set_unpause();
         storage.set_pause_until(0);
         env.events().publish(
@@ -1216,496 +2138,3384 @@ impl PaymentProcessingTrait for PaymentProcessingContract {
         storage.is_paused()
     }
 
-    // Refund Management Operations
-    fn initiate_refund(
+    // Escrow Operations
+    fn set_merchant_escrow(
         env: Env,
-        caller: Address,
-        refund_id: String,
-        order_id: String,
-        amount: i128,
-        reason: String,
+        admin: Address,
+        merchant: Address,
+        release_timeout: Option<u64>,
     ) -> Result<(), PaymentError> {
-        // Caller may be merchant or payer; require auth
-        caller.require_auth();
-        let storage = Storage::new(&env);
-        let payment = storage.get_payment(&order_id)?;
-
-        // Validate caller is merchant or payer of the original payment
-        let is_merchant = caller == payment.merchant_address;
-        let is_payer = caller == payment.payer_address;
-
-        if !is_merchant && !is_payer {
-            return Err(PaymentError::NotAuthorized);
-        }
-
-        // Validate amount does not exceed remaining refundable
-        let already_refunded = payment.refunded_amount;
-        if amount > payment.amount - already_refunded {
-            return Err(PaymentError::ExceedsOriginalAmount);
-        }
+        Self::require_role(&env, &admin, Role::MerchantVerifier)?;
 
-        // Validate refund window (30 days)
-        const MAX_REFUND_WINDOW: u64 = 30 * 24 * 60 * 60;
-        if env.ledger().timestamp() > payment.paid_at + MAX_REFUND_WINDOW {
-            return Err(PaymentError::RefundWindowExceeded);
+        if let Some(timeout) = release_timeout {
+            if timeout == 0 || timeout > MAX_ESCROW_RELEASE_TIMEOUT {
+                return Err(PaymentError::InvalidDateRange);
+            }
         }
 
-        // Create refund request (Pending)
-        let request = RefundRequest {
-            refund_id: refund_id.clone(),
-            order_id: order_id.clone(),
-            merchant_address: payment.merchant_address.clone(),
-            payer_address: payment.payer_address.clone(),
-            token: payment.token.clone(),
-            amount,
-            reason: reason.clone(),
-            requested_at: env.ledger().timestamp(),
-            status: RefundStatus::Pending,
-            approved_by: None,
-        };
-
-        storage.save_refund(&request);
+        let storage = Storage::new(&env);
+        storage.get_merchant(&merchant)?;
+        storage.set_escrow_timeout(&merchant, &release_timeout);
 
-        // Event
         env.events().publish(
-            ("refund_initiated",),
-            (refund_id.clone(), order_id.clone(), amount),
+            (escrow_topic(&env), Symbol::new(&env, "configured")),
+            (merchant, release_timeout),
         );
 
         Ok(())
     }
 
-    fn approve_refund(env: Env, caller: Address, refund_id: String) -> Result<(), PaymentError> {
-        caller.require_auth();
-        let storage = Storage::new(&env);
-        let mut req = storage.get_refund(&refund_id)?;
+    fn get_merchant_escrow(env: Env, merchant: Address) -> Option<u64> {
+        Storage::new(&env).get_escrow_timeout(&merchant)
+    }
 
-        // Authorization: merchant of payment or admin
-        let admin = storage.get_admin();
-        let authorized = Some(caller.clone()) == admin || caller == req.merchant_address;
+    fn confirm_delivery(
+        env: Env,
+        payer: Address,
+        merchant: Address,
+        order_id: String,
+    ) -> Result<(), PaymentError> {
+        payer.require_auth();
 
-        if !authorized {
+        let storage = Storage::new(&env);
+        let mut payment = storage.get_payment(&merchant, &order_id)?;
+        if payment.payer_address != payer {
             return Err(PaymentError::NotAuthorized);
         }
-
-        if let RefundStatus::Pending = req.status {
-            req.status = RefundStatus::Approved;
-            req.approved_by = Some(caller.clone());
-            storage.update_refund(&req);
-            env.events()
-                .publish(("refund_approved",), (refund_id.clone(),));
-            Ok(())
-        } else {
-            Err(PaymentError::InvalidRefundStatus)
+        if payment.escrow_status != EscrowStatus::Held {
+            return Err(PaymentError::InvalidStatus);
         }
+
+        Self::payout_escrow(&env, &storage, &mut payment, 0)
     }
 
-    fn reject_refund(env: Env, caller: Address, refund_id: String) -> Result<(), PaymentError> {
-        caller.require_auth();
+    fn release_escrow(env: Env, merchant: Address, order_id: String) -> Result<(), PaymentError> {
         let storage = Storage::new(&env);
-        let mut req = storage.get_refund(&refund_id)?;
-
-        let admin = storage.get_admin();
-        let authorized = Some(caller.clone()) == admin || caller == req.merchant_address;
+        let mut payment = storage.get_payment(&merchant, &order_id)?;
+        if payment.escrow_status != EscrowStatus::Held {
+            return Err(PaymentError::InvalidStatus);
+        }
 
-        if !authorized {
+        // Without the payer's confirmation, funds release only once the timeout has passed
+        if env.ledger().timestamp() < payment.escrow_release_at {
             return Err(PaymentError::NotAuthorized);
         }
 
-        if let RefundStatus::Pending = req.status {
-            req.status = RefundStatus::Rejected;
-            req.approved_by = Some(caller.clone());
-            storage.update_refund(&req);
-            env.events()
-                .publish(("refund_rejected",), (refund_id.clone(),));
-            Ok(())
-        } else {
-            Err(PaymentError::InvalidRefundStatus)
-        }
+        Self::payout_escrow(&env, &storage, &mut payment, 0)
     }
 
-    fn execute_refund(env: Env, refund_id: String) -> Result<(), PaymentError> {
-        let storage = Storage::new(&env);
-        let mut req = storage.get_refund(&refund_id)?;
-
-        // Must be approved
-        if let RefundStatus::Approved = req.status {
-        } else {
-            return Err(PaymentError::InvalidRefundStatus);
-        }
+    fn dispute_escrow(
+        env: Env,
+        payer: Address,
+        merchant: Address,
+        order_id: String,
+    ) -> Result<(), PaymentError> {
+        payer.require_auth();
 
-        // Load payment to update and validate remaining amount again
-        let mut payment = storage.get_payment(&req.order_id)?;
-        if req.amount > payment.amount - payment.refunded_amount {
-            return Err(PaymentError::ExceedsOriginalAmount);
+        let storage = Storage::new(&env);
+        let mut payment = storage.get_payment(&merchant, &order_id)?;
+        if payment.payer_address != payer {
+            return Err(PaymentError::NotAuthorized);
+        }
+        if payment.escrow_status != EscrowStatus::Held {
+            return Err(PaymentError::InvalidStatus);
+        }
+        if env.ledger().timestamp() >= payment.escrow_release_at {
+            return Err(PaymentError::PaymentExpired);
         }
 
-        // Require merchant authorization for token transfer
-        req.merchant_address.require_auth();
+        payment.escrow_status = EscrowStatus::Disputed;
+        storage.update_payment(&payment);
 
-        // Transfer from merchant to payer
-        let token_client = token::Client::new(&env, &req.token);
+        env.events().publish(
+            (escrow_topic(&env), Symbol::new(&env, "disputed")),
+            (order_id, payer),
+        );
 
-        // Optional balance check for clearer error
-        let merchant_balance = token_client.balance(&req.merchant_address);
-        if merchant_balance < req.amount {
-            return Err(PaymentError::InsufficientBalance);
+        Ok(())
+    }
+
+    fn resolve_escrow_dispute(
+        env: Env,
+        admin: Address,
+        merchant: Address,
+        order_id: String,
+        refund_amount: i128,
+    ) -> Result<(), PaymentError> {
+        Self::require_role(&env, &admin, Role::RefundAgent)?;
+
+        let storage = Storage::new(&env);
+        let mut payment = storage.get_payment(&merchant, &order_id)?;
+        if payment.escrow_status != EscrowStatus::Disputed {
+            return Err(PaymentError::InvalidStatus);
+        }
+        // Escrows under arbitration are paid out by the arbitrator's ruling
+        if storage.has_open_dispute(&merchant, &order_id) {
+            return Err(PaymentError::InvalidStatus);
         }
 
-        token_client.transfer(&req.merchant_address, &req.payer_address, &req.amount);
+        Self::payout_escrow(&env, &storage, &mut payment, refund_amount)
+    }
 
-        // Update payment refunded amount
-        payment.refunded_amount = payment.refunded_amount + req.amount;
-        storage.update_payment(&payment);
+    // Dispute and Arbitration Operations
+    fn add_arbitrator(env: Env, admin: Address, arbitrator: Address) -> Result<(), PaymentError> {
+        Self::require_admin_access(&env, &admin)?;
 
-        // Mark refund completed
-        req.status = RefundStatus::Completed;
-        storage.update_refund(&req);
+        let storage = Storage::new(&env);
+        Self::require_no_council(&storage)?;
+        Self::apply_add_arbitrator(&env, &storage, arbitrator);
 
-        env.events()
-            .publish(("refund_executed",), (refund_id.clone(), req.amount));
         Ok(())
     }
 
-    fn get_refund_status(env: Env, refund_id: String) -> Result<RefundStatus, PaymentError> {
+    fn remove_arbitrator(
+        env: Env,
+        admin: Address,
+        arbitrator: Address,
+    ) -> Result<(), PaymentError> {
+        Self::require_admin_access(&env, &admin)?;
+
         let storage = Storage::new(&env);
-        let req = storage.get_refund(&refund_id)?;
-        Ok(req.status)
+        Self::require_no_council(&storage)?;
+        Self::apply_remove_arbitrator(&env, &storage, arbitrator);
+
+        Ok(())
     }
 
-    // Payment History Query Operations
-    fn get_merchant_payment_history(
+    fn get_arbitrators(env: Env) -> Vec<Address> {
+        Storage::new(&env).get_arbitrators()
+    }
+
+    fn deposit_reserve(
         env: Env,
         merchant: Address,
-        cursor: Option<String>,
-        limit: u32,
-        filter: Option<PaymentQueryFilter>,
-        sort_field: Option<SortField>,
-        sort_order: Option<SortOrder>,
-    ) -> Result<PaymentQueryResult, PaymentError> {
-        // Require authorization - merchant or admin
-        Self::require_merchant_access(&env, &merchant)?;
+        token: Address,
+        amount: i128,
+    ) -> Result<(), PaymentError> {
+        merchant.require_auth();
 
-        // Validate query parameters
-        validate_query_limit(limit)?;
-        if let Some(ref f) = filter {
-            validate_query_filter(f)?;
-        }
-        if let Some(ref c) = cursor {
-            let storage = Storage::new(&env);
-            validate_cursor(&env, &Some(c.clone()), &storage)?;
+        if amount <= 0 {
+            return Err(PaymentError::InvalidAmount);
         }
 
         let storage = Storage::new(&env);
-        let default_filter = PaymentQueryFilter {
-            date_start: None,
-            date_end: None,
-            amount_min: None,
-            amount_max: None,
-            token: None,
-            status: PaymentRecordStatus::Any,
-        };
-        let filter = filter.unwrap_or(default_filter);
+        storage.get_merchant(&merchant)?;
 
-        // Get merchant payment indices
-        let order_ids = storage.get_merchant_payment_indices(&merchant);
+        let token_client = token::Client::new(&env, &token);
+        token_client.transfer(&merchant, env.current_contract_address(), &amount);
+        storage.adjust_merchant_reserve(&merchant, &token, amount);
 
-        // Apply filters
-        let filtered = storage.query_payments_with_filters(&order_ids, &filter);
+        env.events().publish(
+            (reserve_topic(&env), Symbol::new(&env, "deposited")),
+            (merchant, token, amount),
+        );
 
-        // Sort
-        let sort_field = sort_field.unwrap_or(SortField::Date);
-        let sort_order = sort_order.unwrap_or(SortOrder::Descending);
-        let sorted = storage.sort_payments(filtered, &sort_field, &sort_order);
+        Ok(())
+    }
 
-        // Paginate
-        let (paginated, next_cursor) = storage.paginate_payments(sorted, cursor, limit);
+    fn withdraw_reserve(
+        env: Env,
+        merchant: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<(), PaymentError> {
+        merchant.require_auth();
 
-        Ok(PaymentQueryResult {
-            records: paginated,
-            next_cursor,
-            total_count: order_ids.len() as u32,
-        })
+        if amount <= 0 {
+            return Err(PaymentError::InvalidAmount);
+        }
+
+        // Amounts under open disputes stay in the reserve until they're ruled on
+        let storage = Storage::new(&env);
+        let available = storage.get_merchant_reserve(&merchant, &token)
+            - storage.get_reserve_hold(&merchant, &token);
+        if amount > available {
+            return Err(PaymentError::InsufficientBalance);
+        }
+
+        storage.adjust_merchant_reserve(&merchant, &token, -amount);
+        let token_client = token::Client::new(&env, &token);
+        token_client.transfer(&env.current_contract_address(), &merchant, &amount);
+
+        env.events().publish(
+            (reserve_topic(&env), Symbol::new(&env, "withdrawn")),
+            (merchant, token, amount),
+        );
+
+        Ok(())
     }
 
-    fn get_payer_payment_history(
+    fn get_merchant_reserve(env: Env, merchant: Address, token: Address) -> i128 {
+        Storage::new(&env).get_merchant_reserve(&merchant, &token)
+    }
+
+    fn open_dispute(
         env: Env,
         payer: Address,
-        cursor: Option<String>,
-        limit: u32,
-        filter: Option<PaymentQueryFilter>,
-        sort_field: Option<SortField>,
-        sort_order: Option<SortOrder>,
-    ) -> Result<PaymentQueryResult, PaymentError> {
-        // Require authorization - payer must match caller
-        Self::require_payer_access(&env, &payer)?;
+        merchant: Address,
+        order_id: String,
+        amount: i128,
+        reason: String,
+    ) -> Result<(), PaymentError> {
+        payer.require_auth();
 
-        // Validate query parameters
-        validate_query_limit(limit)?;
-        if let Some(ref f) = filter {
-            validate_query_filter(f)?;
+        let storage = Storage::new(&env);
+        let mut payment = storage.get_payment(&merchant, &order_id)?;
+        if payment.payer_address != payer {
+            return Err(PaymentError::NotAuthorized);
         }
-        if let Some(ref c) = cursor {
-            let storage = Storage::new(&env);
-            validate_cursor(&env, &Some(c.clone()), &storage)?;
+
+        // A payment can be disputed once
+        if storage.get_dispute(&merchant, &order_id).is_ok() {
+            return Err(PaymentError::InvalidStatus);
         }
 
-        let storage = Storage::new(&env);
-        let default_filter = PaymentQueryFilter {
-            date_start: None,
-            date_end: None,
-            amount_min: None,
-            amount_max: None,
-            token: None,
-            status: PaymentRecordStatus::Any,
-        };
-        let filter = filter.unwrap_or(default_filter);
+        let now = env.ledger().timestamp();
+        if now > payment.paid_at + DISPUTE_WINDOW {
+            return Err(PaymentError::RefundWindowExceeded);
+        }
 
-        // Get payer payment indices
-        let order_ids = storage.get_payer_payment_indices(&payer);
+        // Escrowed payments are refunded from the held net amount, others from the reserve
+        let refundable = if payment.is_escrow_open() {
+            payment.net_amount - payment.refunded_amount
+        } else {
+            payment.amount - payment.refunded_amount
+        };
+        if amount <= 0 {
+            return Err(PaymentError::InvalidAmount);
+        }
+        if amount > refundable {
+            return Err(PaymentError::ExceedsOriginalAmount);
+        }
 
-        // Apply filters
-        let filtered = storage.query_payments_with_filters(&order_ids, &filter);
+        if payment.is_escrow_open() {
+            if payment.escrow_status == EscrowStatus::Held {
+                payment.escrow_status = EscrowStatus::Disputed;
+                storage.update_payment(&payment);
+            }
+        } else {
+            storage.adjust_reserve_hold(&payment.merchant_address, &payment.token, amount);
+        }
 
-        // Sort
-        let sort_field = sort_field.unwrap_or(SortField::Date);
-        let sort_order = sort_order.unwrap_or(SortOrder::Descending);
-        let sorted = storage.sort_payments(filtered, &sort_field, &sort_order);
+        let dispute = Dispute {
+            order_id: order_id.clone(),
+            merchant_address: payment.merchant_address.clone(),
+            payer_address: payer.clone(),
+            token: payment.token.clone(),
+            amount,
+            reason,
+            payer_evidence: Vec::new(&env),
+            merchant_evidence: Vec::new(&env),
+            opened_at: now,
+            evidence_deadline: now + DISPUTE_EVIDENCE_PERIOD,
+            status: DisputeStatus::Open,
+            refund_amount: 0,
+            arbitrator: None,
+            resolved_at: 0,
+        };
+        storage.save_dispute(&dispute);
 
-        // Paginate
-        let (paginated, next_cursor) = storage.paginate_payments(sorted, cursor, limit);
+        env.events().publish(
+            (dispute_topic(&env), Symbol::new(&env, "opened")),
+            (order_id, payer, amount),
+        );
 
-        Ok(PaymentQueryResult {
-            records: paginated,
-            next_cursor,
-            total_count: order_ids.len() as u32,
-        })
+        Ok(())
     }
 
-    fn get_payment_by_id(
+    fn submit_dispute_evidence(
         env: Env,
         caller: Address,
+        merchant: Address,
         order_id: String,
-    ) -> Result<PaymentRecord, PaymentError> {
-        let storage = Storage::new(&env);
-        let payment = storage.get_payment(&order_id)?;
+        evidence_hash: BytesN<32>,
+    ) -> Result<(), PaymentError> {
+        caller.require_auth();
 
-        // Verify authorization: merchant, payer, or admin
-        let is_merchant = payment.merchant_address == caller;
-        let is_payer = payment.payer_address == caller;
-        let is_admin = storage.get_admin().map(|a| a == caller).unwrap_or(false);
+        let storage = Storage::new(&env);
+        let mut dispute = storage.get_dispute(&merchant, &order_id)?;
+        if dispute.status != DisputeStatus::Open {
+            return Err(PaymentError::InvalidStatus);
+        }
+        if env.ledger().timestamp() > dispute.evidence_deadline {
+            return Err(PaymentError::PaymentExpired);
+        }
 
-        if !is_merchant && !is_payer && !is_admin {
-            return Err(PaymentError::UnauthorizedQuery);
+        let evidence = if caller == dispute.payer_address {
+            &mut dispute.payer_evidence
+        } else if caller == dispute.merchant_address {
+            &mut dispute.merchant_evidence
+        } else {
+            return Err(PaymentError::NotAuthorized);
+        };
+        // Cap each side's submissions so a dispute can't grow without bound
+        if evidence.len() >= MAX_DISPUTE_EVIDENCE {
+            return Err(PaymentError::InvalidAmount);
         }
+        evidence.push_back(evidence_hash.clone());
+        storage.save_dispute(&dispute);
 
-        Ok(payment)
+        env.events().publish(
+            (dispute_topic(&env), Symbol::new(&env, "evidence")),
+            (order_id, caller, evidence_hash),
+        );
+
+        Ok(())
     }
 
-    fn get_global_payment_stats(
+    fn resolve_dispute(
         env: Env,
-        admin: Address,
-        date_start: Option<u64>,
-        date_end: Option<u64>,
-    ) -> Result<PaymentStats, PaymentError> {
-        // Require admin authorization
-        Self::require_admin_access(&env, &admin)?;
-
-        // Validate date range
-        if let (Some(start), Some(end)) = (date_start, date_end) {
-            if end < start {
-                return Err(PaymentError::InvalidDateRange);
-            }
-        }
+        arbitrator: Address,
+        merchant: Address,
+        order_id: String,
+        ruling: DisputeRuling,
+    ) -> Result<(), PaymentError> {
+        arbitrator.require_auth();
 
         let storage = Storage::new(&env);
-        let payments = storage.get_payments_map();
+        if !storage.is_arbitrator(&arbitrator) {
+            return Err(PaymentError::NotAuthorized);
+        }
 
-        let mut total_payments = 0u32;
-        let mut total_amount = 0i128;
-        let mut total_refunded = 0i128;
-        let mut completed_count = 0u32;
-        let mut partially_refunded_count = 0u32;
-        let mut fully_refunded_count = 0u32;
+        let mut dispute = storage.get_dispute(&merchant, &order_id)?;
+        if dispute.status != DisputeStatus::Open {
+            return Err(PaymentError::InvalidStatus);
+        }
+        // Parties can't rule on their own dispute
+        if arbitrator == dispute.payer_address || arbitrator == dispute.merchant_address {
+            return Err(PaymentError::NotAuthorized);
+        }
+        // Both sides get the full evidence period before a ruling
+        let now = env.ledger().timestamp();
+        if now <= dispute.evidence_deadline {
+            return Err(PaymentError::NotAuthorized);
+        }
 
-        for payment in payments.values() {
-            // Apply date filter if provided
-            if let Some(start) = date_start {
-                if payment.paid_at < start {
-                    continue;
+        let (status, refund_amount) = match ruling {
+            DisputeRuling::Full => (DisputeStatus::Refunded, dispute.amount),
+            DisputeRuling::Partial(amount) => {
+                if amount <= 0 || amount >= dispute.amount {
+                    return Err(PaymentError::InvalidAmount);
                 }
+                (DisputeStatus::PartiallyRefunded, amount)
             }
-            if let Some(end) = date_end {
-                if payment.paid_at > end {
-                    continue;
-                }
+            DisputeRuling::Denied => (DisputeStatus::Rejected, 0),
+        };
+
+        let mut payment = storage.get_payment(&merchant, &order_id)?;
+        let from_escrow = payment.is_escrow_open();
+        if from_escrow {
+            Self::payout_escrow(&env, &storage, &mut payment, refund_amount)?;
+        } else {
+            let merchant = &dispute.merchant_address;
+            storage.adjust_reserve_hold(merchant, &dispute.token, -dispute.amount);
+
+            if refund_amount > 0 {
+                // The deposited reserve pays first, and the rolling reserve covers the rest
+                let from_deposit = storage
+                    .get_merchant_reserve(merchant, &dispute.token)
+                    .min(refund_amount);
+                if from_deposit < refund_amount {
+                    Self::draw_rolling_reserve(
+                        &env,
+                        &storage,
+                        merchant,
+                        &dispute.token,
+                        refund_amount - from_deposit,
+                    )?;
+                }
+                if from_deposit > 0 {
+                    storage.adjust_merchant_reserve(merchant, &dispute.token, -from_deposit);
+                }
+                let token_client = token::Client::new(&env, &dispute.token);
+                token_client.transfer(
+                    &env.current_contract_address(),
+                    &dispute.payer_address,
+                    &refund_amount,
+                );
+
+                payment.refunded_amount += refund_amount;
+                storage.update_payment(&payment);
+            }
+        }
+
+        dispute.status = status;
+        dispute.refund_amount = refund_amount;
+        dispute.arbitrator = Some(arbitrator.clone());
+        dispute.resolved_at = now;
+        storage.save_dispute(&dispute);
+
+        env.events().publish(
+            (dispute_topic(&env), Symbol::new(&env, "resolved")),
+            DisputeResolvedEvent {
+                order_id,
+                arbitrator,
+                status,
+                refund_amount,
+                from_escrow,
+                timestamp: now,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn get_dispute(env: Env, merchant: Address, order_id: String) -> Result<Dispute, PaymentError> {
+        Storage::new(&env).get_dispute(&merchant, &order_id)
+    }
+
+    // Subscription Operations
+    fn create_subscription_plan(
+        env: Env,
+        merchant: Address,
+        token: Address,
+        amount: i128,
+        period: u64,
+        trial_period: u64,
+        max_cycles: u32,
+    ) -> Result<u64, PaymentError> {
+        merchant.require_auth();
+
+        if amount <= 0 {
+            return Err(PaymentError::InvalidAmount);
+        }
+        if period == 0 {
+            return Err(PaymentError::InvalidDateRange);
+        }
+
+        let storage = Storage::new(&env);
+        Self::load_chargeable_merchant(&env, &storage, &merchant, &token, amount)?;
+
+        let plan_id = storage.get_next_subscription_plan_id();
+        storage.save_subscription_plan(&SubscriptionPlan {
+            plan_id,
+            merchant_address: merchant.clone(),
+            token,
+            amount,
+            period,
+            trial_period,
+            max_cycles,
+            active: true,
+        });
+
+        env.events().publish(
+            (subscription_topic(&env), Symbol::new(&env, "plan_created")),
+            (plan_id, merchant, amount, period),
+        );
+
+        Ok(plan_id)
+    }
+
+    fn set_subscription_plan_active(
+        env: Env,
+        merchant: Address,
+        plan_id: u64,
+        active: bool,
+    ) -> Result<(), PaymentError> {
+        merchant.require_auth();
+
+        let storage = Storage::new(&env);
+        let mut plan = storage.get_subscription_plan(plan_id)?;
+        if plan.merchant_address != merchant {
+            return Err(PaymentError::NotAuthorized);
+        }
+
+        // Existing subscriptions keep running; this only controls new ones
+        plan.active = active;
+        storage.save_subscription_plan(&plan);
+
+        env.events().publish(
+            (subscription_topic(&env), Symbol::new(&env, "plan_updated")),
+            (plan_id, active),
+        );
+
+        Ok(())
+    }
+
+    fn get_subscription_plan(env: Env, plan_id: u64) -> Result<SubscriptionPlan, PaymentError> {
+        Storage::new(&env).get_subscription_plan(plan_id)
+    }
+
+    fn subscribe(env: Env, payer: Address, plan_id: u64) -> Result<u64, PaymentError> {
+        payer.require_auth();
+
+        let storage = Storage::new(&env);
+        let plan = storage.get_subscription_plan(plan_id)?;
+        if !plan.active {
+            return Err(PaymentError::InvalidStatus);
+        }
+
+        let now = env.ledger().timestamp();
+        let subscription = Subscription {
+            subscription_id: storage.get_next_subscription_id(),
+            plan_id,
+            merchant_address: plan.merchant_address,
+            payer_address: payer.clone(),
+            token: plan.token,
+            amount: plan.amount,
+            period: plan.period,
+            max_cycles: plan.max_cycles,
+            cycles_charged: 0,
+            next_charge_at: now.saturating_add(plan.trial_period),
+            status: SubscriptionStatus::Active,
+            created_at: now,
+        };
+        storage.save_subscription(&subscription);
+        storage.index_subscription(&subscription);
+
+        env.events().publish(
+            (subscription_topic(&env), Symbol::new(&env, "subscribed")),
+            (subscription.subscription_id, plan_id, payer),
+        );
+
+        Ok(subscription.subscription_id)
+    }
+
+    fn charge_due(env: Env, subscription_id: u64) -> Result<PaymentRecord, PaymentError> {
+        if Self::is_paused(&env) {
+            return Err(PaymentError::ContractPaused);
+        }
+
+        let storage = Storage::new(&env);
+        let mut subscription = storage.get_subscription(subscription_id)?;
+        if subscription.status != SubscriptionStatus::Active {
+            return Err(PaymentError::InvalidStatus);
+        }
+        if env.ledger().timestamp() < subscription.next_charge_at {
+            return Err(PaymentError::NotDue);
+        }
+
+        let order_id = sequential_order_id(
+            &env,
+            SUBSCRIPTION_ORDER_PREFIX,
+            subscription_id,
+            subscription.cycles_charged + 1,
+        );
+        if storage
+            .find_order_payment(&subscription.merchant_address, &order_id)
+            .is_some()
+        {
+            return Err(PaymentError::DuplicateOrderId);
+        }
+
+        // The payer authorized the plan by subscribing, so the charge pulls from their allowance
+        let merchant = Self::load_chargeable_merchant(
+            &env,
+            &storage,
+            &subscription.merchant_address,
+            &subscription.token,
+            subscription.amount,
+        )?;
+        let payment_record = Self::settle_charge(
+            &env,
+            &storage,
+            merchant,
+            &Charge {
+                payer: &subscription.payer_address,
+                merchant: &subscription.merchant_address,
+                token: &subscription.token,
+                order_id: &order_id,
+                amount: subscription.amount,
+                agreed_fee: None,
+                source: FundsSource::Allowance,
+                invoice_id: None,
+                splits: None,
+                fiat: None,
+            },
+        )?;
+
+        subscription.cycles_charged += 1;
+        subscription.next_charge_at = subscription
+            .next_charge_at
+            .saturating_add(subscription.period);
+        if subscription.max_cycles > 0 && subscription.cycles_charged >= subscription.max_cycles {
+            subscription.status = SubscriptionStatus::Completed;
+        }
+        storage.save_subscription(&subscription);
+
+        env.events().publish(
+            (subscription_topic(&env), Symbol::new(&env, "charged")),
+            (subscription_id, subscription.cycles_charged, order_id),
+        );
+
+        Ok(payment_record)
+    }
+
+    fn cancel_subscription(
+        env: Env,
+        caller: Address,
+        subscription_id: u64,
+    ) -> Result<(), PaymentError> {
+        let (storage, mut subscription) =
+            Self::load_subscription_for_party(&env, &caller, subscription_id)?;
+        if !matches!(
+            subscription.status,
+            SubscriptionStatus::Active | SubscriptionStatus::Paused
+        ) {
+            return Err(PaymentError::InvalidStatus);
+        }
+
+        subscription.status = SubscriptionStatus::Cancelled;
+        storage.save_subscription(&subscription);
+
+        env.events().publish(
+            (subscription_topic(&env), Symbol::new(&env, "cancelled")),
+            (subscription_id, caller),
+        );
+
+        Ok(())
+    }
+
+    fn pause_subscription(
+        env: Env,
+        caller: Address,
+        subscription_id: u64,
+    ) -> Result<(), PaymentError> {
+        let (storage, mut subscription) =
+            Self::load_subscription_for_party(&env, &caller, subscription_id)?;
+        if subscription.status != SubscriptionStatus::Active {
+            return Err(PaymentError::InvalidStatus);
+        }
+
+        subscription.status = SubscriptionStatus::Paused;
+        storage.save_subscription(&subscription);
+
+        env.events().publish(
+            (subscription_topic(&env), Symbol::new(&env, "paused")),
+            (subscription_id, caller),
+        );
+
+        Ok(())
+    }
+
+    fn resume_subscription(
+        env: Env,
+        caller: Address,
+        subscription_id: u64,
+    ) -> Result<(), PaymentError> {
+        let (storage, mut subscription) =
+            Self::load_subscription_for_party(&env, &caller, subscription_id)?;
+        if subscription.status != SubscriptionStatus::Paused {
+            return Err(PaymentError::InvalidStatus);
+        }
+
+        // Periods missed while paused are skipped rather than charged on resume
+        subscription.status = SubscriptionStatus::Active;
+        subscription.next_charge_at = subscription.next_charge_at.max(env.ledger().timestamp());
+        storage.save_subscription(&subscription);
+
+        env.events().publish(
+            (subscription_topic(&env), Symbol::new(&env, "resumed")),
+            (subscription_id, caller),
+        );
+
+        Ok(())
+    }
+
+    fn get_subscription(env: Env, subscription_id: u64) -> Result<Subscription, PaymentError> {
+        Storage::new(&env).get_subscription(subscription_id)
+    }
+
+    fn get_merchant_subscriptions(env: Env, merchant: Address) -> Vec<Subscription> {
+        Storage::new(&env).get_merchant_subscriptions(&merchant)
+    }
+
+    fn get_payer_subscriptions(env: Env, payer: Address) -> Vec<Subscription> {
+        Storage::new(&env).get_payer_subscriptions(&payer)
+    }
+
+    // Invoice Operations
+    fn create_invoice(
+        env: Env,
+        merchant: Address,
+        terms: InvoiceTerms,
+    ) -> Result<u64, PaymentError> {
+        merchant.require_auth();
+
+        if terms.amount <= 0 {
+            return Err(PaymentError::InvalidAmount);
+        }
+        let now = env.ledger().timestamp();
+        if terms.due_date <= now {
+            return Err(PaymentError::InvalidDateRange);
+        }
+        validate_description(&terms.memo)?;
+
+        let storage = Storage::new(&env);
+        Self::load_chargeable_merchant(&env, &storage, &merchant, &terms.token, terms.amount)?;
+
+        let invoice = Invoice {
+            invoice_id: storage.get_next_invoice_id(),
+            merchant_address: merchant.clone(),
+            terms,
+            amount_paid: 0,
+            payment_count: 0,
+            status: InvoiceStatus::Open,
+            created_at: now,
+        };
+        storage.save_invoice(&invoice);
+
+        env.events().publish(
+            (invoice_topic(&env), Symbol::new(&env, "created")),
+            (invoice.invoice_id, merchant, invoice.terms.amount),
+        );
+
+        Ok(invoice.invoice_id)
+    }
+
+    fn pay_invoice(
+        env: Env,
+        payer: Address,
+        invoice_id: u64,
+        amount: i128,
+    ) -> Result<PaymentRecord, PaymentError> {
+        if Self::is_paused(&env) {
+            return Err(PaymentError::ContractPaused);
+        }
+
+        payer.require_auth();
+
+        let storage = Storage::new(&env);
+        let mut invoice = storage.get_invoice(invoice_id)?;
+        if !matches!(
+            invoice.status,
+            InvoiceStatus::Open | InvoiceStatus::PartiallyPaid
+        ) {
+            return Err(PaymentError::InvalidStatus);
+        }
+        if env.ledger().timestamp() > invoice.terms.due_date {
+            return Err(PaymentError::OrderExpired);
+        }
+        if let Some(ref allowed_payer) = invoice.terms.payer {
+            if *allowed_payer != payer {
+                return Err(PaymentError::NotAuthorized);
+            }
+        }
+
+        // Invoices can be paid in parts but never beyond what's owed
+        if amount <= 0 {
+            return Err(PaymentError::InvalidAmount);
+        }
+        if amount > invoice.terms.amount - invoice.amount_paid {
+            return Err(PaymentError::ExceedsOriginalAmount);
+        }
+
+        let order_id = sequential_order_id(
+            &env,
+            INVOICE_ORDER_PREFIX,
+            invoice_id,
+            invoice.payment_count + 1,
+        );
+        if storage
+            .find_order_payment(&invoice.merchant_address, &order_id)
+            .is_some()
+        {
+            return Err(PaymentError::DuplicateOrderId);
+        }
+
+        let merchant = Self::load_chargeable_merchant(
+            &env,
+            &storage,
+            &invoice.merchant_address,
+            &invoice.terms.token,
+            amount,
+        )?;
+        let payment_record = Self::settle_charge(
+            &env,
+            &storage,
+            merchant,
+            &Charge {
+                payer: &payer,
+                merchant: &invoice.merchant_address,
+                token: &invoice.terms.token,
+                order_id: &order_id,
+                amount,
+                agreed_fee: None,
+                source: FundsSource::Payer,
+                invoice_id: Some(invoice_id),
+                splits: None,
+                fiat: None,
+            },
+        )?;
+
+        invoice.amount_paid += amount;
+        invoice.payment_count += 1;
+        invoice.status = if invoice.amount_paid == invoice.terms.amount {
+            InvoiceStatus::Paid
+        } else {
+            InvoiceStatus::PartiallyPaid
+        };
+        storage.save_invoice(&invoice);
+
+        env.events().publish(
+            (invoice_topic(&env), Symbol::new(&env, "paid")),
+            (invoice_id, order_id, amount, invoice.status),
+        );
+
+        Ok(payment_record)
+    }
+
+    fn cancel_invoice(env: Env, merchant: Address, invoice_id: u64) -> Result<(), PaymentError> {
+        merchant.require_auth();
+
+        let storage = Storage::new(&env);
+        let mut invoice = storage.get_invoice(invoice_id)?;
+        if invoice.merchant_address != merchant {
+            return Err(PaymentError::NotAuthorized);
+        }
+        if !matches!(
+            invoice.status,
+            InvoiceStatus::Open | InvoiceStatus::PartiallyPaid
+        ) {
+            return Err(PaymentError::InvalidStatus);
+        }
+
+        // Partial payments already made stay settled and can be refunded through the refund flow
+        invoice.status = InvoiceStatus::Cancelled;
+        storage.save_invoice(&invoice);
+
+        env.events().publish(
+            (invoice_topic(&env), Symbol::new(&env, "cancelled")),
+            (invoice_id, invoice.amount_paid),
+        );
+
+        Ok(())
+    }
+
+    fn get_invoice(env: Env, invoice_id: u64) -> Result<Invoice, PaymentError> {
+        let mut invoice = Storage::new(&env).get_invoice(invoice_id)?;
+
+        // Expiry is derived from the due date rather than stored
+        let unpaid = matches!(
+            invoice.status,
+            InvoiceStatus::Open | InvoiceStatus::PartiallyPaid
+        );
+        if unpaid && env.ledger().timestamp() > invoice.terms.due_date {
+            invoice.status = InvoiceStatus::Expired;
+        }
+
+        Ok(invoice)
+    }
+
+    // Authorize and Capture Operations
+    fn authorize_payment(
+        env: Env,
+        payer: Address,
+        merchant: Address,
+        token: Address,
+        max_amount: i128,
+        expires_at: u64,
+    ) -> Result<u64, PaymentError> {
+        if Self::is_paused(&env) {
+            return Err(PaymentError::ContractPaused);
+        }
+
+        payer.require_auth();
+
+        if max_amount <= 0 {
+            return Err(PaymentError::InvalidAmount);
+        }
+        let now = env.ledger().timestamp();
+        if expires_at <= now || expires_at - now > MAX_AUTHORIZATION_PERIOD {
+            return Err(PaymentError::InvalidDateRange);
+        }
+
+        let storage = Storage::new(&env);
+        Self::load_chargeable_merchant(&env, &storage, &merchant, &token, max_amount)?;
+
+        let token_client = token::Client::new(&env, &token);
+        if token_client.balance(&payer) < max_amount {
+            return Err(PaymentError::InsufficientBalance);
+        }
+        token_client.transfer(&payer, env.current_contract_address(), &max_amount);
+
+        let authorization = PaymentAuthorization {
+            authorization_id: storage.get_next_authorization_id(),
+            merchant_address: merchant.clone(),
+            payer_address: payer.clone(),
+            token,
+            max_amount,
+            captured_amount: 0,
+            capture_count: 0,
+            expires_at,
+            status: AuthorizationStatus::Active,
+            created_at: now,
+        };
+        storage.save_authorization(&authorization);
+
+        env.events().publish(
+            (authorization_topic(&env), Symbol::new(&env, "authorized")),
+            (authorization.authorization_id, payer, merchant, max_amount),
+        );
+
+        Ok(authorization.authorization_id)
+    }
+
+    fn capture_payment(
+        env: Env,
+        merchant: Address,
+        authorization_id: u64,
+        amount: i128,
+        final_capture: bool,
+    ) -> Result<PaymentRecord, PaymentError> {
+        if Self::is_paused(&env) {
+            return Err(PaymentError::ContractPaused);
+        }
+
+        merchant.require_auth();
+
+        let storage = Storage::new(&env);
+        let mut authorization = storage.get_authorization(authorization_id)?;
+        if authorization.merchant_address != merchant {
+            return Err(PaymentError::NotAuthorized);
+        }
+        if authorization.status != AuthorizationStatus::Active {
+            return Err(PaymentError::InvalidStatus);
+        }
+        if env.ledger().timestamp() > authorization.expires_at {
+            return Err(PaymentError::PaymentExpired);
+        }
+
+        let remaining = authorization.max_amount - authorization.captured_amount;
+        if amount <= 0 {
+            return Err(PaymentError::InvalidAmount);
+        }
+        if amount > remaining {
+            return Err(PaymentError::ExceedsOriginalAmount);
+        }
+
+        let order_id = sequential_order_id(
+            &env,
+            CAPTURE_ORDER_PREFIX,
+            authorization_id,
+            authorization.capture_count + 1,
+        );
+        if storage
+            .find_order_payment(&authorization.merchant_address, &order_id)
+            .is_some()
+        {
+            return Err(PaymentError::DuplicateOrderId);
+        }
+
+        // Captures settle from the hold, so a surcharge must fit in what's left of it
+        let merchant_record = Self::load_chargeable_merchant(
+            &env,
+            &storage,
+            &merchant,
+            &authorization.token,
+            amount,
+        )?;
+        let payment_record = Self::settle_charge(
+            &env,
+            &storage,
+            merchant_record,
+            &Charge {
+                payer: &authorization.payer_address,
+                merchant: &merchant,
+                token: &authorization.token,
+                order_id: &order_id,
+                amount,
+                agreed_fee: None,
+                source: FundsSource::Held {
+                    available: remaining,
+                },
+                invoice_id: None,
+                splits: None,
+                fiat: None,
+            },
+        )?;
+
+        authorization.captured_amount += payment_record.gross_amount;
+        authorization.capture_count += 1;
+
+        env.events().publish(
+            (authorization_topic(&env), Symbol::new(&env, "captured")),
+            (authorization_id, order_id, payment_record.gross_amount),
+        );
+
+        if final_capture || authorization.captured_amount == authorization.max_amount {
+            Self::close_authorization(
+                &env,
+                &storage,
+                &mut authorization,
+                AuthorizationStatus::Captured,
+            );
+        } else {
+            storage.save_authorization(&authorization);
+        }
+
+        Ok(payment_record)
+    }
+
+    fn void_authorization(
+        env: Env,
+        merchant: Address,
+        authorization_id: u64,
+    ) -> Result<(), PaymentError> {
+        merchant.require_auth();
+
+        let storage = Storage::new(&env);
+        let mut authorization = storage.get_authorization(authorization_id)?;
+        if authorization.merchant_address != merchant {
+            return Err(PaymentError::NotAuthorized);
+        }
+        if authorization.status != AuthorizationStatus::Active {
+            return Err(PaymentError::InvalidStatus);
+        }
+
+        Self::close_authorization(
+            &env,
+            &storage,
+            &mut authorization,
+            AuthorizationStatus::Voided,
+        );
+
+        Ok(())
+    }
+
+    fn release_expired_authorization(env: Env, authorization_id: u64) -> Result<(), PaymentError> {
+        let storage = Storage::new(&env);
+        let mut authorization = storage.get_authorization(authorization_id)?;
+        if authorization.status != AuthorizationStatus::Active {
+            return Err(PaymentError::InvalidStatus);
+        }
+
+        // Anyone can return a lapsed hold to its payer
+        if env.ledger().timestamp() <= authorization.expires_at {
+            return Err(PaymentError::NotDue);
+        }
+
+        Self::close_authorization(
+            &env,
+            &storage,
+            &mut authorization,
+            AuthorizationStatus::Expired,
+        );
+
+        Ok(())
+    }
+
+    fn get_authorization(
+        env: Env,
+        authorization_id: u64,
+    ) -> Result<PaymentAuthorization, PaymentError> {
+        Storage::new(&env).get_authorization(authorization_id)
+    }
+
+    // Cross-token Payment Operations
+    fn set_swap_router(env: Env, admin: Address, router: Address) -> Result<(), PaymentError> {
+        Self::require_admin_access(&env, &admin)?;
+
+        let storage = Storage::new(&env);
+        Self::require_no_council(&storage)?;
+        Self::apply_swap_router(&env, &storage, router, admin);
+
+        Ok(())
+    }
+
+    fn get_swap_router(env: Env) -> Option<Address> {
+        Storage::new(&env).get_swap_router()
+    }
+
+    // Fiat-denominated Order Operations
+    fn set_price_oracle(
+        env: Env,
+        admin: Address,
+        config: OracleConfig,
+    ) -> Result<(), PaymentError> {
+        Self::require_admin_access(&env, &admin)?;
+
+        let storage = Storage::new(&env);
+        Self::require_no_council(&storage)?;
+        Self::apply_price_oracle(&env, &storage, config, admin)
+    }
+
+    fn get_price_oracle(env: Env) -> Option<OracleConfig> {
+        Storage::new(&env).get_price_oracle()
+    }
+
+    // Relayed Payment Operations
+    fn set_payer_relay_key(
+        env: Env,
+        payer: Address,
+        public_key: Option<BytesN<32>>,
+    ) -> Result<(), PaymentError> {
+        payer.require_auth();
+
+        let storage = Storage::new(&env);
+        storage.set_payer_relay_key(&payer, &public_key);
+
+        env.events().publish(
+            (payment_topic(&env), Symbol::new(&env, "relay_key_set")),
+            (payer, public_key),
+        );
+
+        Ok(())
+    }
+
+    fn get_payer_relay_key(env: Env, payer: Address) -> Option<BytesN<32>> {
+        Storage::new(&env).get_payer_relay_key(&payer)
+    }
+
+    fn process_relayed_payment(
+        env: Env,
+        relayer: Address,
+        order: PaymentOrder,
+        signature: BytesN<64>,
+        merchant_public_key: BytesN<32>,
+        relay: RelayedPayment,
+    ) -> Result<PaymentRecord, PaymentError> {
+        if Self::is_paused(&env) {
+            return Err(PaymentError::ContractPaused);
+        }
+
+        // The relayer submits the transaction; the payer authorized the order off-chain
+        relayer.require_auth();
+
+        if relay.relay_fee < 0 {
+            return Err(PaymentError::InvalidAmount);
+        }
+
+        let storage = Storage::new(&env);
+        let payer_key = storage
+            .get_payer_relay_key(&relay.payer)
+            .ok_or(PaymentError::SigningKeyNotFound)?;
+        let contract = env.current_contract_address();
+        let order_message = create_order_message(&env, &storage, &order)?;
+        let relay_message = create_relay_message(
+            &env,
+            &contract,
+            &relayer,
+            &relay.payer,
+            relay.relay_fee,
+            &order_message,
+        );
+        env.crypto()
+            .ed25519_verify(&payer_key, &relay_message, &relay.payer_signature);
+
+        let already_settled = storage
+            .find_order_payment(&order.merchant_address, &order.order_id)
+            .is_some();
+        let record = Self::settle_order(
+            &env,
+            &storage,
+            &relay.payer,
+            &order,
+            &merchant_public_key,
+            Some(&signature),
+            true,
+        )?;
+        // Resubmitting a settled order returns its record, and its relayer was already paid
+        if already_settled {
+            return Ok(record);
+        }
+
+        // The relay fee comes from the same allowance, capped relative to the settled amount
+        if relay.relay_fee > 0 {
+            let max_relay_fee =
+                record.amount * i128::from(MAX_RELAY_FEE_BPS) / BASIS_POINTS_DENOMINATOR;
+            if relay.relay_fee > max_relay_fee {
+                return Err(PaymentError::InvalidAmount);
+            }
+
+            let token_client = token::Client::new(&env, &record.token);
+            let available = token_client
+                .balance(&relay.payer)
+                .min(token_client.allowance(&relay.payer, &contract));
+            if available < relay.relay_fee {
+                return Err(PaymentError::InsufficientBalance);
+            }
+            token_client.transfer_from(&contract, &relay.payer, &relayer, &relay.relay_fee);
+        }
+
+        env.events().publish(
+            (payment_topic(&env), Symbol::new(&env, "relayed")),
+            (order.order_id, relayer, relay.relay_fee),
+        );
+
+        Ok(record)
+    }
+
+    // Merchant Balance Operations
+    fn set_settlement_config(
+        env: Env,
+        merchant: Address,
+        payout_address: Address,
+        schedule: SettlementSchedule,
+        min_payout: i128,
+    ) -> Result<(), PaymentError> {
+        merchant.require_auth();
+
+        if min_payout < 0 {
+            return Err(PaymentError::InvalidAmount);
+        }
+
+        let storage = Storage::new(&env);
+        storage.get_merchant(&merchant)?;
+
+        // Changing the settings keeps the settlement history, so the schedule can't be reset early
+        let last_settled_at = storage
+            .get_settlement_config(&merchant)
+            .map(|config| config.last_settled_at)
+            .unwrap_or_else(|| Map::new(&env));
+        storage.set_settlement_config(
+            &merchant,
+            &Some(SettlementConfig {
+                payout_address: payout_address.clone(),
+                schedule,
+                min_payout,
+                last_settled_at,
+            }),
+        );
+
+        env.events().publish(
+            (settlement_topic(&env), Symbol::new(&env, "configured")),
+            (merchant, payout_address, schedule, min_payout),
+        );
+
+        Ok(())
+    }
+
+    fn disable_settlement(env: Env, merchant: Address) -> Result<(), PaymentError> {
+        merchant.require_auth();
+
+        // Later payments go straight to the merchant; any balance stays withdrawable
+        let storage = Storage::new(&env);
+        storage.set_settlement_config(&merchant, &None);
+
+        env.events().publish(
+            (settlement_topic(&env), Symbol::new(&env, "disabled")),
+            merchant,
+        );
+
+        Ok(())
+    }
+
+    fn get_settlement_config(env: Env, merchant: Address) -> Option<SettlementConfig> {
+        Storage::new(&env).get_settlement_config(&merchant)
+    }
+
+    fn get_merchant_balance(env: Env, merchant: Address, token: Address) -> i128 {
+        Storage::new(&env).get_merchant_balance(&merchant, &token)
+    }
+
+    fn withdraw_balance(
+        env: Env,
+        merchant: Address,
+        token: Address,
+        amount: i128,
+        to: Address,
+    ) -> Result<(), PaymentError> {
+        merchant.require_auth();
+
+        if amount <= 0 {
+            return Err(PaymentError::InvalidAmount);
+        }
+
+        let storage = Storage::new(&env);
+        if amount > storage.get_merchant_balance(&merchant, &token) {
+            return Err(PaymentError::InsufficientBalance);
+        }
+
+        storage.adjust_merchant_balance(&merchant, &token, -amount);
+        let token_client = token::Client::new(&env, &token);
+        token_client.transfer(&env.current_contract_address(), &to, &amount);
+
+        env.events().publish(
+            (settlement_topic(&env), Symbol::new(&env, "withdrawn")),
+            (merchant, token, amount, to),
+        );
+
+        Ok(())
+    }
+
+    fn settle(env: Env, merchant: Address, token: Address) -> Result<i128, PaymentError> {
+        if Self::is_paused(&env) {
+            return Err(PaymentError::ContractPaused);
+        }
+
+        // Anyone can trigger a due settlement; funds only go to the merchant's payout address
+        let storage = Storage::new(&env);
+        let mut config = storage
+            .get_settlement_config(&merchant)
+            .ok_or(PaymentError::InvalidStatus)?;
+
+        let now = env.ledger().timestamp();
+        if let Some(last_settled_at) = config.last_settled_at.get(token.clone()) {
+            if now < last_settled_at.saturating_add(config.schedule.period()) {
+                return Err(PaymentError::NotDue);
+            }
+        }
+
+        let amount = storage.get_merchant_balance(&merchant, &token);
+        if amount <= 0 || amount < config.min_payout {
+            return Err(PaymentError::InsufficientBalance);
+        }
+
+        storage.adjust_merchant_balance(&merchant, &token, -amount);
+        config.last_settled_at.set(token.clone(), now);
+        storage.set_settlement_config(&merchant, &Some(config.clone()));
+
+        let token_client = token::Client::new(&env, &token);
+        token_client.transfer(
+            &env.current_contract_address(),
+            &config.payout_address,
+            &amount,
+        );
+
+        env.events().publish(
+            (settlement_topic(&env), Symbol::new(&env, "settled")),
+            (merchant, token, amount, config.payout_address),
+        );
+
+        Ok(amount)
+    }
+
+    // Rolling Reserve Operations
+    fn set_rolling_reserve(
+        env: Env,
+        admin: Address,
+        scope: ReserveScope,
+        config: Option<RollingReserveConfig>,
+    ) -> Result<(), PaymentError> {
+        Self::require_role(&env, &admin, Role::MerchantVerifier)?;
+
+        if let Some(config) = &config {
+            if config.rate_bps == 0 || config.rate_bps > MAX_ROLLING_RESERVE_RATE_BPS {
+                return Err(PaymentError::InvalidFeeRate);
+            }
+            if config.hold_period == 0 || config.hold_period > MAX_ROLLING_RESERVE_PERIOD {
+                return Err(PaymentError::InvalidDateRange);
+            }
+        }
+
+        // Only later payments are affected; amounts already held keep their release times
+        let storage = Storage::new(&env);
+        storage.set_rolling_reserve_config(&scope, &config);
+
+        env.events().publish(
+            (reserve_topic(&env), Symbol::new(&env, "configured")),
+            (scope, config),
+        );
+
+        Ok(())
+    }
+
+    fn get_rolling_reserve(env: Env, scope: ReserveScope) -> Option<RollingReserveConfig> {
+        Storage::new(&env).get_rolling_reserve_config(&scope)
+    }
+
+    fn release_rolling_reserve(
+        env: Env,
+        merchant: Address,
+        token: Address,
+    ) -> Result<i128, PaymentError> {
+        if Self::is_paused(&env) {
+            return Err(PaymentError::ContractPaused);
+        }
+
+        // Anyone can release matured amounts; they only ever go to the merchant
+        let storage = Storage::new(&env);
+        let released = Self::release_matured_reserve(&env, &storage, &merchant, &token);
+        if released == 0 {
+            return Err(PaymentError::InsufficientBalance);
+        }
+
+        Ok(released)
+    }
+
+    fn get_rolling_reserve_balance(env: Env, merchant: Address, token: Address) -> i128 {
+        Storage::new(&env)
+            .get_reserve_tranches(&merchant)
+            .iter()
+            .filter(|tranche| tranche.token == token)
+            .map(|tranche| tranche.amount)
+            .sum()
+    }
+
+    fn get_reserve_tranches(env: Env, merchant: Address) -> Vec<ReserveTranche> {
+        Storage::new(&env).get_reserve_tranches(&merchant)
+    }
+
+    // Volume Limit Operations
+    fn set_volume_limits(
+        env: Env,
+        admin: Address,
+        scope: LimitScope,
+        limits: Vec<VolumeLimit>,
+    ) -> Result<(), PaymentError> {
+        Self::require_role(&env, &admin, Role::MerchantVerifier)?;
+
+        if limits.len() > MAX_VOLUME_LIMITS_PER_SCOPE {
+            return Err(PaymentError::InvalidTransactionLimit);
+        }
+        for limit in limits.iter() {
+            if limit.window == 0 || limit.window > MAX_VOLUME_LIMIT_WINDOW {
+                return Err(PaymentError::InvalidDateRange);
+            }
+            // Each limit must cap something, and caps must be positive
+            let caps_nothing = limit.max_amount.is_none() && limit.max_payments.is_none();
+            if caps_nothing
+                || limit.max_amount.is_some_and(|max| max <= 0)
+                || limit.max_payments == Some(0)
+            {
+                return Err(PaymentError::InvalidTransactionLimit);
+            }
+        }
+
+        let storage = Storage::new(&env);
+        storage.set_volume_limits(&scope, &limits);
+
+        env.events().publish(
+            (volume_limit_topic(&env), Symbol::new(&env, "configured")),
+            (admin, scope, limits),
+        );
+
+        Ok(())
+    }
+
+    fn get_volume_limits(env: Env, scope: LimitScope) -> Vec<VolumeLimit> {
+        Storage::new(&env).get_volume_limits(&scope)
+    }
+
+    fn get_volume_usage(env: Env, counter: VolumeCounter, window: u64) -> VolumeUsage {
+        if window == 0 {
+            return VolumeUsage::default();
+        }
+        Storage::new(&env).get_volume_usage(&counter, window, env.ledger().timestamp())
+    }
+
+    // Refund Management Operations
+    fn initiate_refund(
+        env: Env,
+        caller: Address,
+        refund_id: String,
+        merchant: Address,
+        order_id: String,
+        amount: i128,
+        reason: String,
+    ) -> Result<(), PaymentError> {
+        // Caller may be merchant or payer; require auth
+        caller.require_auth();
+        let storage = Storage::new(&env);
+        let payment = storage.get_payment(&merchant, &order_id)?;
+
+        // Validate caller is merchant or payer of the original payment
+        let is_merchant = caller == payment.merchant_address;
+        let is_payer = caller == payment.payer_address;
+
+        if !is_merchant && !is_payer {
+            return Err(PaymentError::NotAuthorized);
+        }
+
+        // Escrowed funds are returned through the escrow dispute flow, not by the merchant
+        if payment.is_escrow_open() {
+            return Err(PaymentError::NotRefundable);
+        }
+
+        // Payments under arbitration are refunded by the ruling
+        if storage.has_open_dispute(&merchant, &order_id) {
+            return Err(PaymentError::NotRefundable);
+        }
+
+        // Validate amount does not exceed remaining refundable
+        let already_refunded = payment.refunded_amount;
+        if amount > payment.amount - already_refunded {
+            return Err(PaymentError::ExceedsOriginalAmount);
+        }
+
+        // Validate refund window (30 days)
+        const MAX_REFUND_WINDOW: u64 = 30 * 24 * 60 * 60;
+        if env.ledger().timestamp() > payment.paid_at + MAX_REFUND_WINDOW {
+            return Err(PaymentError::RefundWindowExceeded);
+        }
+
+        // Create refund request (Pending)
+        let request = RefundRequest {
+            refund_id: refund_id.clone(),
+            order_id: order_id.clone(),
+            merchant_address: payment.merchant_address.clone(),
+            payer_address: payment.payer_address.clone(),
+            token: payment.token.clone(),
+            amount,
+            reason: reason.clone(),
+            requested_at: env.ledger().timestamp(),
+            status: RefundStatus::Pending,
+            approved_by: None,
+        };
+
+        storage.save_refund(&request);
+
+        // Event
+        env.events().publish(
+            ("refund_initiated",),
+            (refund_id.clone(), order_id.clone(), amount),
+        );
+
+        Ok(())
+    }
+
+    fn approve_refund(env: Env, caller: Address, refund_id: String) -> Result<(), PaymentError> {
+        caller.require_auth();
+        let storage = Storage::new(&env);
+        let mut req = storage.get_refund(&refund_id)?;
+
+        // Authorization: merchant of payment or refund agent
+        let authorized = caller == req.merchant_address
+            || Self::holds_role(&storage, &caller, Role::RefundAgent);
+
+        if !authorized {
+            return Err(PaymentError::NotAuthorized);
+        }
+
+        if let RefundStatus::Pending = req.status {
+            req.status = RefundStatus::Approved;
+            req.approved_by = Some(caller.clone());
+            storage.update_refund(&req);
+            env.events()
+                .publish(("refund_approved",), (refund_id.clone(),));
+            Ok(())
+        } else {
+            Err(PaymentError::InvalidRefundStatus)
+        }
+    }
+
+    fn reject_refund(env: Env, caller: Address, refund_id: String) -> Result<(), PaymentError> {
+        caller.require_auth();
+        let storage = Storage::new(&env);
+        let mut req = storage.get_refund(&refund_id)?;
+
+        let authorized = caller == req.merchant_address
+            || Self::holds_role(&storage, &caller, Role::RefundAgent);
+
+        if !authorized {
+            return Err(PaymentError::NotAuthorized);
+        }
+
+        if let RefundStatus::Pending = req.status {
+            req.status = RefundStatus::Rejected;
+            req.approved_by = Some(caller.clone());
+            storage.update_refund(&req);
+            env.events()
+                .publish(("refund_rejected",), (refund_id.clone(),));
+            Ok(())
+        } else {
+            Err(PaymentError::InvalidRefundStatus)
+        }
+    }
+
+    fn execute_refund(env: Env, refund_id: String) -> Result<(), PaymentError> {
+        let storage = Storage::new(&env);
+        let mut req = storage.get_refund(&refund_id)?;
+
+        // Must be approved
+        if let RefundStatus::Approved = req.status {
+        } else {
+            return Err(PaymentError::InvalidRefundStatus);
+        }
+
+        // Load payment to update and validate remaining amount again
+        let mut payment = storage.get_payment(&req.merchant_address, &req.order_id)?;
+        if req.amount > payment.amount - payment.refunded_amount {
+            return Err(PaymentError::ExceedsOriginalAmount);
+        }
+        if storage.has_open_dispute(&req.merchant_address, &req.order_id) {
+            return Err(PaymentError::NotRefundable);
+        }
+
+        let token_client = token::Client::new(&env, &req.token);
+
+        // Refund from the merchant's internal balance when it covers the amount
+        if storage.get_merchant_balance(&req.merchant_address, &req.token) >= req.amount {
+            // The balance is the merchant's money, so an approval alone can't spend it
+            req.merchant_address.require_auth();
+            storage.adjust_merchant_balance(&req.merchant_address, &req.token, -req.amount);
+            token_client.transfer(
+                &env.current_contract_address(),
+                &req.payer_address,
+                &req.amount,
+            );
+        } else if token_client.balance(&req.merchant_address) >= req.amount {
+            // Require merchant authorization for token transfer
+            req.merchant_address.require_auth();
+
+            // Transfer from merchant to payer
+            token_client.transfer(&req.merchant_address, &req.payer_address, &req.amount);
+        } else {
+            // The merchant's wallet is short, so the refund comes out of its rolling reserve,
+            // which is still the merchant's money
+            req.merchant_address.require_auth();
+            Self::draw_rolling_reserve(
+                &env,
+                &storage,
+                &req.merchant_address,
+                &req.token,
+                req.amount,
+            )?;
+            token_client.transfer(
+                &env.current_contract_address(),
+                &req.payer_address,
+                &req.amount,
+            );
+        }
+
+        // Update payment refunded amount
+        payment.refunded_amount = payment.refunded_amount + req.amount;
+        storage.update_payment(&payment);
+
+        // Mark refund completed
+        req.status = RefundStatus::Completed;
+        storage.update_refund(&req);
+
+        env.events()
+            .publish(("refund_executed",), (refund_id.clone(), req.amount));
+        Ok(())
+    }
+
+    fn get_refund_status(env: Env, refund_id: String) -> Result<RefundStatus, PaymentError> {
+        let storage = Storage::new(&env);
+        let req = storage.get_refund(&refund_id)?;
+        Ok(req.status)
+    }
+
+    // Payment History Query Operations
+    fn get_merchant_payment_history(
+        env: Env,
+        merchant: Address,
+        cursor: Option<PaymentKey>,
+        limit: u32,
+        filter: Option<PaymentQueryFilter>,
+        sort_field: Option<SortField>,
+        sort_order: Option<SortOrder>,
+    ) -> Result<PaymentQueryResult, PaymentError> {
+        // Require authorization - merchant or admin
+        Self::require_merchant_access(&env, &merchant)?;
+
+        // Validate query parameters
+        validate_query_limit(limit)?;
+        if let Some(ref f) = filter {
+            validate_query_filter(f)?;
+        }
+        let storage = Storage::new(&env);
+        // Get merchant payment indices
+        let keys = storage.get_merchant_payment_keys(&merchant);
+        validate_cursor(&env, &cursor, &keys)?;
+
+        let default_filter = PaymentQueryFilter {
+            date_start: None,
+            date_end: None,
+            amount_min: None,
+            amount_max: None,
+            token: None,
+            status: PaymentRecordStatus::Any,
+        };
+        let filter = filter.unwrap_or(default_filter);
+
+        // Apply filters
+        let filtered = storage.query_payments_with_filters(&keys, &filter);
+
+        // Sort
+        let sort_field = sort_field.unwrap_or(SortField::Date);
+        let sort_order = sort_order.unwrap_or(SortOrder::Descending);
+        let sorted = storage.sort_payments(filtered, &sort_field, &sort_order);
+
+        // Paginate
+        let (paginated, next_cursor) = storage.paginate_payments(sorted, cursor, limit);
+        let (next_cursor_merchant, next_cursor) = next_cursor.unzip();
+
+        Ok(PaymentQueryResult {
+            records: paginated,
+            next_cursor,
+            next_cursor_merchant,
+            total_count: keys.len(),
+        })
+    }
+
+    fn get_payer_payment_history(
+        env: Env,
+        payer: Address,
+        cursor: Option<PaymentKey>,
+        limit: u32,
+        filter: Option<PaymentQueryFilter>,
+        sort_field: Option<SortField>,
+        sort_order: Option<SortOrder>,
+    ) -> Result<PaymentQueryResult, PaymentError> {
+        // Require authorization - payer must match caller
+        Self::require_payer_access(&env, &payer)?;
+
+        // Validate query parameters
+        validate_query_limit(limit)?;
+        if let Some(ref f) = filter {
+            validate_query_filter(f)?;
+        }
+        let storage = Storage::new(&env);
+        // Get payer payment indices
+        let keys = storage.get_payer_payment_indices(&payer);
+        validate_cursor(&env, &cursor, &keys)?;
+
+        let default_filter = PaymentQueryFilter {
+            date_start: None,
+            date_end: None,
+            amount_min: None,
+            amount_max: None,
+            token: None,
+            status: PaymentRecordStatus::Any,
+        };
+        let filter = filter.unwrap_or(default_filter);
+
+        // Apply filters
+        let filtered = storage.query_payments_with_filters(&keys, &filter);
+
+        // Sort
+        let sort_field = sort_field.unwrap_or(SortField::Date);
+        let sort_order = sort_order.unwrap_or(SortOrder::Descending);
+        let sorted = storage.sort_payments(filtered, &sort_field, &sort_order);
+
+        // Paginate
+        let (paginated, next_cursor) = storage.paginate_payments(sorted, cursor, limit);
+        let (next_cursor_merchant, next_cursor) = next_cursor.unzip();
+
+        Ok(PaymentQueryResult {
+            records: paginated,
+            next_cursor,
+            next_cursor_merchant,
+            total_count: keys.len(),
+        })
+    }
+
+    fn get_payment_by_id(
+        env: Env,
+        caller: Address,
+        merchant: Address,
+        order_id: String,
+    ) -> Result<PaymentRecord, PaymentError> {
+        let storage = Storage::new(&env);
+        let payment = storage.get_payment(&merchant, &order_id)?;
+
+        // Verify authorization: merchant, payer, or refund agent
+        let is_merchant = payment.merchant_address == caller;
+        let is_payer = payment.payer_address == caller;
+        let is_agent = Self::holds_role(&storage, &caller, Role::RefundAgent);
+
+        if !is_merchant && !is_payer && !is_agent {
+            return Err(PaymentError::UnauthorizedQuery);
+        }
+
+        Ok(payment)
+    }
+
+    fn get_global_payment_stats(
+        env: Env,
+        admin: Address,
+        date_start: Option<u64>,
+        date_end: Option<u64>,
+    ) -> Result<PaymentStats, PaymentError> {
+        // Require admin authorization
+        Self::require_admin_access(&env, &admin)?;
+
+        // Validate date range
+        if let (Some(start), Some(end)) = (date_start, date_end) {
+            if end < start {
+                return Err(PaymentError::InvalidDateRange);
+            }
+        }
+
+        let storage = Storage::new(&env);
+        let payments = storage.get_payments_map();
+
+        let mut total_payments = 0u32;
+        let mut total_amount = 0i128;
+        let mut total_refunded = 0i128;
+        let mut completed_count = 0u32;
+        let mut partially_refunded_count = 0u32;
+        let mut fully_refunded_count = 0u32;
+
+        for payment in payments.values() {
+            // Apply date filter if provided
+            if let Some(start) = date_start {
+                if payment.paid_at < start {
+                    continue;
+                }
+            }
+            if let Some(end) = date_end {
+                if payment.paid_at > end {
+                    continue;
+                }
+            }
+
+            total_payments += 1;
+            total_amount += payment.amount;
+            total_refunded += payment.refunded_amount;
+
+            match payment.get_status() {
+                crate::types::PaymentRecordStatus::Completed => completed_count += 1,
+                crate::types::PaymentRecordStatus::PartiallyRefunded => {
+                    partially_refunded_count += 1
+                }
+                crate::types::PaymentRecordStatus::FullyRefunded => fully_refunded_count += 1,
+                crate::types::PaymentRecordStatus::Any => {
+                    // This should never happen from get_status(), but included for exhaustiveness
+                }
+            }
+        }
+
+        let average_amount = if total_payments > 0 {
+            total_amount / (total_payments as i128)
+        } else {
+            0i128
+        };
+
+        Ok(PaymentStats {
+            total_payments,
+            total_amount,
+            average_amount,
+            total_refunded,
+            completed_count,
+            partially_refunded_count,
+            fully_refunded_count,
+        })
+    }
+
+    // Payment History Management Operations
+    fn update_payment_status(
+        env: Env,
+        caller: Address,
+        merchant: Address,
+        order_id: String,
+        refunded_amount: i128,
+    ) -> Result<(), PaymentError> {
+        caller.require_auth();
+
+        let storage = Storage::new(&env);
+        let mut payment = storage.get_payment(&merchant, &order_id)?;
+
+        // Verify authorization: merchant or refund agent
+        let is_merchant = payment.merchant_address == caller;
+        let is_agent = Self::holds_role(&storage, &caller, Role::RefundAgent);
+
+        if !is_merchant && !is_agent {
+            return Err(PaymentError::UnauthorizedQuery);
+        }
+
+        // Validate refunded amount doesn't exceed original amount
+        if refunded_amount > payment.amount {
+            return Err(PaymentError::ExceedsOriginalAmount);
+        }
+        if refunded_amount < 0 {
+            return Err(PaymentError::InvalidAmount);
+        }
+
+        payment.refunded_amount = refunded_amount;
+        storage.update_payment(&payment);
+
+        env.events()
+            .publish(("payment_status_updated",), (order_id, refunded_amount));
+
+        Ok(())
+    }
+
+    fn archive_payment_record(
+        env: Env,
+        admin: Address,
+        merchant: Address,
+        order_id: String,
+    ) -> Result<(), PaymentError> {
+        // Require admin authorization
+        Self::require_role(&env, &admin, Role::Archiver)?;
+
+        let storage = Storage::new(&env);
+        let payment = storage.get_payment(&merchant, &order_id)?;
+
+        // Funds still held in escrow or under dispute must be settled first
+        if payment.is_escrow_open() || storage.has_open_dispute(&merchant, &order_id) {
+            return Err(PaymentError::InvalidStatus);
+        }
+
+        // Archive the payment
+        storage.archive_payment_record(&payment);
+
+        // Remove from active payments
+        storage.remove_payment(&merchant, &order_id);
+
+        // Remove from indices
+        storage.remove_merchant_payment_index(&merchant, &order_id);
+        storage.remove_payer_payment_index(&payment.payer_address, &payment.key());
+
+        env.events()
+            .publish(("payment_archived",), (merchant, order_id));
+
+        Ok(())
+    }
+
+    fn cleanup_expired_payments(env: Env, admin: Address) -> Result<u32, PaymentError> {
+        // Require admin authorization
+        Self::require_role(&env, &admin, Role::Archiver)?;
+
+        let storage = Storage::new(&env);
+        let cleanup_period = storage.get_cleanup_period();
+        let current_time = env.ledger().timestamp();
+        let cutoff_time = current_time.saturating_sub(cleanup_period);
+
+        let payments = storage.get_payments_map();
+        let mut cleaned_count = 0u32;
+        let mut to_cleanup = Vec::new(&env);
+
+        // Find expired payments
+        for payment in payments.values() {
+            if payment.paid_at < cutoff_time
+                && !payment.is_escrow_open()
+                && !storage.has_open_dispute(&payment.merchant_address, &payment.order_id)
+            {
+                to_cleanup.push_back(payment.key());
+            }
+        }
+
+        // Archive and remove expired payments
+        for (merchant, order_id) in to_cleanup.iter() {
+            if let Ok(payment) = storage.get_payment(&merchant, &order_id) {
+                // Archive before removal
+                storage.archive_payment_record(&payment);
+
+                // Remove from active payments
+                storage.remove_payment(&merchant, &order_id);
+
+                // Remove from indices
+                storage.remove_merchant_payment_index(&merchant, &order_id);
+                storage.remove_payer_payment_index(&payment.payer_address, &payment.key());
+
+                cleaned_count += 1;
+            }
+        }
+
+        env.events()
+            .publish(("payments_cleaned_up",), cleaned_count);
+
+        Ok(cleaned_count)
+    }
+
+    fn set_payment_cleanup_period(
+        env: Env,
+        admin: Address,
+        period: u64,
+    ) -> Result<(), PaymentError> {
+        // Require admin authorization
+        Self::require_role(&env, &admin, Role::Archiver)?;
+
+        let storage = Storage::new(&env);
+        Self::require_no_council(&storage)?;
+        Self::apply_cleanup_period(&env, &storage, period)
+    }
+}
+
+// Admin governance helper functions
+impl PaymentProcessingContract {
+    /// Sensitive admin actions need council approval once a council is set
+    fn require_no_council(storage: &Storage) -> Result<(), PaymentError> {
+        if storage.get_admin_council().is_some() {
+            return Err(PaymentError::ThresholdNotMet);
+        }
+        Ok(())
+    }
+
+    /// Carry out a proposal once enough current council members approved it
+    fn execute_if_approved(
+        env: &Env,
+        storage: &Storage,
+        council: &AdminCouncil,
+        proposal: &mut CouncilProposal,
+        approver: &Address,
+    ) -> Result<(), PaymentError> {
+        // Approvals from members who have since left the council don't count
+        let approvals = proposal
+            .approvals
+            .iter()
+            .filter(|member| council.members.contains(member))
+            .count() as u32;
+        if approvals < council.threshold {
+            return Ok(());
+        }
+
+        let by = approver.clone();
+        match proposal.action.clone() {
+            CouncilAction::SetFee(rate_bps, collector, token) => {
+                Self::apply_set_fee(storage, rate_bps, collector, token)?
+            }
+            CouncilAction::SetPaymentCleanupPeriod(period) => {
+                Self::apply_cleanup_period(env, storage, period)?
+            }
+            CouncilAction::GrantRole(account, role) => {
+                Self::apply_grant_role(env, storage, account, role, by)?
+            }
+            CouncilAction::RevokeRole(account, role) => {
+                Self::apply_revoke_role(env, storage, account, role, by)?
+            }
+            CouncilAction::ProposeSuperAdmin(new_admin) => {
+                Self::apply_propose_super_admin(env, storage, new_admin, by)
+            }
+            CouncilAction::SetFeeSchedule(schedule) => Self::apply_fee_schedule(storage, schedule)?,
+            CouncilAction::SetFeeOverride(scope, schedule) => {
+                Self::apply_fee_override(env, storage, scope, Some(schedule))?
+            }
+            CouncilAction::RemoveFeeOverride(scope) => {
+                Self::apply_fee_override(env, storage, scope, None)?
+            }
+            CouncilAction::SetTokenFee(token, fee_collector, schedule) => {
+                Self::apply_token_fee(env, storage, token, fee_collector, schedule)?
+            }
+            CouncilAction::RemoveTokenFee(token) => {
+                Self::apply_remove_token_fee(env, storage, token)
+            }
+            CouncilAction::SetFeeCustody(enabled) => Self::apply_fee_custody(env, storage, enabled),
+            CouncilAction::SetFeeBeneficiaries(beneficiaries) => {
+                Self::apply_fee_beneficiaries(env, storage, beneficiaries)?
+            }
+            CouncilAction::SetLegacySignatureDeadline(deadline) => {
+                Self::apply_legacy_signature_deadline(env, storage, deadline, by)
+            }
+            CouncilAction::AddArbitrator(arbitrator) => {
+                Self::apply_add_arbitrator(env, storage, arbitrator)
+            }
+            CouncilAction::RemoveArbitrator(arbitrator) => {
+                Self::apply_remove_arbitrator(env, storage, arbitrator)
+            }
+            CouncilAction::SetSwapRouter(router) => {
+                Self::apply_swap_router(env, storage, router, by)
+            }
+            CouncilAction::SetPriceOracle(config) => {
+                Self::apply_price_oracle(env, storage, config, by)?
+            }
+            CouncilAction::UpdateCouncil(new_council) => {
+                validate_admin_council(&new_council)?;
+                storage.set_admin_council(&Some(new_council.clone()));
+                env.events().publish(
+                    (council_topic(env), Symbol::new(env, "configured")),
+                    new_council,
+                );
+            }
+            CouncilAction::DissolveCouncil => {
+                storage.set_admin_council(&None);
+                env.events().publish(
+                    (council_topic(env), Symbol::new(env, "dissolved")),
+                    proposal.id,
+                );
+            }
+        }
+        proposal.executed = true;
+
+        env.events().publish(
+            (council_topic(env), Symbol::new(env, "executed")),
+            (proposal.id, proposal.action.clone()),
+        );
+
+        Ok(())
+    }
+
+    fn apply_set_fee(
+        storage: &Storage,
+        fee_rate_bps: u32,
+        fee_collector: Address,
+        fee_token: Address,
+    ) -> Result<(), PaymentError> {
+        let fee = Fee {
+            schedule: FeeSchedule::from_rate(fee_rate_bps),
+            fee_collector,
+            fee_token,
+        };
+        storage.set_fee_info(&fee)
+    }
+
+    fn apply_fee_schedule(storage: &Storage, schedule: FeeSchedule) -> Result<(), PaymentError> {
+        let fee = Fee {
+            schedule,
+            fee_collector: storage
+                .get_fee_collector()
+                .ok_or(PaymentError::AdminNotFound)?,
+            fee_token: storage.get_fee_token().ok_or(PaymentError::InvalidToken)?,
+        };
+        storage.set_fee_info(&fee)
+    }
+
+    fn apply_fee_override(
+        env: &Env,
+        storage: &Storage,
+        scope: FeeScope,
+        schedule: Option<FeeSchedule>,
+    ) -> Result<(), PaymentError> {
+        if let Some(schedule) = &schedule {
+            validate_fee_schedule(schedule)?;
+        }
+
+        storage.set_fee_override(&scope, &schedule);
+
+        env.events()
+            .publish(("fee_override_set",), (scope, schedule));
+
+        Ok(())
+    }
+
+    fn apply_token_fee(
+        env: &Env,
+        storage: &Storage,
+        token: Address,
+        fee_collector: Address,
+        schedule: FeeSchedule,
+    ) -> Result<(), PaymentError> {
+        validate_fee_schedule(&schedule)?;
+
+        storage.set_token_fee_collector(&token, &Some(fee_collector.clone()));
+        storage.set_fee_override(&FeeScope::Token(token.clone()), &Some(schedule.clone()));
+
+        env.events().publish(
+            ("token_fee_set",),
+            (token, fee_collector, schedule.rate_bps),
+        );
+
+        Ok(())
+    }
+
+    fn apply_remove_token_fee(env: &Env, storage: &Storage, token: Address) {
+        storage.set_token_fee_collector(&token, &None);
+        storage.set_fee_override(&FeeScope::Token(token.clone()), &None);
+
+        env.events().publish(("token_fee_removed",), token);
+    }
+
+    fn apply_fee_custody(env: &Env, storage: &Storage, enabled: bool) {
+        storage.set_fee_custody(enabled);
+        env.events().publish(("fee_custody_set",), enabled);
+    }
+
+    fn apply_fee_beneficiaries(
+        env: &Env,
+        storage: &Storage,
+        beneficiaries: Vec<FeeBeneficiary>,
+    ) -> Result<(), PaymentError> {
+        validate_fee_beneficiaries(&beneficiaries)?;
+
+        storage.set_fee_beneficiaries(&beneficiaries);
+        env.events()
+            .publish(("fee_beneficiaries_set",), beneficiaries.len());
+
+        Ok(())
+    }
+
+    fn apply_legacy_signature_deadline(env: &Env, storage: &Storage, deadline: u64, by: Address) {
+        storage.set_legacy_signature_deadline(deadline);
+
+        env.events()
+            .publish(("legacy_sig_deadline_set",), (by, deadline));
+    }
+
+    fn apply_add_arbitrator(env: &Env, storage: &Storage, arbitrator: Address) {
+        let mut arbitrators = storage.get_arbitrators();
+        if !arbitrators.contains(&arbitrator) {
+            arbitrators.push_back(arbitrator.clone());
+            storage.set_arbitrators(&arbitrators);
+            env.events().publish(
+                (dispute_topic(env), Symbol::new(env, "arbitrator_added")),
+                arbitrator,
+            );
+        }
+    }
+
+    fn apply_remove_arbitrator(env: &Env, storage: &Storage, arbitrator: Address) {
+        let mut arbitrators = storage.get_arbitrators();
+        if let Some(index) = arbitrators.first_index_of(&arbitrator) {
+            arbitrators.remove(index);
+            storage.set_arbitrators(&arbitrators);
+            env.events().publish(
+                (dispute_topic(env), Symbol::new(env, "arbitrator_removed")),
+                arbitrator,
+            );
+        }
+    }
+
+    fn apply_swap_router(env: &Env, storage: &Storage, router: Address, by: Address) {
+        storage.set_swap_router(&router);
+
+        env.events().publish(
+            (swap_topic(env), Symbol::new(env, "router_set")),
+            (by, router),
+        );
+    }
+
+    fn apply_price_oracle(
+        env: &Env,
+        storage: &Storage,
+        config: OracleConfig,
+        by: Address,
+    ) -> Result<(), PaymentError> {
+        if config.max_price_age == 0 {
+            return Err(PaymentError::InvalidDateRange);
+        }
+        if config.max_deviation_bps == 0
+            || i128::from(config.max_deviation_bps) > BASIS_POINTS_DENOMINATOR
+        {
+            return Err(PaymentError::InvalidAmount);
+        }
+
+        storage.set_price_oracle(&config);
+
+        env.events().publish(
+            (oracle_topic(env), Symbol::new(env, "configured")),
+            (by, config.oracle, config.base_currency),
+        );
+
+        Ok(())
+    }
+
+    fn apply_cleanup_period(env: &Env, storage: &Storage, period: u64) -> Result<(), PaymentError> {
+        // Validate period (max 10 years)
+        const MAX_CLEANUP_PERIOD: u64 = 10 * 365 * 24 * 60 * 60;
+        if period > MAX_CLEANUP_PERIOD {
+            return Err(PaymentError::InvalidDateRange);
+        }
+
+        storage.set_cleanup_period(period);
+
+        env.events().publish(("cleanup_period_set",), period);
+
+        Ok(())
+    }
+
+    fn apply_grant_role(
+        env: &Env,
+        storage: &Storage,
+        account: Address,
+        role: Role,
+        by: Address,
+    ) -> Result<(), PaymentError> {
+        if !storage.grant_role(role, &account) {
+            return Err(PaymentError::InvalidStatus);
+        }
+
+        env.events().publish(
+            (role_topic(env), Symbol::new(env, "granted")),
+            (role, account, by),
+        );
+
+        Ok(())
+    }
+
+    fn apply_revoke_role(
+        env: &Env,
+        storage: &Storage,
+        account: Address,
+        role: Role,
+        by: Address,
+    ) -> Result<(), PaymentError> {
+        if !storage.revoke_role(role, &account) {
+            return Err(PaymentError::InvalidStatus);
+        }
+
+        env.events().publish(
+            (role_topic(env), Symbol::new(env, "revoked")),
+            (role, account, by),
+        );
+
+        Ok(())
+    }
+
+    fn apply_propose_super_admin(env: &Env, storage: &Storage, new_admin: Address, by: Address) {
+        // A new proposal replaces any pending one
+        let expires_at = env
+            .ledger()
+            .timestamp()
+            .saturating_add(ADMIN_HANDOVER_WINDOW);
+        storage.set_pending_super_admin(&Some(AdminHandover {
+            new_admin: new_admin.clone(),
+            expires_at,
+        }));
+
+        env.events().publish(
+            (role_topic(env), Symbol::new(env, "super_admin_proposed")),
+            (by, new_admin, expires_at),
+        );
+    }
+}
+
+// Payment settlement helper functions
+/// Where a charge's funds come from
+#[derive(Clone, Copy, PartialEq)]
+enum FundsSource<'a> {
+    /// The payer authorized this invocation and transfers directly
+    Payer,
+    /// The contract pulls the funds from the payer's token allowance
+    Allowance,
+    /// The contract already holds up to `available` for the payer, e.g. an authorization hold
+    Held { available: i128 },
+    /// The payer pays in `token_in`, swapped through the router for at most `max_amount_in`
+    Swap {
+        token_in: &'a Address,
+        max_amount_in: i128,
+    },
+}
+
+/// A payment settled through the shared fee, escrow and record pipeline
+struct Charge<'a> {
+    payer: &'a Address,
+    merchant: &'a Address,
+    token: &'a Address,
+    order_id: &'a String,
+    amount: i128,
+    /// Fee the payer agreed to up front, which must match the computed fee in surcharge mode
+    agreed_fee: Option<i128>,
+    source: FundsSource<'a>,
+    invoice_id: Option<u64>,
+    /// Revenue shares the payer agreed to
+    splits: Option<&'a Vec<SplitRecipient>>,
+    /// Fiat price the amount was converted from
+    fiat: Option<FiatConversion>,
+}
+
+/// A fiat-denominated order amount and the rate it was converted at
+struct FiatConversion {
+    currency: Symbol,
+    amount: i128,
+    /// Price of one token unit in `currency`, scaled by `FIAT_RATE_SCALE`
+    rate: i128,
+}
+
+impl PaymentProcessingContract {
+    /// Validate, verify and settle one signed order for an already-authorized payer.
+    /// Shared by single and batch payments so both collect fees and write the same records.
+    fn settle_order(
+        env: &Env,
+        storage: &Storage,
+        payer: &Address,
+        order: &PaymentOrder,
+        merchant_public_key: &BytesN<32>,
+        signature: Option<&BytesN<64>>,
+        relayed: bool,
+    ) -> Result<PaymentRecord, PaymentError> {
+        // Resubmitting an already-settled order returns the existing record instead of charging twice
+        if let Some((existing, _)) =
+            storage.find_order_payment(&order.merchant_address, &order.order_id)
+        {
+            if existing.matches_order(payer, order) {
+                return Ok(existing);
             }
+            return Err(PaymentError::DuplicateOrderId);
+        }
 
-            total_payments += 1;
-            total_amount += payment.amount;
-            total_refunded += payment.refunded_amount;
+        // Verify the order hasn't expired (optimized timestamp check)
+        if env.ledger().timestamp() > order.expiration as u64 {
+            return Err(PaymentError::OrderExpired);
+        }
 
-            match payment.get_status() {
-                crate::types::PaymentRecordStatus::Completed => completed_count += 1,
-                crate::types::PaymentRecordStatus::PartiallyRefunded => {
-                    partially_refunded_count += 1
+        // Fiat orders are converted to the payment token at the oracle's current rate
+        let fiat = match &order.currency {
+            Some(currency) => {
+                let rate = Self::fiat_rate(env, storage, &order.token, currency)?;
+                Some(FiatConversion {
+                    currency: currency.clone(),
+                    amount: i128::from(order.amount),
+                    rate,
+                })
+            }
+            None => None,
+        };
+        let amount = match &fiat {
+            Some(fiat) => fiat_to_token_amount(fiat.amount, fiat.rate)?,
+            None => order.amount as i128,
+        };
+
+        let merchant = Self::load_chargeable_merchant(
+            env,
+            storage,
+            &order.merchant_address,
+            &order.token,
+            amount,
+        )?;
+
+        validate_split_recipients(&order.merchant_address, &order.splits)?;
+
+        // Cross-token orders carry the payer's input token and the merchant-signed slippage limit
+        let source = match &order.input_token {
+            Some(input_token) => {
+                // The swap is funded by a direct transfer, which a relayer can't authorize
+                if *input_token == order.token || relayed {
+                    return Err(PaymentError::InvalidToken);
                 }
-                crate::types::PaymentRecordStatus::FullyRefunded => fully_refunded_count += 1,
-                crate::types::PaymentRecordStatus::Any => {
-                    // This should never happen from get_status(), but included for exhaustiveness
+                if order.max_input_amount <= 0 {
+                    return Err(PaymentError::InvalidAmount);
+                }
+                FundsSource::Swap {
+                    token_in: input_token,
+                    max_amount_in: order.max_input_amount,
+                }
+            }
+            // Relayed payments pull from the allowance the payer granted the contract
+            None if relayed => FundsSource::Allowance,
+            None => FundsSource::Payer,
+        };
+
+        // Verify the nonce hasn't been used
+        if storage.is_nonce_used(&order.merchant_address, order.nonce) {
+            return Err(PaymentError::NonceAlreadyUsed);
+        }
+
+        // Verify the signing key is registered to this merchant and currently valid
+        storage.require_active_signing_key(&order.merchant_address, merchant_public_key)?;
+
+        // Build the signed message for the order's schema version
+        let message = create_order_message(env, storage, order)?;
+        // Verify signature
+        if let Some(signature) = signature {
+            env.crypto()
+                .ed25519_verify(merchant_public_key, &message, signature);
+        }
+
+        let payment_record = Self::settle_charge(
+            env,
+            storage,
+            merchant,
+            &Charge {
+                payer,
+                merchant: &order.merchant_address,
+                token: &order.token,
+                order_id: &order.order_id,
+                amount,
+                // A fiat order's token fee isn't known until conversion, so none was agreed
+                agreed_fee: fiat.is_none().then_some(order.fee_amount),
+                source,
+                invoice_id: None,
+                splits: Some(&order.splits),
+                fiat,
+            },
+        )?;
+
+        // Record used nonce (optimized bitmap storage)
+        storage.mark_nonce_used(&order.merchant_address, order.nonce);
+
+        Ok(payment_record)
+    }
+
+    /// Load a merchant that can currently be paid `amount` in `token`
+    fn load_chargeable_merchant(
+        env: &Env,
+        storage: &Storage,
+        merchant_address: &Address,
+        token: &Address,
+        amount: i128,
+    ) -> Result<Merchant, PaymentError> {
+        // Verify merchant exists and is active
+        let merchant = storage.get_merchant(merchant_address)?;
+        if !merchant.active {
+            return Err(PaymentError::MerchantInactive);
+        }
+
+        // Verify token is supported by merchant (optimized lookup)
+        if !merchant.supports_token(token) {
+            return Err(PaymentError::InvalidToken);
+        }
+
+        // Verify transaction limit
+        if amount > Self::transaction_limit(env, storage, &merchant, token) {
+            return Err(PaymentError::TransactionLimitExceeded);
+        }
+
+        Ok(merchant)
+    }
+
+    /// A merchant's per-payment limit in `token`'s base units: its own limit for the token if set,
+    /// else its default limit scaled to the token's decimals
+    fn transaction_limit(
+        env: &Env,
+        storage: &Storage,
+        merchant: &Merchant,
+        token: &Address,
+    ) -> i128 {
+        merchant.token_limits.get(token.clone()).unwrap_or_else(|| {
+            scale_transaction_limit(
+                merchant.max_transaction_limit,
+                Self::token_decimals(env, storage, token),
+            )
+        })
+    }
+
+    /// Decimals of a token, read from its metadata on first use and cached
+    fn token_decimals(env: &Env, storage: &Storage, token: &Address) -> u32 {
+        storage.get_token_decimals(token).unwrap_or_else(|| {
+            let decimals = token::Client::new(env, token).decimals();
+            storage.set_token_decimals(token, decimals);
+            decimals
+        })
+    }
+
+    /// Load a subscription that `caller`, its payer or merchant, is authorized to manage
+    fn load_subscription_for_party<'a>(
+        env: &'a Env,
+        caller: &Address,
+        subscription_id: u64,
+    ) -> Result<(Storage<'a>, Subscription), PaymentError> {
+        caller.require_auth();
+
+        let storage = Storage::new(env);
+        let subscription = storage.get_subscription(subscription_id)?;
+        if *caller != subscription.payer_address && *caller != subscription.merchant_address {
+            return Err(PaymentError::NotAuthorized);
+        }
+
+        Ok((storage, subscription))
+    }
+
+    /// Move a validated charge's funds, collect its fee and record the payment
+    fn settle_charge(
+        env: &Env,
+        storage: &Storage,
+        mut merchant: Merchant,
+        charge: &Charge,
+    ) -> Result<PaymentRecord, PaymentError> {
+        // Fees are collected in the payment token, by that token's collector
+        let fee_collector = storage
+            .resolve_fee_collector(charge.token)
+            .ok_or(PaymentError::AdminNotFound)?;
+
+        let fee_amount = storage.calculate_fee(&merchant, charge.token, charge.amount);
+
+        if fee_amount < 0 {
+            return Err(PaymentError::InvalidAmount);
+        }
+
+        // Deduct mode takes the fee out of the merchant's amount; surcharge mode adds it on top
+        let amount = charge.amount;
+        let fee_mode = storage.get_fee_mode(charge.merchant);
+        let (gross_amount, net_amount) = match fee_mode {
+            FeeMode::Deduct => (amount, amount - fee_amount),
+            FeeMode::Surcharge => {
+                // The payer agreed to the signed fee, so it must match what is charged
+                if charge.agreed_fee.is_some_and(|agreed| agreed != fee_amount) {
+                    return Err(PaymentError::InvalidAmount);
+                }
+                (amount + fee_amount, amount)
+            }
+        };
+
+        // Process the payment using Stellar token contract
+        let payment_token_client = token::Client::new(env, charge.token);
+        let contract = env.current_contract_address();
+
+        // Check the balance up front so a short payer fails cleanly instead of trapping in the token
+        let available = match charge.source {
+            FundsSource::Payer => payment_token_client.balance(charge.payer),
+            FundsSource::Allowance => payment_token_client
+                .balance(charge.payer)
+                .min(payment_token_client.allowance(charge.payer, &contract)),
+            FundsSource::Held { available } => available,
+            // The swap delivers the gross amount exactly, so the payer must cover its input limit
+            FundsSource::Swap {
+                token_in,
+                max_amount_in,
+            } => {
+                if token::Client::new(env, token_in).balance(charge.payer) < max_amount_in {
+                    return Err(PaymentError::InsufficientBalance);
+                }
+                gross_amount
+            }
+        };
+        if available < gross_amount {
+            return Err(PaymentError::InsufficientBalance);
+        }
+
+        let volume_usage = Self::check_volume_limits(env, storage, charge)?;
+
+        // Escrowed merchants' funds are held by the contract until release
+        let (escrow_status, escrow_release_at) = match storage.get_escrow_timeout(charge.merchant) {
+            Some(timeout) => (
+                EscrowStatus::Held,
+                env.ledger().timestamp().saturating_add(timeout),
+            ),
+            None => (EscrowStatus::None, 0),
+        };
+
+        // Revenue shares come out of the net amount and the merchant keeps the rest
+        let mut split_payouts = Vec::new(env);
+        let mut merchant_amount = net_amount;
+        if let Some(splits) = charge.splits {
+            // Escrow payouts go to the merchant alone, so they can't honor a split
+            if !splits.is_empty() && escrow_status == EscrowStatus::Held {
+                return Err(PaymentError::InvalidStatus);
+            }
+            for split in splits.iter() {
+                let share = net_amount * i128::from(split.share_bps) / BASIS_POINTS_DENOMINATOR;
+                merchant_amount -= share;
+                split_payouts.push_back(SplitPayout {
+                    recipient: split.recipient,
+                    amount: share,
+                });
+            }
+        }
+
+        // Cross-token payments are swapped into the payment token, held by the contract
+        let (input_token, input_amount) = match charge.source {
+            FundsSource::Swap {
+                token_in,
+                max_amount_in,
+            } => {
+                let input_amount = Self::swap_for_charge(
+                    env,
+                    storage,
+                    charge,
+                    token_in,
+                    max_amount_in,
+                    gross_amount,
+                )?;
+                (Some(token_in.clone()), input_amount)
+            }
+            _ => (None, 0),
+        };
+
+        // Transfer merchant amount first
+        if escrow_status == EscrowStatus::Held {
+            Self::collect_funds(env, &payment_token_client, charge, &contract, net_amount);
+        } else {
+            // Part of the merchant's amount is held back in its rolling reserve
+            let reserve_amount = Self::hold_rolling_reserve(
+                env,
+                storage,
+                &merchant,
+                charge.merchant,
+                charge.token,
+                charge.order_id,
+                merchant_amount,
+            );
+            if reserve_amount > 0 {
+                Self::collect_funds(
+                    env,
+                    &payment_token_client,
+                    charge,
+                    &contract,
+                    reserve_amount,
+                );
+                merchant_amount -= reserve_amount;
+            }
+
+            // Merchants on an internal balance are credited instead of paid out
+            if merchant_amount > 0 && storage.get_settlement_config(charge.merchant).is_some() {
+                Self::collect_funds(
+                    env,
+                    &payment_token_client,
+                    charge,
+                    &contract,
+                    merchant_amount,
+                );
+                storage.adjust_merchant_balance(charge.merchant, charge.token, merchant_amount);
+            } else if merchant_amount > 0 {
+                Self::collect_funds(
+                    env,
+                    &payment_token_client,
+                    charge,
+                    charge.merchant,
+                    merchant_amount,
+                );
+            }
+            for payout in split_payouts.iter() {
+                if payout.amount > 0 {
+                    Self::collect_funds(
+                        env,
+                        &payment_token_client,
+                        charge,
+                        &payout.recipient,
+                        payout.amount,
+                    );
+                }
+            }
+        }
+
+        // Then transfer fee if applicable
+        if fee_amount > 0 && storage.is_fee_custody_enabled() {
+            // The contract holds the fee and credits it to the beneficiaries' ledger
+            Self::collect_funds(env, &payment_token_client, charge, &contract, fee_amount);
+            Self::accrue_fee(storage, charge.token, &fee_collector, fee_amount);
+            env.events().publish(
+                ("fee_accrued",),
+                (charge.token.clone(), fee_amount, charge.order_id.clone()),
+            );
+        } else if fee_amount > 0 {
+            Self::collect_funds(
+                env,
+                &payment_token_client,
+                charge,
+                &fee_collector,
+                fee_amount,
+            );
+            env.events().publish(
+                ("fee_collected",),
+                (fee_collector.clone(), fee_amount, charge.order_id.clone()),
+            );
+        }
+
+        // Amounts held long enough go back to the merchant with its next payment
+        Self::release_matured_reserve(env, storage, charge.merchant, charge.token);
+
+        // Update merchant's last activity timestamp
+        merchant.last_activity_timestamp = env.ledger().timestamp();
+        storage.save_merchant(charge.merchant, &merchant);
+
+        // Record payment history
+        let payment_record = PaymentRecord {
+            order_id: charge.order_id.clone(),
+            merchant_address: charge.merchant.clone(),
+            payer_address: charge.payer.clone(),
+            token: charge.token.clone(),
+            amount,
+            paid_at: env.ledger().timestamp(),
+            refunded_amount: 0,
+            gross_amount,
+            fee_amount,
+            net_amount,
+            escrow_status,
+            escrow_release_at,
+            invoice_id: charge.invoice_id,
+            splits: split_payouts,
+            input_token,
+            input_amount,
+            fiat_currency: charge.fiat.as_ref().map(|fiat| fiat.currency.clone()),
+            fiat_amount: charge.fiat.as_ref().map_or(0, |fiat| fiat.amount),
+            fiat_rate: charge.fiat.as_ref().map_or(0, |fiat| fiat.rate),
+        };
+        storage.save_payment(&payment_record);
+        Self::record_volume_usage(env, storage, charge, &volume_usage);
+
+        env.events().publish(
+            (payment_topic(env), Symbol::new(env, "processed")),
+            PaymentProcessedEvent {
+                order_id: charge.order_id.clone(),
+                merchant: charge.merchant.clone(),
+                payer: charge.payer.clone(),
+                token: charge.token.clone(),
+                gross_amount,
+                fee_amount,
+                net_amount,
+                fee_mode,
+            },
+        );
+
+        // Maintain payment indices for efficient querying
+        storage.save_merchant_payment_index(charge.merchant, charge.order_id);
+        storage.save_payer_payment_index(charge.payer, &payment_record.key());
+
+        Ok(payment_record)
+    }
+
+    /// Check a charge against every volume limit that applies to it. Returns each limit's
+    /// counter with its usage including the charge, to record once the charge settles.
+    fn check_volume_limits(
+        env: &Env,
+        storage: &Storage,
+        charge: &Charge,
+    ) -> Result<Vec<(VolumeCounter, VolumeLimit, VolumeUsage)>, PaymentError> {
+        let (payer, merchant, token) = (charge.payer, charge.merchant, charge.token);
+        let applicable = [
+            (
+                LimitScope::Merchant(merchant.clone(), token.clone()),
+                VolumeCounter::Merchant(merchant.clone(), token.clone()),
+            ),
+            (
+                LimitScope::Token(token.clone()),
+                VolumeCounter::Token(token.clone()),
+            ),
+            (
+                LimitScope::Payer(token.clone()),
+                VolumeCounter::Payer(payer.clone(), token.clone()),
+            ),
+            (
+                LimitScope::PayerMerchant(merchant.clone(), token.clone()),
+                VolumeCounter::PayerMerchant(payer.clone(), merchant.clone(), token.clone()),
+            ),
+        ];
+
+        let now = env.ledger().timestamp();
+        let mut updated = Vec::new(env);
+        for (scope, counter) in applicable {
+            for limit in storage.get_volume_limits(&scope).iter() {
+                let mut usage = storage.get_volume_usage(&counter, limit.window, now);
+                usage.amount = usage.amount.saturating_add(charge.amount);
+                usage.payments = usage.payments.saturating_add(1);
+
+                let breached = limit.max_amount.is_some_and(|max| usage.amount > max)
+                    || limit.max_payments.is_some_and(|max| usage.payments > max);
+                if breached {
+                    env.events().publish(
+                        (volume_limit_topic(env), Symbol::new(env, "breached")),
+                        VolumeLimitEvent {
+                            order_id: charge.order_id.clone(),
+                            counter,
+                            limit,
+                            usage,
+                            timestamp: now,
+                        },
+                    );
+                    return Err(match scope {
+                        LimitScope::Merchant(..) => PaymentError::MerchantVolumeLimitExceeded,
+                        LimitScope::Token(_) => PaymentError::TokenVolumeLimitExceeded,
+                        LimitScope::Payer(_) => PaymentError::PayerVelocityLimitExceeded,
+                        LimitScope::PayerMerchant(..) => PaymentError::PayerMerchantLimitExceeded,
+                    });
                 }
+                updated.push_back((counter.clone(), limit, usage));
+            }
+        }
+
+        Ok(updated)
+    }
+
+    /// Save the usage a settled charge added, flagging counters it took past the warning level
+    fn record_volume_usage(
+        env: &Env,
+        storage: &Storage,
+        charge: &Charge,
+        updated: &Vec<(VolumeCounter, VolumeLimit, VolumeUsage)>,
+    ) {
+        let now = env.ledger().timestamp();
+        let crosses_warning = |before: i128, after: i128, max: i128| {
+            let warning = max.saturating_mul(VOLUME_WARNING_BPS);
+            before.saturating_mul(BASIS_POINTS_DENOMINATOR) < warning
+                && after.saturating_mul(BASIS_POINTS_DENOMINATOR) >= warning
+        };
+
+        for (counter, limit, usage) in updated.iter() {
+            storage.save_volume_usage(&counter, limit.window, now, &usage);
+
+            let amount_warning = limit.max_amount.is_some_and(|max| {
+                crosses_warning(usage.amount - charge.amount, usage.amount, max)
+            });
+            let payments_warning = limit.max_payments.is_some_and(|max| {
+                let payments = i128::from(usage.payments);
+                crosses_warning(payments - 1, payments, i128::from(max))
+            });
+            if amount_warning || payments_warning {
+                env.events().publish(
+                    (volume_limit_topic(env), Symbol::new(env, "approaching")),
+                    VolumeLimitEvent {
+                        order_id: charge.order_id.clone(),
+                        counter,
+                        limit,
+                        usage,
+                        timestamp: now,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Transfer part of a charge from the payer to `to`
+    fn collect_funds(
+        env: &Env,
+        token_client: &token::Client,
+        charge: &Charge,
+        to: &Address,
+        amount: i128,
+    ) {
+        let contract = env.current_contract_address();
+        match charge.source {
+            FundsSource::Payer => token_client.transfer(charge.payer, to, &amount),
+            FundsSource::Allowance => {
+                token_client.transfer_from(&contract, charge.payer, to, &amount)
+            }
+            // Held funds and swap proceeds meant to stay with the contract don't move
+            FundsSource::Held { .. } | FundsSource::Swap { .. } if *to == contract => {}
+            FundsSource::Held { .. } | FundsSource::Swap { .. } => {
+                token_client.transfer(&contract, to, &amount)
             }
         }
+    }
 
-        let average_amount = if total_payments > 0 {
-            total_amount / (total_payments as i128)
+    /// Swap the payer's `token_in` into exactly `amount_out` of the charge token, held by the contract.
+    /// Returns the input the router consumed; the unused input goes back to the payer.
+    fn swap_for_charge(
+        env: &Env,
+        storage: &Storage,
+        charge: &Charge,
+        token_in: &Address,
+        max_amount_in: i128,
+        amount_out: i128,
+    ) -> Result<i128, PaymentError> {
+        let router = storage
+            .get_swap_router()
+            .ok_or(PaymentError::InvalidToken)?;
+        let contract = env.current_contract_address();
+        let input_client = token::Client::new(env, token_in);
+        let output_client = token::Client::new(env, charge.token);
+        let input_before = input_client.balance(&contract);
+        let output_before = output_client.balance(&contract);
+
+        // The router is funded with the slippage limit up front and returns what it doesn't use
+        input_client.transfer(charge.payer, &router, &max_amount_in);
+        SwapRouterClient::new(env, &router).swap_exact_out(
+            token_in,
+            charge.token,
+            &amount_out,
+            &max_amount_in,
+            &contract,
+        );
+
+        // Settle on the balances the contract actually received rather than the router's report
+        let received = output_client.balance(&contract) - output_before;
+        if received < amount_out {
+            return Err(PaymentError::InsufficientBalance);
+        }
+        if received > amount_out {
+            output_client.transfer(&contract, charge.payer, &(received - amount_out));
+        }
+        let unused = input_client.balance(&contract) - input_before;
+        if unused > 0 {
+            input_client.transfer(&contract, charge.payer, &unused);
+        }
+        let input_amount = max_amount_in - unused;
+
+        env.events().publish(
+            (swap_topic(env), Symbol::new(env, "executed")),
+            (
+                charge.order_id.clone(),
+                token_in.clone(),
+                input_amount,
+                charge.token.clone(),
+                amount_out,
+            ),
+        );
+
+        Ok(input_amount)
+    }
+
+    /// Price of one `token` unit in `currency`, scaled by `FIAT_RATE_SCALE`, from the configured oracle
+    fn fiat_rate(
+        env: &Env,
+        storage: &Storage,
+        token: &Address,
+        currency: &Symbol,
+    ) -> Result<i128, PaymentError> {
+        let config = storage
+            .get_price_oracle()
+            .ok_or(PaymentError::InvalidToken)?;
+        let oracle = PriceOracleClient::new(env, &config.oracle);
+
+        let token_price =
+            Self::checked_oracle_price(env, &oracle, &config, OracleAsset::Stellar(token.clone()))?;
+        // The oracle's base currency is worth exactly one unit at its own precision
+        let currency_price = if *currency == config.base_currency {
+            10i128
+                .checked_pow(oracle.decimals())
+                .ok_or(PaymentError::InvalidAmount)?
         } else {
-            0i128
+            Self::checked_oracle_price(env, &oracle, &config, OracleAsset::Other(currency.clone()))?
         };
 
-        Ok(PaymentStats {
-            total_payments,
-            total_amount,
-            average_amount,
-            total_refunded,
-            completed_count,
-            partially_refunded_count,
-            fully_refunded_count,
-        })
+        let rate = token_price
+            .checked_mul(FIAT_RATE_SCALE)
+            .ok_or(PaymentError::InvalidAmount)?
+            / currency_price;
+        if rate <= 0 {
+            return Err(PaymentError::InvalidAmount);
+        }
+        Ok(rate)
     }
 
-    // Payment History Management Operations
-    fn update_payment_status(
-        env: Env,
-        caller: Address,
-        order_id: String,
-        refunded_amount: i128,
-    ) -> Result<(), PaymentError> {
-        caller.require_auth();
+    /// Latest oracle price for `asset`, rejected if stale or too far from the previous price
+    fn checked_oracle_price(
+        env: &Env,
+        oracle: &PriceOracleClient,
+        config: &OracleConfig,
+        asset: OracleAsset,
+    ) -> Result<i128, PaymentError> {
+        let latest = oracle.lastprice(&asset).ok_or(PaymentError::InvalidToken)?;
+        if latest.price <= 0 {
+            return Err(PaymentError::InvalidAmount);
+        }
+        if env.ledger().timestamp() > latest.timestamp.saturating_add(config.max_price_age) {
+            return Err(PaymentError::PaymentExpired);
+        }
 
-        let storage = Storage::new(&env);
-        let mut payment = storage.get_payment(&order_id)?;
+        // A sudden jump from the previous price suggests a faulty or manipulated feed
+        let previous = oracle
+            .prices(&asset, &2)
+            .and_then(|history| history.iter().find(|p| p.timestamp < latest.timestamp));
+        if let Some(previous) = previous {
+            if previous.price > 0 {
+                let deviation = (latest.price - previous.price).abs() * BASIS_POINTS_DENOMINATOR
+                    / previous.price;
+                if deviation > i128::from(config.max_deviation_bps) {
+                    return Err(PaymentError::InvalidAmount);
+                }
+            }
+        }
 
-        // Verify authorization: merchant or admin
-        let is_merchant = payment.merchant_address == caller;
-        let is_admin = storage.get_admin().map(|a| a == caller).unwrap_or(false);
+        Ok(latest.price)
+    }
 
-        if !is_merchant && !is_admin {
-            return Err(PaymentError::UnauthorizedQuery);
+    /// Close an authorization hold, returning whatever wasn't captured to the payer
+    fn close_authorization(
+        env: &Env,
+        storage: &Storage,
+        authorization: &mut PaymentAuthorization,
+        status: AuthorizationStatus,
+    ) {
+        let remainder = authorization.max_amount - authorization.captured_amount;
+        if remainder > 0 {
+            let token_client = token::Client::new(env, &authorization.token);
+            token_client.transfer(
+                &env.current_contract_address(),
+                &authorization.payer_address,
+                &remainder,
+            );
         }
 
-        // Validate refunded amount doesn't exceed original amount
-        if refunded_amount > payment.amount {
-            return Err(PaymentError::ExceedsOriginalAmount);
-        }
-        if refunded_amount < 0 {
+        authorization.status = status;
+        storage.save_authorization(authorization);
+
+        env.events().publish(
+            (authorization_topic(env), Symbol::new(env, "closed")),
+            (authorization.authorization_id, status, remainder),
+        );
+    }
+
+    /// Pay out a held or disputed escrow: `refund_amount` back to the payer, the rest to the merchant
+    fn payout_escrow(
+        env: &Env,
+        storage: &Storage,
+        payment: &mut PaymentRecord,
+        refund_amount: i128,
+    ) -> Result<(), PaymentError> {
+        if refund_amount < 0 || refund_amount > payment.net_amount {
             return Err(PaymentError::InvalidAmount);
         }
+        let merchant_amount = payment.net_amount - refund_amount;
 
-        payment.refunded_amount = refunded_amount;
-        storage.update_payment(&payment);
+        let token_client = token::Client::new(env, &payment.token);
+        let contract = env.current_contract_address();
+        if refund_amount > 0 {
+            token_client.transfer(&contract, &payment.payer_address, &refund_amount);
+        }
+        // The rolling reserve is already with the contract, so it only needs recording
+        let merchant = storage.get_merchant(&payment.merchant_address)?;
+        let reserve_amount = Self::hold_rolling_reserve(
+            env,
+            storage,
+            &merchant,
+            &payment.merchant_address,
+            &payment.token,
+            &payment.order_id,
+            merchant_amount,
+        );
+        Self::pay_merchant(
+            env,
+            storage,
+            &payment.merchant_address,
+            &payment.token,
+            merchant_amount - reserve_amount,
+        );
 
-        env.events()
-            .publish(("payment_status_updated",), (order_id, refunded_amount));
+        payment.refunded_amount += refund_amount;
+        payment.escrow_status = EscrowStatus::Released;
+        storage.update_payment(payment);
+
+        env.events().publish(
+            (escrow_topic(env), Symbol::new(env, "released")),
+            EscrowReleasedEvent {
+                order_id: payment.order_id.clone(),
+                merchant_amount,
+                refund_amount,
+                timestamp: env.ledger().timestamp(),
+            },
+        );
 
         Ok(())
     }
 
-    fn archive_payment_record(
-        env: Env,
-        admin: Address,
-        order_id: String,
-    ) -> Result<(), PaymentError> {
-        // Require admin authorization
-        Self::require_admin_access(&env, &admin)?;
-
-        let storage = Storage::new(&env);
-        let payment = storage.get_payment(&order_id)?;
-
-        // Archive the payment
-        storage.archive_payment_record(&payment);
+    /// Pay a merchant from funds the contract holds, crediting its internal balance if it has one
+    fn pay_merchant(
+        env: &Env,
+        storage: &Storage,
+        merchant: &Address,
+        token: &Address,
+        amount: i128,
+    ) {
+        if amount <= 0 {
+            return;
+        }
+        if storage.get_settlement_config(merchant).is_some() {
+            storage.adjust_merchant_balance(merchant, token, amount);
+        } else {
+            token::Client::new(env, token).transfer(
+                &env.current_contract_address(),
+                merchant,
+                &amount,
+            );
+        }
+    }
 
-        // Remove from active payments
-        let mut payments = storage.get_payments_map();
-        payments.remove(order_id.clone());
+    /// Record the merchant's rolling reserve share of `amount`, which the contract must hold.
+    /// Returns the amount held back.
+    fn hold_rolling_reserve(
+        env: &Env,
+        storage: &Storage,
+        merchant: &Merchant,
+        merchant_address: &Address,
+        token: &Address,
+        order_id: &String,
+        amount: i128,
+    ) -> i128 {
+        let Some(config) = storage.resolve_rolling_reserve(merchant_address, &merchant.category)
+        else {
+            return 0;
+        };
+        let held = amount * i128::from(config.rate_bps) / BASIS_POINTS_DENOMINATOR;
+        if held <= 0 {
+            return 0;
+        }
 
-        // Remove from indices
-        storage.remove_merchant_payment_index(&payment.merchant_address, &order_id);
-        storage.remove_payer_payment_index(&payment.payer_address, &order_id);
+        let release_at = env.ledger().timestamp().saturating_add(config.hold_period);
+        let mut tranches = storage.get_reserve_tranches(merchant_address);
+        tranches.push_back(ReserveTranche {
+            order_id: order_id.clone(),
+            token: token.clone(),
+            amount: held,
+            release_at,
+        });
+        storage.save_reserve_tranches(merchant_address, &tranches);
 
-        env.events().publish(("payment_archived",), order_id);
+        env.events().publish(
+            (reserve_topic(env), Symbol::new(env, "held")),
+            (
+                merchant_address.clone(),
+                token.clone(),
+                order_id.clone(),
+                held,
+                release_at,
+            ),
+        );
 
-        Ok(())
+        held
     }
 
-    fn cleanup_expired_payments(env: Env, admin: Address) -> Result<u32, PaymentError> {
-        // Require admin authorization
-        Self::require_admin_access(&env, &admin)?;
+    /// Pay out the merchant's reserve tranches in `token` whose hold period has ended
+    fn release_matured_reserve(
+        env: &Env,
+        storage: &Storage,
+        merchant: &Address,
+        token: &Address,
+    ) -> i128 {
+        let now = env.ledger().timestamp();
+        let mut remaining = Vec::new(env);
+        let mut released = 0;
+        for tranche in storage.get_reserve_tranches(merchant).iter() {
+            if tranche.token == *token && tranche.release_at <= now {
+                released += tranche.amount;
+            } else {
+                remaining.push_back(tranche);
+            }
+        }
+        if released == 0 {
+            return 0;
+        }
 
-        let storage = Storage::new(&env);
-        let cleanup_period = storage.get_cleanup_period();
-        let current_time = env.ledger().timestamp();
-        let cutoff_time = current_time.saturating_sub(cleanup_period);
+        storage.save_reserve_tranches(merchant, &remaining);
+        Self::pay_merchant(env, storage, merchant, token, released);
 
-        let payments = storage.get_payments_map();
-        let mut cleaned_count = 0u32;
-        let mut to_cleanup = Vec::new(&env);
+        env.events().publish(
+            (reserve_topic(env), Symbol::new(env, "released")),
+            (merchant.clone(), token.clone(), released),
+        );
 
-        // Find expired payments
-        for payment in payments.values() {
-            if payment.paid_at < cutoff_time {
-                to_cleanup.push_back(payment.order_id.clone());
+        released
+    }
+
+    /// Take `amount` of `token` out of the merchant's rolling reserve, oldest tranches first.
+    /// The funds stay with the contract for the caller to pay out.
+    fn draw_rolling_reserve(
+        env: &Env,
+        storage: &Storage,
+        merchant: &Address,
+        token: &Address,
+        amount: i128,
+    ) -> Result<(), PaymentError> {
+        let mut outstanding = amount;
+        let mut remaining = Vec::new(env);
+        for mut tranche in storage.get_reserve_tranches(merchant).iter() {
+            if tranche.token == *token && outstanding > 0 {
+                let drawn = tranche.amount.min(outstanding);
+                outstanding -= drawn;
+                tranche.amount -= drawn;
             }
+            if tranche.amount > 0 {
+                remaining.push_back(tranche);
+            }
+        }
+        if outstanding > 0 {
+            return Err(PaymentError::InsufficientBalance);
         }
 
-        // Archive and remove expired payments
-        for order_id in to_cleanup.iter() {
-            if let Ok(payment) = storage.get_payment(&order_id) {
-                // Archive before removal
-                storage.archive_payment_record(&payment);
+        storage.save_reserve_tranches(merchant, &remaining);
 
-                // Remove from active payments
-                let mut payments_map = storage.get_payments_map();
-                payments_map.remove(order_id.clone());
-                // Save updated map
-                env.storage().instance().set(
-                    &crate::storage::DataKey::Payments.as_symbol(&env),
-                    &payments_map,
-                );
+        env.events().publish(
+            (reserve_topic(env), Symbol::new(env, "drawn")),
+            (merchant.clone(), token.clone(), amount),
+        );
 
-                // Remove from indices
-                storage.remove_merchant_payment_index(&payment.merchant_address, &order_id);
-                storage.remove_payer_payment_index(&payment.payer_address, &order_id);
+        Ok(())
+    }
 
-                cleaned_count += 1;
+    /// Credit a custodied fee to the configured beneficiaries by share, or to `collector` if none.
+    /// Rounding dust goes to the first beneficiary.
+    fn accrue_fee(storage: &Storage, token: &Address, collector: &Address, fee_amount: i128) {
+        let beneficiaries = storage.get_fee_beneficiaries();
+        let first = match beneficiaries.first() {
+            Some(first) => first,
+            None => {
+                storage.adjust_fee_balance(collector, token, fee_amount);
+                return;
             }
+        };
+
+        let mut remaining = fee_amount;
+        for beneficiary in beneficiaries.iter().skip(1) {
+            let share = fee_amount * i128::from(beneficiary.share_bps) / BASIS_POINTS_DENOMINATOR;
+            storage.adjust_fee_balance(&beneficiary.address, token, share);
+            remaining -= share;
         }
+        storage.adjust_fee_balance(&first.address, token, remaining);
+    }
 
-        env.events()
-            .publish(("payments_cleaned_up",), cleaned_count);
+    /// Verify per-order batch signatures before anything settles. Orders that already fail a
+    /// cheap check (unknown or inactive key, repeated, already settled, expired, reused nonce)
+    /// are skipped, so best-effort mode reports them instead of trapping on their signature.
+    /// Returns whether each order was verified.
+    fn verify_batch_order_signatures(
+        env: &Env,
+        storage: &Storage,
+        batch: &BatchPayment,
+        signatures: &Vec<BytesN<64>>,
+    ) -> Result<Vec<bool>, PaymentError> {
+        let now = env.ledger().timestamp();
+        let mut seen_orders: Map<PaymentKey, bool> = Map::new(env);
+        let mut verified = Vec::new(env);
 
-        Ok(cleaned_count)
+        for (idx, order) in batch.orders.iter().enumerate() {
+            let key = (order.merchant_address.clone(), order.order_id.clone());
+            let repeated = seen_orders.contains_key(key.clone());
+            seen_orders.set(key, true);
+
+            let Some(public_key) = batch
+                .merchant_public_keys
+                .get(order.merchant_address.clone())
+            else {
+                verified.push_back(false);
+                continue;
+            };
+            if repeated
+                || storage
+                    .find_order_payment(&order.merchant_address, &order.order_id)
+                    .is_some()
+                || now > order.expiration as u64
+                || storage.is_nonce_used(&order.merchant_address, order.nonce)
+                || storage
+                    .require_active_signing_key(&order.merchant_address, &public_key)
+                    .is_err()
+            {
+                verified.push_back(false);
+                continue;
+            }
+            let Ok(message) = create_order_message(env, storage, &order) else {
+                verified.push_back(false);
+                continue;
+            };
+
+            let signature = signatures
+                .get(idx as u32)
+                .ok_or(PaymentError::InvalidSignature)?;
+            env.crypto()
+                .ed25519_verify(&public_key, &message, &signature);
+            verified.push_back(true);
+        }
+
+        Ok(verified)
     }
 
-    fn set_payment_cleanup_period(
-        env: Env,
-        admin: Address,
-        period: u64,
+    /// Verify one signature per merchant over the digest of that merchant's orders in the batch
+    fn verify_batch_digests(
+        env: &Env,
+        storage: &Storage,
+        batch: &BatchPayment,
+        signatures: &Map<Address, BytesN<64>>,
     ) -> Result<(), PaymentError> {
-        // Require admin authorization
-        Self::require_admin_access(&env, &admin)?;
+        // Every order's merchant must have signed a digest, so no order settles unsigned
+        for order in batch.orders.iter() {
+            if !signatures.contains_key(order.merchant_address.clone()) {
+                return Err(PaymentError::InvalidSignature);
+            }
+        }
 
-        // Validate period (max 10 years)
-        const MAX_CLEANUP_PERIOD: u64 = 10 * 365 * 24 * 60 * 60;
-        if period > MAX_CLEANUP_PERIOD {
-            return Err(PaymentError::InvalidDateRange);
+        let mut messages: Map<Address, Vec<Bytes>> = Map::new(env);
+        for order in batch.orders.iter() {
+            let mut merchant_messages = messages
+                .get(order.merchant_address.clone())
+                .unwrap_or(Vec::new(env));
+            merchant_messages.push_back(create_order_message(env, storage, &order)?);
+            messages.set(order.merchant_address.clone(), merchant_messages);
+        }
+        // A digest for a merchant with no orders in the batch signs nothing
+        if signatures.len() != messages.len() {
+            return Err(PaymentError::InvalidSignature);
         }
 
-        let storage = Storage::new(&env);
-        storage.set_cleanup_period(period);
+        for (merchant, merchant_messages) in messages.iter() {
+            let public_key = batch
+                .merchant_public_keys
+                .get(merchant.clone())
+                .ok_or(PaymentError::SigningKeyNotFound)?;
+            storage.require_active_signing_key(&merchant, &public_key)?;
+
+            let signature = signatures
+                .get(merchant.clone())
+                .ok_or(PaymentError::InvalidSignature)?;
+            let digest = create_batch_digest(
+                env,
+                &env.current_contract_address(),
+                &merchant,
+                &merchant_messages,
+            );
+            env.crypto()
+                .ed25519_verify(&public_key, &digest, &signature);
+        }
 
-        env.events().publish(("cleanup_period_set",), period);
+        Ok(())
+    }
+}
+
+// Signing key helper functions
+impl PaymentProcessingContract {
+    /// Drop revoked and expired keys so they don't count towards the per-merchant limit
+    fn prune_signing_keys(env: &Env, keys: Vec<MerchantSigningKey>) -> Vec<MerchantSigningKey> {
+        let now = env.ledger().timestamp();
+        let mut pruned = Vec::new(env);
+        for key in keys.iter() {
+            let expired = key.valid_until.map(|until| now > until).unwrap_or(false);
+            if key.revoked_at.is_none() && !expired {
+                pruned.push_back(key);
+            }
+        }
+        pruned
+    }
 
+    /// Append a key after checking for duplicates, past revocation and the per-merchant limit
+    fn push_signing_key(
+        storage: &Storage,
+        merchant: &Address,
+        keys: &mut Vec<MerchantSigningKey>,
+        key: MerchantSigningKey,
+    ) -> Result<(), PaymentError> {
+        if keys.iter().any(|k| k.public_key == key.public_key) {
+            return Err(PaymentError::SigningKeyAlreadyRegistered);
+        }
+        // A revoked key may be compromised, so it can never sign for the merchant again
+        if storage.is_signing_key_revoked(merchant, &key.public_key) {
+            return Err(PaymentError::SigningKeyInactive);
+        }
+        if keys.len() >= MAX_SIGNING_KEYS_PER_MERCHANT {
+            return Err(PaymentError::TooManySigningKeys);
+        }
+        keys.push_back(key);
         Ok(())
     }
 }
@@ -1741,7 +5551,26 @@ impl PaymentProcessingContract {
         Ok(())
     }
 
-    /// Require admin access
+    /// Require `caller` to hold `role`
+    fn require_role(env: &Env, caller: &Address, role: Role) -> Result<(), PaymentError> {
+        caller.require_auth();
+        let storage = Storage::new(env);
+        if Self::holds_role(&storage, caller, role) {
+            return Ok(());
+        }
+        if storage.get_admin().is_none() {
+            return Err(PaymentError::AdminNotFound);
+        }
+        Err(PaymentError::NotAuthorized)
+    }
+
+    /// Whether `account` holds `role`, directly or as the super-admin
+    fn holds_role(storage: &Storage, account: &Address, role: Role) -> bool {
+        storage.get_admin().is_some_and(|admin| admin == *account)
+            || storage.has_role(role, account)
+    }
+
+    /// Require super-admin access
     fn require_admin_access(env: &Env, admin: &Address) -> Result<(), PaymentError> {
         admin.require_auth();
         let storage = Storage::new(env);
@@ -1755,9 +5584,134 @@ impl PaymentProcessingContract {
     }
 }
 
-/// Optimized message creation for signature verification
+/// Build the message an order's signature must cover, based on its schema version
+fn create_order_message(
+    env: &Env,
+    storage: &Storage,
+    order: &PaymentOrder,
+) -> Result<Bytes, PaymentError> {
+    // Older formats don't commit to splits, swap terms or currency, so they can't carry them
+    if order.schema_version < SPLIT_ORDER_SCHEMA_VERSION && !order.splits.is_empty() {
+        return Err(PaymentError::UnsupportedSchemaVersion);
+    }
+    if order.schema_version < SWAP_ORDER_SCHEMA_VERSION && order.input_token.is_some() {
+        return Err(PaymentError::UnsupportedSchemaVersion);
+    }
+    if order.schema_version < ORDER_SCHEMA_VERSION && order.currency.is_some() {
+        return Err(PaymentError::UnsupportedSchemaVersion);
+    }
+
+    match order.schema_version {
+        LEGACY_ORDER_SCHEMA_VERSION => {
+            if !storage.is_legacy_signature_allowed() {
+                return Err(PaymentError::LegacySignatureExpired);
+            }
+            Ok(create_optimized_message(env, order))
+        }
+        TYPED_ORDER_SCHEMA_VERSION
+        | SPLIT_ORDER_SCHEMA_VERSION
+        | SWAP_ORDER_SCHEMA_VERSION
+        | ORDER_SCHEMA_VERSION => Ok(create_typed_order_message(
+            env,
+            &env.current_contract_address(),
+            order,
+        )),
+        _ => Err(PaymentError::UnsupportedSchemaVersion),
+    }
+}
+
+/// Domain-separated order message (schema versions 1 to 4)
+/// Binds the signature to this deployment and network so it cannot be replayed elsewhere.
+/// Version 2 also commits to the order's revenue splits, version 3 to its swap terms
+/// and version 4 to its fiat currency.
+pub(crate) fn create_typed_order_message(
+    env: &Env,
+    contract: &Address,
+    order: &PaymentOrder,
+) -> Bytes {
+    let mut message = Bytes::from_slice(env, ORDER_DOMAIN_TAG);
+
+    // Schema version as 4 bytes (u32)
+    message.extend_from_array(&order.schema_version.to_be_bytes());
+
+    // Network id (32 bytes) and contract address
+    message.append(&env.ledger().network_id().into());
+    message.append(&contract.clone().to_xdr(env));
+
+    message.append(&order.merchant_address.clone().to_xdr(env));
+    message.extend_from_array(&order.amount.to_be_bytes());
+    message.append(&order.token.clone().to_xdr(env));
+    message.extend_from_array(&order.nonce.to_be_bytes());
+    message.extend_from_array(&order.expiration.to_be_bytes());
+    message.extend_from_array(&order.fee_amount.to_be_bytes());
+    message.append(&order.order_id.clone().to_xdr(env));
+
+    if order.schema_version >= SPLIT_ORDER_SCHEMA_VERSION {
+        message.extend_from_array(&order.splits.len().to_be_bytes());
+        for split in order.splits.iter() {
+            message.append(&split.recipient.to_xdr(env));
+            message.extend_from_array(&split.share_bps.to_be_bytes());
+        }
+    }
+
+    if order.schema_version >= SWAP_ORDER_SCHEMA_VERSION {
+        message.append(&order.input_token.clone().to_xdr(env));
+        message.extend_from_array(&order.max_input_amount.to_be_bytes());
+    }
+
+    if order.schema_version >= ORDER_SCHEMA_VERSION {
+        message.append(&order.currency.clone().to_xdr(env));
+    }
+
+    message
+}
+
+/// Digest a merchant signs once to authorize all of its orders in a batch.
+/// Covers the sha256 of each order's signed message, in batch order.
+pub(crate) fn create_batch_digest(
+    env: &Env,
+    contract: &Address,
+    merchant: &Address,
+    order_messages: &Vec<Bytes>,
+) -> Bytes {
+    let mut message = Bytes::from_slice(env, BATCH_DIGEST_DOMAIN_TAG);
+    message.append(&env.ledger().network_id().into());
+    message.append(&contract.clone().to_xdr(env));
+    message.append(&merchant.clone().to_xdr(env));
+    message.extend_from_array(&order_messages.len().to_be_bytes());
+    for order_message in order_messages.iter() {
+        let order_hash: BytesN<32> = env.crypto().sha256(&order_message).into();
+        message.append(&order_hash.into());
+    }
+
+    let digest: BytesN<32> = env.crypto().sha256(&message).into();
+    digest.into()
+}
+
+/// Message a payer signs to let `relayer` submit an order on their behalf.
+/// Commits to the relayer, its fee and the sha256 of the order's signed message.
+pub(crate) fn create_relay_message(
+    env: &Env,
+    contract: &Address,
+    relayer: &Address,
+    payer: &Address,
+    relay_fee: i128,
+    order_message: &Bytes,
+) -> Bytes {
+    let mut message = Bytes::from_slice(env, RELAY_DOMAIN_TAG);
+    message.append(&env.ledger().network_id().into());
+    message.append(&contract.clone().to_xdr(env));
+    message.append(&payer.clone().to_xdr(env));
+    message.append(&relayer.clone().to_xdr(env));
+    message.extend_from_array(&relay_fee.to_be_bytes());
+    let order_hash: BytesN<32> = env.crypto().sha256(order_message).into();
+    message.append(&order_hash.into());
+    message
+}
+
+/// Legacy message creation for signature verification (schema version 0)
 /// Reduces gas cost by pre-calculating message size and using efficient byte operations
-fn create_optimized_message(env: &Env, order: &PaymentOrder) -> Bytes {
+pub(crate) fn create_optimized_message(env: &Env, order: &PaymentOrder) -> Bytes {
     // Pre-calculate approximate message size to avoid reallocations
     let mut message = Bytes::new(env);
 
@@ -1,16 +1,21 @@
 use crate::{
     error::PaymentError,
+    helper::{validate_fee_schedule, SECONDS_PER_LEDGER},
     types::{
-        Fee, Merchant, MultiSigPayment, MultiSigPaymentRecord, NonceTracker, PaymentQueryFilter,
-        PaymentRecord, RefundRequest, SortField, SortOrder,
+        AdminCouncil, AdminHandover, CouncilProposal, Dispute, DisputeStatus, Fee, FeeBeneficiary,
+        FeeMode, FeeSchedule, FeeScope, Invoice, LimitScope, Merchant, MerchantCategory,
+        MerchantSigningKey, MultiSigPayment, MultiSigPaymentRecord, NonceTracker, OracleConfig,
+        PaymentAuthorization, PaymentQueryFilter, PaymentRecord, RefundRequest, ReserveScope,
+        ReserveTranche, Role, RollingReserveConfig, SettlementConfig, SortField, SortOrder,
+        Subscription, SubscriptionPlan, TokenFeeInfo, VolumeCounter, VolumeLimit, VolumeUsage,
     },
 };
-use soroban_sdk::{contracttype, log, Address, Env, Map, String, Symbol, Vec};
+use soroban_sdk::{log, Address, BytesN, Env, Map, String, Symbol, Vec};
 
-#[contracttype]
+/// Storage keys, stored as their `as_symbol` names. The enum itself is never stored, so it
+/// isn't a contract type, which would cap it at 50 variants.
 #[derive(Clone)]
 pub enum DataKey {
-    Initialized,
     Merchants,
     NonceTrackers,
     // Multi-signature
@@ -19,24 +24,80 @@ pub enum DataKey {
     PaymentCounter,
     // Admin / pause / fee
     Paused,
-    PausedAdmin,
+    PausedAdmin, // Address - legacy pause admin, moved into the PauseGuardian role
     PausedUntil,
     Admin,
     Fee,
     // Payment records and refunds
-    Payments,
+    Payments, // Map<PaymentKey, PaymentRecord>
     Refunds,
     // Payment history query indices
     MerchantPaymentIndices, // Map<Address, Vec<String>> - merchant -> order_ids
-    PayerPaymentIndices,    // Map<Address, Vec<String>> - payer -> order_ids
+    PayerPaymentIndices,    // Map<Address, Vec<PaymentKey>> - payer -> payments
     PaymentCleanupPeriod,   // u64 - cleanup period in seconds
-    PaymentArchive,         // Map<String, PaymentRecord> - archived payments
+    PaymentArchive,         // Map<PaymentKey, PaymentRecord> - archived payments
+    // Merchant signing keys
+    MerchantSigningKeys, // Map<Address, Vec<MerchantSigningKey>> - merchant -> keys
+    LegacySignatureDeadline, // u64 - last timestamp legacy order signatures are accepted
+    RevokedSigningKeys,  // Map<Address, Map<BytesN<32>, u64>> - merchant -> key -> revoked_at
+    // Fee schedule overrides
+    FeeOverrides,       // Map<FeeScope, FeeSchedule>
+    TokenFeeCollectors, // Map<Address, Address> - token -> fee collector
+    // Fee custody
+    FeeCustody,       // bool - fees are held by the contract instead of transferred
+    FeeBeneficiaries, // Vec<FeeBeneficiary>
+    FeeBalances,      // Map<Address, Map<Address, i128>> - beneficiary -> token -> balance
+    AccruedFees,      // Map<Address, i128> - token -> fees held by the contract
+    // Fee modes
+    MerchantFeeModes, // Map<Address, FeeMode> - merchant -> who bears the fee
+    // Escrow
+    MerchantEscrowTimeouts, // Map<Address, u64> - merchant -> escrow release timeout
+    // Disputes and arbitration
+    Disputes,         // Map<PaymentKey, Dispute>
+    Arbitrators,      // Vec<Address>
+    MerchantReserves, // Map<Address, Map<Address, i128>> - merchant -> token -> reserve
+    ReserveHolds, // Map<Address, Map<Address, i128>> - merchant -> token -> held for open disputes
+    // Subscriptions
+    SubscriptionPlans,       // Map<u64, SubscriptionPlan>
+    SubscriptionPlanCounter, // u64 - last plan id
+    Subscriptions,           // Map<u64, Subscription>
+    SubscriptionCounter,     // u64 - last subscription id
+    MerchantSubscriptions,   // Map<Address, Vec<u64>> - merchant -> subscription ids
+    PayerSubscriptions,      // Map<Address, Vec<u64>> - payer -> subscription ids
+    // Invoices
+    Invoices,       // Map<u64, Invoice>
+    InvoiceCounter, // u64 - last invoice id
+    // Authorization holds
+    Authorizations,       // Map<u64, PaymentAuthorization>
+    AuthorizationCounter, // u64 - last authorization id
+    // Cross-token payments
+    SwapRouter, // Address - router that swaps cross-token payments
+    // Fiat-denominated orders
+    PriceOracle, // OracleConfig - oracle pricing fiat-denominated orders
+    // Relayed payments
+    PayerRelayKeys, // Map<Address, BytesN<32>> - payer -> key authorizing relayed payments
+    // Merchant balances
+    SettlementConfigs, // Map<Address, SettlementConfig> - merchant -> internal balance payout settings
+    MerchantBalances,  // Map<Address, Map<Address, i128>> - merchant -> token -> internal balance
+    // Rolling reserves
+    RollingReserveConfigs, // Map<ReserveScope, RollingReserveConfig>
+    RollingReserves,       // Map<Address, Vec<ReserveTranche>> - merchant -> held tranches
+    // Volume and velocity limits
+    VolumeLimits, // Map<LimitScope, Vec<VolumeLimit>>
+    VolumeUsage,  // temporary: (symbol, VolumeCounter, window, window index) -> VolumeUsage
+    // Token metadata
+    TokenDecimals, // Map<Address, u32> - token -> decimals read from its metadata
+    // Access control
+    Roles,                  // Map<Role, Vec<Address>> - role -> holders
+    PendingSuperAdmin,      // AdminHandover - address proposed to take over as super-admin
+    AdminCouncil,           // AdminCouncil approving sensitive admin actions
+    CouncilProposals,       // Map<u64, CouncilProposal>
+    CouncilProposalCounter, // u64 - last council proposal id
 }
 
 impl DataKey {
     pub fn as_symbol(self, env: &Env) -> Symbol {
         match self {
-            DataKey::Initialized => Symbol::new(env, "initialized"),
             DataKey::Merchants => Symbol::new(env, "merchants"),
             DataKey::NonceTrackers => Symbol::new(env, "nonce_trackers"),
             DataKey::MultiSigPayments => Symbol::new(env, "multisig_payments"),
@@ -53,10 +114,54 @@ impl DataKey {
             DataKey::PayerPaymentIndices => Symbol::new(env, "payer_pay_idx"),
             DataKey::PaymentCleanupPeriod => Symbol::new(env, "pay_cleanup_period"),
             DataKey::PaymentArchive => Symbol::new(env, "pay_archive"),
+            DataKey::MerchantSigningKeys => Symbol::new(env, "merchant_sig_keys"),
+            DataKey::LegacySignatureDeadline => Symbol::new(env, "legacy_sig_deadline"),
+            DataKey::RevokedSigningKeys => Symbol::new(env, "revoked_sig_keys"),
+            DataKey::FeeOverrides => Symbol::new(env, "fee_overrides"),
+            DataKey::TokenFeeCollectors => Symbol::new(env, "token_fee_collectors"),
+            DataKey::FeeCustody => Symbol::new(env, "fee_custody"),
+            DataKey::FeeBeneficiaries => Symbol::new(env, "fee_beneficiaries"),
+            DataKey::FeeBalances => Symbol::new(env, "fee_balances"),
+            DataKey::AccruedFees => Symbol::new(env, "accrued_fees"),
+            DataKey::MerchantFeeModes => Symbol::new(env, "merchant_fee_modes"),
+            DataKey::MerchantEscrowTimeouts => Symbol::new(env, "merchant_escrow"),
+            DataKey::Disputes => Symbol::new(env, "disputes"),
+            DataKey::Arbitrators => Symbol::new(env, "arbitrators"),
+            DataKey::MerchantReserves => Symbol::new(env, "merchant_reserves"),
+            DataKey::ReserveHolds => Symbol::new(env, "reserve_holds"),
+            DataKey::SubscriptionPlans => Symbol::new(env, "sub_plans"),
+            DataKey::SubscriptionPlanCounter => Symbol::new(env, "sub_plan_counter"),
+            DataKey::Subscriptions => Symbol::new(env, "subscriptions"),
+            DataKey::SubscriptionCounter => Symbol::new(env, "subscription_counter"),
+            DataKey::MerchantSubscriptions => Symbol::new(env, "merchant_subs"),
+            DataKey::PayerSubscriptions => Symbol::new(env, "payer_subs"),
+            DataKey::Invoices => Symbol::new(env, "invoices"),
+            DataKey::InvoiceCounter => Symbol::new(env, "invoice_counter"),
+            DataKey::Authorizations => Symbol::new(env, "authorizations"),
+            DataKey::AuthorizationCounter => Symbol::new(env, "authorization_counter"),
+            DataKey::SwapRouter => Symbol::new(env, "swap_router"),
+            DataKey::PriceOracle => Symbol::new(env, "price_oracle"),
+            DataKey::PayerRelayKeys => Symbol::new(env, "payer_relay_keys"),
+            DataKey::SettlementConfigs => Symbol::new(env, "settlement_configs"),
+            DataKey::MerchantBalances => Symbol::new(env, "merchant_balances"),
+            DataKey::RollingReserveConfigs => Symbol::new(env, "rolling_reserve_cfgs"),
+            DataKey::RollingReserves => Symbol::new(env, "rolling_reserves"),
+            DataKey::VolumeLimits => Symbol::new(env, "volume_limits"),
+            DataKey::VolumeUsage => Symbol::new(env, "volume_usage"),
+            DataKey::TokenDecimals => Symbol::new(env, "token_decimals"),
+            DataKey::Roles => Symbol::new(env, "roles"),
+            DataKey::PendingSuperAdmin => Symbol::new(env, "pending_super_admin"),
+            DataKey::AdminCouncil => Symbol::new(env, "admin_council"),
+            DataKey::CouncilProposals => Symbol::new(env, "council_proposals"),
+            DataKey::CouncilProposalCounter => Symbol::new(env, "council_proposal_counter"),
         }
     }
 }
 
+/// A payment is identified by its merchant and order_id, since order IDs are only unique
+/// per merchant
+pub type PaymentKey = (Address, String);
+
 /// Optimized storage with efficient operations
 pub struct Storage<'a> {
     env: &'a Env,
@@ -67,33 +172,142 @@ impl<'a> Storage<'a> {
         Self { env }
     }
 
-    // ===== Pause management =====
-    pub fn set_pause_admin_internal(
-        &self,
-        admin: Address,
-        new_admin: Address,
-    ) -> Result<(), PaymentError> {
-        self.require_admin(&admin)?;
+    // ===== Access control =====
+    fn get_roles_map(&self) -> Map<Role, Vec<Address>> {
+        let instance = self.env.storage().instance();
+        let mut roles: Map<Role, Vec<Address>> = instance
+            .get(&DataKey::Roles.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env));
+
+        // Contracts upgraded from before roles kept a single pause admin. It becomes a pause
+        // guardian the first time roles are read, and the old entry is dropped.
+        let legacy_key = DataKey::PausedAdmin.as_symbol(self.env);
+        if let Some(pause_admin) = instance.get::<_, Address>(&legacy_key) {
+            instance.remove(&legacy_key);
+            let mut guardians = roles
+                .get(Role::PauseGuardian)
+                .unwrap_or_else(|| Vec::new(self.env));
+            if !guardians.contains(&pause_admin) {
+                guardians.push_back(pause_admin);
+            }
+            roles.set(Role::PauseGuardian, guardians);
+            instance.set(&DataKey::Roles.as_symbol(self.env), &roles);
+        }
+
+        roles
+    }
+
+    pub fn get_role_members(&self, role: Role) -> Vec<Address> {
+        self.get_roles_map()
+            .get(role)
+            .unwrap_or_else(|| Vec::new(self.env))
+    }
+
+    pub fn has_role(&self, role: Role, account: &Address) -> bool {
+        self.get_role_members(role).contains(account)
+    }
+
+    /// Add `account` to `role`; returns false if it already held it
+    pub fn grant_role(&self, role: Role, account: &Address) -> bool {
+        let mut members = self.get_role_members(role);
+        if members.contains(account) {
+            return false;
+        }
+        members.push_back(account.clone());
+        self.save_role_members(role, &members);
+        true
+    }
+
+    /// Remove `account` from `role`; returns false if it didn't hold it
+    pub fn revoke_role(&self, role: Role, account: &Address) -> bool {
+        let mut members = self.get_role_members(role);
+        match members.first_index_of(account) {
+            Some(index) => {
+                members.remove(index);
+                self.save_role_members(role, &members);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn save_role_members(&self, role: Role, members: &Vec<Address>) {
+        let mut roles = self.get_roles_map();
+        if members.is_empty() {
+            roles.remove(role);
+        } else {
+            roles.set(role, members.clone());
+        }
         self.env
             .storage()
             .instance()
-            .set(&DataKey::PausedAdmin.as_symbol(self.env), &new_admin);
-        Ok(())
+            .set(&DataKey::Roles.as_symbol(self.env), &roles);
     }
 
-    pub fn set_pause_until(&self, timestamp: u64) {
+    pub fn get_pending_super_admin(&self) -> Option<AdminHandover> {
         self.env
             .storage()
             .instance()
-            .set(&DataKey::PausedUntil.as_symbol(self.env), &timestamp);
+            .get(&DataKey::PendingSuperAdmin.as_symbol(self.env))
+    }
+
+    pub fn set_pending_super_admin(&self, pending: &Option<AdminHandover>) {
+        let key = DataKey::PendingSuperAdmin.as_symbol(self.env);
+        match pending {
+            Some(pending) => self.env.storage().instance().set(&key, pending),
+            None => self.env.storage().instance().remove(&key),
+        }
+    }
+
+    pub fn get_admin_council(&self) -> Option<AdminCouncil> {
+        self.env
+            .storage()
+            .instance()
+            .get(&DataKey::AdminCouncil.as_symbol(self.env))
+    }
+
+    /// Set or dissolve (`None`) the admin council
+    pub fn set_admin_council(&self, council: &Option<AdminCouncil>) {
+        let key = DataKey::AdminCouncil.as_symbol(self.env);
+        match council {
+            Some(council) => self.env.storage().instance().set(&key, council),
+            None => self.env.storage().instance().remove(&key),
+        }
+    }
+
+    pub fn get_council_proposals_map(&self) -> Map<u64, CouncilProposal> {
+        self.env
+            .storage()
+            .instance()
+            .get(&DataKey::CouncilProposals.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env))
+    }
+
+    pub fn get_council_proposal(&self, id: u64) -> Result<CouncilProposal, PaymentError> {
+        self.get_council_proposals_map()
+            .get(id)
+            .ok_or(PaymentError::PaymentNotFound)
     }
 
-    pub fn get_pause_admin(&self) -> Result<Address, PaymentError> {
+    pub fn save_council_proposal(&self, proposal: &CouncilProposal) {
+        let mut proposals = self.get_council_proposals_map();
+        proposals.set(proposal.id, proposal.clone());
         self.env
             .storage()
             .instance()
-            .get(&DataKey::PausedAdmin.as_symbol(self.env))
-            .ok_or(PaymentError::AdminNotFound)
+            .set(&DataKey::CouncilProposals.as_symbol(self.env), &proposals);
+    }
+
+    pub fn next_council_proposal_id(&self) -> u64 {
+        self.next_id(DataKey::CouncilProposalCounter)
+    }
+
+    // ===== Pause management =====
+    pub fn set_pause_until(&self, timestamp: u64) {
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::PausedUntil.as_symbol(self.env), &timestamp);
     }
 
     pub fn set_pause(&self) {
@@ -127,326 +341,1197 @@ impl<'a> Storage<'a> {
             .unwrap_or(0);
         log!(&self.env, "pause_until: {}", pause_until);
 
-        let current_time = self.env.ledger().timestamp();
-        if !pause && (pause_until == 0 || current_time > pause_until) {
-            return false;
+        let current_time = self.env.ledger().timestamp();
+        if !pause && (pause_until == 0 || current_time > pause_until) {
+            return false;
+        }
+        true
+    }
+
+    // ===== Merchant management =====
+    pub fn save_merchant(&self, address: &Address, merchant: &Merchant) {
+        let mut merchants = self.get_merchants_map();
+        merchants.set(address.clone(), merchant.clone());
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::Merchants.as_symbol(self.env), &merchants);
+    }
+
+    pub fn get_merchant(&self, address: &Address) -> Result<Merchant, PaymentError> {
+        let merchants = self.get_merchants_map();
+        merchants
+            .get(address.clone())
+            .ok_or(PaymentError::MerchantNotFound)
+    }
+    fn get_merchants_map(&self) -> Map<Address, Merchant> {
+        self.env
+            .storage()
+            .instance()
+            .get(&DataKey::Merchants.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env))
+    }
+
+    /// Get nonce trackers map
+    fn get_nonce_trackers_map(&self) -> Map<Address, NonceTracker> {
+        self.env
+            .storage()
+            .instance()
+            .get(&DataKey::NonceTrackers.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env))
+    }
+
+    /// Check if nonce is used with bitmap optimization
+    pub fn is_nonce_used(&self, merchant: &Address, nonce: u32) -> bool {
+        let trackers = self.get_nonce_trackers_map();
+        if let Some(tracker) = trackers.get(merchant.clone()) {
+            tracker.is_nonce_used(nonce)
+        } else {
+            false
+        }
+    }
+
+    /// Mark nonce as used with bitmap optimization
+    pub fn mark_nonce_used(&self, merchant: &Address, nonce: u32) {
+        let mut trackers = self.get_nonce_trackers_map();
+        let mut tracker = trackers
+            .get(merchant.clone())
+            .unwrap_or_else(|| NonceTracker::new(self.env));
+
+        tracker.mark_nonce_used(nonce);
+        trackers.set(merchant.clone(), tracker);
+
+        // Persist to storage
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::NonceTrackers.as_symbol(self.env), &trackers);
+    }
+
+    // ===== Merchant signing keys =====
+    fn get_signing_keys_map(&self) -> Map<Address, Vec<MerchantSigningKey>> {
+        self.env
+            .storage()
+            .instance()
+            .get(&DataKey::MerchantSigningKeys.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env))
+    }
+
+    /// Get all signing keys (including revoked and expired) for a merchant
+    pub fn get_signing_keys(&self, merchant: &Address) -> Vec<MerchantSigningKey> {
+        self.get_signing_keys_map()
+            .get(merchant.clone())
+            .unwrap_or_else(|| Vec::new(self.env))
+    }
+
+    pub fn save_signing_keys(&self, merchant: &Address, keys: &Vec<MerchantSigningKey>) {
+        let mut keys_map = self.get_signing_keys_map();
+        keys_map.set(merchant.clone(), keys.clone());
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::MerchantSigningKeys.as_symbol(self.env), &keys_map);
+    }
+
+    /// Ensure the public key is registered for the merchant and currently valid
+    pub fn require_active_signing_key(
+        &self,
+        merchant: &Address,
+        public_key: &BytesN<32>,
+    ) -> Result<(), PaymentError> {
+        let now = self.env.ledger().timestamp();
+        for key in self.get_signing_keys(merchant).iter() {
+            if key.public_key == *public_key {
+                if key.is_active(now) {
+                    return Ok(());
+                }
+                return Err(PaymentError::SigningKeyInactive);
+            }
+        }
+        Err(PaymentError::SigningKeyNotFound)
+    }
+
+    /// Revoked keys are kept apart from the merchant's key list, so they stay blocked after
+    /// being pruned from it
+    fn get_revoked_signing_keys_map(&self) -> Map<Address, Map<BytesN<32>, u64>> {
+        self.env
+            .storage()
+            .instance()
+            .get(&DataKey::RevokedSigningKeys.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env))
+    }
+
+    pub fn mark_signing_key_revoked(&self, merchant: &Address, public_key: &BytesN<32>) {
+        let mut revoked_map = self.get_revoked_signing_keys_map();
+        let mut revoked = revoked_map
+            .get(merchant.clone())
+            .unwrap_or_else(|| Map::new(self.env));
+        revoked.set(public_key.clone(), self.env.ledger().timestamp());
+        revoked_map.set(merchant.clone(), revoked);
+        self.env.storage().instance().set(
+            &DataKey::RevokedSigningKeys.as_symbol(self.env),
+            &revoked_map,
+        );
+    }
+
+    /// Whether the merchant ever revoked this key
+    pub fn is_signing_key_revoked(&self, merchant: &Address, public_key: &BytesN<32>) -> bool {
+        self.get_revoked_signing_keys_map()
+            .get(merchant.clone())
+            .is_some_and(|revoked| revoked.contains_key(public_key.clone()))
+    }
+
+    /// Set the last timestamp at which legacy order signatures are accepted
+    pub fn set_legacy_signature_deadline(&self, deadline: u64) {
+        self.env.storage().instance().set(
+            &DataKey::LegacySignatureDeadline.as_symbol(self.env),
+            &deadline,
+        );
+    }
+
+    /// Get the legacy signature deadline (None while no migration window was opened)
+    pub fn get_legacy_signature_deadline(&self) -> Option<u64> {
+        self.env
+            .storage()
+            .instance()
+            .get(&DataKey::LegacySignatureDeadline.as_symbol(self.env))
+    }
+
+    /// Legacy signatures are only accepted inside a migration window the admin opened
+    pub fn is_legacy_signature_allowed(&self) -> bool {
+        self.get_legacy_signature_deadline()
+            .is_some_and(|deadline| self.env.ledger().timestamp() <= deadline)
+    }
+
+    /// Batch save multiple merchants (gas optimization)
+    pub fn batch_save_merchants(&self, merchants_data: &[(Address, Merchant)]) {
+        let mut merchants = self.get_merchants_map();
+
+        for (address, merchant) in merchants_data {
+            merchants.set(address.clone(), merchant.clone());
+        }
+
+        // Single storage write for all merchants
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::Merchants.as_symbol(self.env), &merchants);
+    }
+
+    pub fn merchant_exists(&self, address: &Address) -> bool {
+        let merchants = self.get_merchants_map();
+        merchants.contains_key(address.clone())
+    }
+
+    /// Get merchant count (for gas estimation)
+    pub fn get_merchant_count(&self) -> u32 {
+        let merchants = self.get_merchants_map();
+        merchants.len() as u32
+    }
+
+    /// Get nonce tracker for a merchant
+    pub fn get_nonce_tracker(&self, merchant: &Address) -> Option<NonceTracker> {
+        let trackers = self.get_nonce_trackers_map();
+        trackers.get(merchant.clone())
+    }
+
+    // ===== Multi-signature payment management =====
+    pub fn save_multisig_payment(&self, payment: &MultiSigPayment) {
+        let mut payments = self.get_multisig_payments_map();
+        payments.set(payment.payment_id, payment.clone());
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::MultiSigPayments.as_symbol(self.env), &payments);
+    }
+
+    pub fn get_multisig_payment(&self, payment_id: u128) -> Result<MultiSigPayment, PaymentError> {
+        let payments = self.get_multisig_payments_map();
+        payments
+            .get(payment_id)
+            .ok_or(PaymentError::PaymentNotFound)
+    }
+
+    pub fn remove_multisig_payment(&self, payment_id: u128) {
+        let mut payments = self.get_multisig_payments_map();
+        payments.remove(payment_id);
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::MultiSigPayments.as_symbol(self.env), &payments);
+    }
+
+    pub fn archive_payment(&self, record: &MultiSigPaymentRecord) {
+        let mut history = self.get_payment_history_map();
+        history.set(record.payment_id, record.clone());
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::PaymentHistory.as_symbol(self.env), &history);
+    }
+
+    #[allow(dead_code)]
+    pub fn get_payment_record(&self, payment_id: u128) -> Option<MultiSigPaymentRecord> {
+        let history = self.get_payment_history_map();
+        history.get(payment_id)
+    }
+
+    pub fn get_next_payment_id(&self) -> u128 {
+        let current_counter: u128 = self
+            .env
+            .storage()
+            .instance()
+            .get(&DataKey::PaymentCounter.as_symbol(self.env))
+            .unwrap_or(0);
+        let next_id = current_counter + 1;
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::PaymentCounter.as_symbol(self.env), &next_id);
+        next_id
+    }
+
+    // ===== Payment records management =====
+    pub fn save_payment(&self, record: &PaymentRecord) {
+        let mut payments = self.get_payments_map();
+        payments.set(record.key(), record.clone());
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::Payments.as_symbol(self.env), &payments);
+    }
+
+    pub fn get_payment(
+        &self,
+        merchant: &Address,
+        order_id: &String,
+    ) -> Result<PaymentRecord, PaymentError> {
+        let payments = self.get_payments_map();
+        payments
+            .get((merchant.clone(), order_id.clone()))
+            .ok_or(PaymentError::PaymentNotFound)
+    }
+
+    pub fn update_payment(&self, record: &PaymentRecord) {
+        let mut payments = self.get_payments_map();
+        payments.set(record.key(), record.clone());
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::Payments.as_symbol(self.env), &payments);
+    }
+
+    pub fn remove_payment(&self, merchant: &Address, order_id: &String) {
+        let mut payments = self.get_payments_map();
+        payments.remove((merchant.clone(), order_id.clone()));
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::Payments.as_symbol(self.env), &payments);
+    }
+
+    // ===== Refund requests management =====
+    pub fn save_refund(&self, request: &RefundRequest) {
+        let mut refunds = self.get_refunds_map();
+        refunds.set(request.refund_id.clone(), request.clone());
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::Refunds.as_symbol(self.env), &refunds);
+    }
+
+    pub fn get_refund(
+        &self,
+        refund_id: &soroban_sdk::String,
+    ) -> Result<RefundRequest, PaymentError> {
+        let refunds = self.get_refunds_map();
+        refunds
+            .get(refund_id.clone())
+            .ok_or(PaymentError::RefundNotFound)
+    }
+
+    pub fn update_refund(&self, request: &RefundRequest) {
+        let mut refunds = self.get_refunds_map();
+        refunds.set(request.refund_id.clone(), request.clone());
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::Refunds.as_symbol(self.env), &refunds);
+    }
+
+    fn get_multisig_payments_map(&self) -> Map<u128, MultiSigPayment> {
+        self.env
+            .storage()
+            .instance()
+            .get(&DataKey::MultiSigPayments.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env))
+    }
+
+    fn get_payment_history_map(&self) -> Map<u128, MultiSigPaymentRecord> {
+        self.env
+            .storage()
+            .instance()
+            .get(&DataKey::PaymentHistory.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env))
+    }
+
+    // ===== Admin and fee management =====
+    pub fn set_admin(&self, admin: &Address) {
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::Admin.as_symbol(self.env), &admin);
+    }
+
+    /// Callers must check the caller's fee manager role
+    pub fn set_fee_info(&self, fee: &Fee) -> Result<(), PaymentError> {
+        validate_fee_schedule(&fee.schedule)?;
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::Fee.as_symbol(self.env), &fee.clone());
+        self.env.events().publish(
+            ("fee_info_set",),
+            (
+                fee.schedule.rate_bps,
+                fee.fee_collector.clone(),
+                fee.fee_token.clone(),
+            ),
+        );
+        Ok(())
+    }
+
+    fn get_fee_info(&self) -> Option<Fee> {
+        self.env
+            .storage()
+            .instance()
+            .get::<_, Fee>(&DataKey::Fee.as_symbol(self.env))
+    }
+
+    /// Default fee schedule applied when no override matches
+    pub fn get_fee_schedule(&self) -> Option<FeeSchedule> {
+        self.get_fee_info().map(|f| f.schedule)
+    }
+
+    pub fn get_fee_collector(&self) -> Option<Address> {
+        self.get_fee_info().map(|f| f.fee_collector)
+    }
+
+    pub fn get_fee_token(&self) -> Option<Address> {
+        self.get_fee_info().map(|f| f.fee_token)
+    }
+
+    fn get_fee_overrides_map(&self) -> Map<FeeScope, FeeSchedule> {
+        self.env
+            .storage()
+            .instance()
+            .get(&DataKey::FeeOverrides.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env))
+    }
+
+    /// Set or clear (`None`) the fee schedule override for a scope
+    pub fn set_fee_override(&self, scope: &FeeScope, schedule: &Option<FeeSchedule>) {
+        let mut overrides = self.get_fee_overrides_map();
+        match schedule {
+            Some(schedule) => overrides.set(scope.clone(), schedule.clone()),
+            None => {
+                overrides.remove(scope.clone());
+            }
+        }
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::FeeOverrides.as_symbol(self.env), &overrides);
+    }
+
+    pub fn get_fee_override(&self, scope: &FeeScope) -> Option<FeeSchedule> {
+        self.get_fee_overrides_map().get(scope.clone())
+    }
+
+    /// Resolve the fee schedule for a merchant paying in `token`:
+    /// merchant override, then token override, then category override, then the default
+    pub fn resolve_fee_schedule(&self, merchant: &Merchant, token: &Address) -> FeeSchedule {
+        let overrides = self.get_fee_overrides_map();
+        let scopes = [
+            FeeScope::Merchant(merchant.wallet_address.clone()),
+            FeeScope::Token(token.clone()),
+            FeeScope::Category(merchant.category.clone()),
+        ];
+        for scope in scopes {
+            if let Some(schedule) = overrides.get(scope) {
+                return schedule;
+            }
+        }
+        self.get_fee_schedule()
+            .unwrap_or_else(|| FeeSchedule::from_rate(0))
+    }
+
+    pub fn calculate_fee(&self, merchant: &Merchant, token: &Address, amount: i128) -> i128 {
+        self.resolve_fee_schedule(merchant, token).calculate(amount)
+    }
+
+    fn get_token_fee_collectors_map(&self) -> Map<Address, Address> {
+        self.env
+            .storage()
+            .instance()
+            .get(&DataKey::TokenFeeCollectors.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env))
+    }
+
+    /// Set or clear (`None`) the collector that receives fees paid in `token`
+    pub fn set_token_fee_collector(&self, token: &Address, collector: &Option<Address>) {
+        let mut collectors = self.get_token_fee_collectors_map();
+        match collector {
+            Some(collector) => collectors.set(token.clone(), collector.clone()),
+            None => {
+                collectors.remove(token.clone());
+            }
+        }
+        self.env.storage().instance().set(
+            &DataKey::TokenFeeCollectors.as_symbol(self.env),
+            &collectors,
+        );
+    }
+
+    /// Collector for fees paid in `token`, falling back to the default collector
+    pub fn resolve_fee_collector(&self, token: &Address) -> Option<Address> {
+        self.get_token_fee_collectors_map()
+            .get(token.clone())
+            .or_else(|| self.get_fee_collector())
+    }
+
+    /// Fee settings for payments in `token`, ignoring merchant and category overrides
+    pub fn get_token_fee_info(&self, token: &Address) -> Option<TokenFeeInfo> {
+        let fee_collector = self.resolve_fee_collector(token)?;
+        let schedule = self
+            .get_fee_override(&FeeScope::Token(token.clone()))
+            .or_else(|| self.get_fee_schedule())
+            .unwrap_or_else(|| FeeSchedule::from_rate(0));
+        Some(TokenFeeInfo {
+            token: token.clone(),
+            fee_collector,
+            schedule,
+        })
+    }
+
+    fn get_fee_modes_map(&self) -> Map<Address, FeeMode> {
+        self.env
+            .storage()
+            .instance()
+            .get(&DataKey::MerchantFeeModes.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env))
+    }
+
+    pub fn set_fee_mode(&self, merchant: &Address, fee_mode: &FeeMode) {
+        let mut modes = self.get_fee_modes_map();
+        modes.set(merchant.clone(), *fee_mode);
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::MerchantFeeModes.as_symbol(self.env), &modes);
+    }
+
+    /// Fee mode for a merchant; fees are deducted unless the merchant opted into surcharging
+    pub fn get_fee_mode(&self, merchant: &Address) -> FeeMode {
+        self.get_fee_modes_map()
+            .get(merchant.clone())
+            .unwrap_or(FeeMode::Deduct)
+    }
+
+    // ===== Escrow =====
+    fn get_escrow_timeouts_map(&self) -> Map<Address, u64> {
+        self.env
+            .storage()
+            .instance()
+            .get(&DataKey::MerchantEscrowTimeouts.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env))
+    }
+
+    /// Set or clear (`None`) the escrow release timeout for a merchant's payments
+    pub fn set_escrow_timeout(&self, merchant: &Address, release_timeout: &Option<u64>) {
+        let mut timeouts = self.get_escrow_timeouts_map();
+        match release_timeout {
+            Some(timeout) => timeouts.set(merchant.clone(), *timeout),
+            None => {
+                timeouts.remove(merchant.clone());
+            }
+        }
+        self.env.storage().instance().set(
+            &DataKey::MerchantEscrowTimeouts.as_symbol(self.env),
+            &timeouts,
+        );
+    }
+
+    /// Escrow release timeout for a merchant, if its payments are escrowed
+    pub fn get_escrow_timeout(&self, merchant: &Address) -> Option<u64> {
+        self.get_escrow_timeouts_map().get(merchant.clone())
+    }
+
+    // ===== Disputes and arbitration =====
+    fn get_disputes_map(&self) -> Map<PaymentKey, Dispute> {
+        self.env
+            .storage()
+            .instance()
+            .get(&DataKey::Disputes.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env))
+    }
+
+    pub fn save_dispute(&self, dispute: &Dispute) {
+        let mut disputes = self.get_disputes_map();
+        disputes.set(
+            (dispute.merchant_address.clone(), dispute.order_id.clone()),
+            dispute.clone(),
+        );
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::Disputes.as_symbol(self.env), &disputes);
+    }
+
+    pub fn get_dispute(
+        &self,
+        merchant: &Address,
+        order_id: &String,
+    ) -> Result<Dispute, PaymentError> {
+        self.get_disputes_map()
+            .get((merchant.clone(), order_id.clone()))
+            .ok_or(PaymentError::PaymentNotFound)
+    }
+
+    /// Whether the payment has a dispute still awaiting a ruling
+    pub fn has_open_dispute(&self, merchant: &Address, order_id: &String) -> bool {
+        matches!(
+            self.get_disputes_map().get((merchant.clone(), order_id.clone())),
+            Some(dispute) if dispute.status == DisputeStatus::Open
+        )
+    }
+
+    pub fn get_arbitrators(&self) -> Vec<Address> {
+        self.env
+            .storage()
+            .instance()
+            .get(&DataKey::Arbitrators.as_symbol(self.env))
+            .unwrap_or_else(|| Vec::new(self.env))
+    }
+
+    pub fn set_arbitrators(&self, arbitrators: &Vec<Address>) {
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::Arbitrators.as_symbol(self.env), arbitrators);
+    }
+
+    pub fn is_arbitrator(&self, address: &Address) -> bool {
+        self.get_arbitrators().contains(address)
+    }
+
+    /// Balance of a per-merchant, per-token ledger stored under `key`
+    fn get_merchant_ledger_balance(
+        &self,
+        key: DataKey,
+        merchant: &Address,
+        token: &Address,
+    ) -> i128 {
+        let ledger: Map<Address, Map<Address, i128>> = self
+            .env
+            .storage()
+            .instance()
+            .get(&key.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env));
+        ledger
+            .get(merchant.clone())
+            .and_then(|balances| balances.get(token.clone()))
+            .unwrap_or(0)
+    }
+
+    /// Adjust a per-merchant, per-token ledger stored under `key` by `delta`
+    fn adjust_merchant_ledger_balance(
+        &self,
+        key: DataKey,
+        merchant: &Address,
+        token: &Address,
+        delta: i128,
+    ) {
+        let symbol = key.as_symbol(self.env);
+        let mut ledger: Map<Address, Map<Address, i128>> = self
+            .env
+            .storage()
+            .instance()
+            .get(&symbol)
+            .unwrap_or_else(|| Map::new(self.env));
+        let mut balances = ledger
+            .get(merchant.clone())
+            .unwrap_or_else(|| Map::new(self.env));
+        let balance = balances.get(token.clone()).unwrap_or(0) + delta;
+        if balance == 0 {
+            balances.remove(token.clone());
+        } else {
+            balances.set(token.clone(), balance);
+        }
+        ledger.set(merchant.clone(), balances);
+        self.env.storage().instance().set(&symbol, &ledger);
+    }
+
+    /// Tokens a merchant has deposited as a reserve for dispute payouts
+    pub fn get_merchant_reserve(&self, merchant: &Address, token: &Address) -> i128 {
+        self.get_merchant_ledger_balance(DataKey::MerchantReserves, merchant, token)
+    }
+
+    pub fn adjust_merchant_reserve(&self, merchant: &Address, token: &Address, delta: i128) {
+        self.adjust_merchant_ledger_balance(DataKey::MerchantReserves, merchant, token, delta);
+    }
+
+    /// Portion of a merchant's reserve held back for open disputes
+    pub fn get_reserve_hold(&self, merchant: &Address, token: &Address) -> i128 {
+        self.get_merchant_ledger_balance(DataKey::ReserveHolds, merchant, token)
+    }
+
+    pub fn adjust_reserve_hold(&self, merchant: &Address, token: &Address, delta: i128) {
+        self.adjust_merchant_ledger_balance(DataKey::ReserveHolds, merchant, token, delta);
+    }
+
+    // ===== Merchant balances =====
+    fn get_settlement_configs_map(&self) -> Map<Address, SettlementConfig> {
+        self.env
+            .storage()
+            .instance()
+            .get(&DataKey::SettlementConfigs.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env))
+    }
+
+    /// Set or clear (`None`) a merchant's internal balance settings
+    pub fn set_settlement_config(&self, merchant: &Address, config: &Option<SettlementConfig>) {
+        let mut configs = self.get_settlement_configs_map();
+        match config {
+            Some(config) => configs.set(merchant.clone(), config.clone()),
+            None => {
+                configs.remove(merchant.clone());
+            }
+        }
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::SettlementConfigs.as_symbol(self.env), &configs);
+    }
+
+    /// Internal balance settings for a merchant, if its payments are credited to a balance
+    pub fn get_settlement_config(&self, merchant: &Address) -> Option<SettlementConfig> {
+        self.get_settlement_configs_map().get(merchant.clone())
+    }
+
+    pub fn get_merchant_balance(&self, merchant: &Address, token: &Address) -> i128 {
+        self.get_merchant_ledger_balance(DataKey::MerchantBalances, merchant, token)
+    }
+
+    pub fn adjust_merchant_balance(&self, merchant: &Address, token: &Address, delta: i128) {
+        self.adjust_merchant_ledger_balance(DataKey::MerchantBalances, merchant, token, delta);
+    }
+
+    // ===== Rolling reserves =====
+    fn get_rolling_reserve_configs_map(&self) -> Map<ReserveScope, RollingReserveConfig> {
+        self.env
+            .storage()
+            .instance()
+            .get(&DataKey::RollingReserveConfigs.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env))
+    }
+
+    /// Set or clear (`None`) the rolling reserve for a scope
+    pub fn set_rolling_reserve_config(
+        &self,
+        scope: &ReserveScope,
+        config: &Option<RollingReserveConfig>,
+    ) {
+        let mut configs = self.get_rolling_reserve_configs_map();
+        match config {
+            Some(config) => configs.set(scope.clone(), config.clone()),
+            None => {
+                configs.remove(scope.clone());
+            }
+        }
+        self.env.storage().instance().set(
+            &DataKey::RollingReserveConfigs.as_symbol(self.env),
+            &configs,
+        );
+    }
+
+    pub fn get_rolling_reserve_config(&self, scope: &ReserveScope) -> Option<RollingReserveConfig> {
+        self.get_rolling_reserve_configs_map().get(scope.clone())
+    }
+
+    /// Rolling reserve for a merchant: its own setting, else its category's
+    pub fn resolve_rolling_reserve(
+        &self,
+        merchant: &Address,
+        category: &MerchantCategory,
+    ) -> Option<RollingReserveConfig> {
+        let configs = self.get_rolling_reserve_configs_map();
+        configs
+            .get(ReserveScope::Merchant(merchant.clone()))
+            .or_else(|| configs.get(ReserveScope::Category(category.clone())))
+    }
+
+    fn get_reserve_tranches_map(&self) -> Map<Address, Vec<ReserveTranche>> {
+        self.env
+            .storage()
+            .instance()
+            .get(&DataKey::RollingReserves.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env))
+    }
+
+    /// Amounts held back from a merchant's payments, in the order they were held
+    pub fn get_reserve_tranches(&self, merchant: &Address) -> Vec<ReserveTranche> {
+        self.get_reserve_tranches_map()
+            .get(merchant.clone())
+            .unwrap_or_else(|| Vec::new(self.env))
+    }
+
+    pub fn save_reserve_tranches(&self, merchant: &Address, tranches: &Vec<ReserveTranche>) {
+        let mut all = self.get_reserve_tranches_map();
+        if tranches.is_empty() {
+            all.remove(merchant.clone());
+        } else {
+            all.set(merchant.clone(), tranches.clone());
         }
-        true
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::RollingReserves.as_symbol(self.env), &all);
     }
 
-    // ===== Merchant management =====
-    pub fn save_merchant(&self, address: &Address, merchant: &Merchant) {
-        let mut merchants = self.get_merchants_map();
-        merchants.set(address.clone(), merchant.clone());
+    // ===== Token metadata =====
+    pub fn get_token_decimals(&self, token: &Address) -> Option<u32> {
         self.env
             .storage()
             .instance()
-            .set(&DataKey::Merchants.as_symbol(self.env), &merchants);
+            .get::<_, Map<Address, u32>>(&DataKey::TokenDecimals.as_symbol(self.env))
+            .and_then(|decimals| decimals.get(token.clone()))
     }
 
-    pub fn get_merchant(&self, address: &Address) -> Result<Merchant, PaymentError> {
-        let merchants = self.get_merchants_map();
-        merchants
-            .get(address.clone())
-            .ok_or(PaymentError::MerchantNotFound)
-    }
-    fn get_merchants_map(&self) -> Map<Address, Merchant> {
-        self.env
+    pub fn set_token_decimals(&self, token: &Address, decimals: u32) {
+        let key = DataKey::TokenDecimals.as_symbol(self.env);
+        let mut all: Map<Address, u32> = self
+            .env
             .storage()
             .instance()
-            .get(&DataKey::Merchants.as_symbol(self.env))
-            .unwrap_or_else(|| Map::new(self.env))
+            .get(&key)
+            .unwrap_or_else(|| Map::new(self.env));
+        all.set(token.clone(), decimals);
+        self.env.storage().instance().set(&key, &all);
     }
 
-    /// Get nonce trackers map
-    fn get_nonce_trackers_map(&self) -> Map<Address, NonceTracker> {
+    // ===== Volume limits =====
+    fn get_volume_limits_map(&self) -> Map<LimitScope, Vec<VolumeLimit>> {
         self.env
             .storage()
             .instance()
-            .get(&DataKey::NonceTrackers.as_symbol(self.env))
+            .get(&DataKey::VolumeLimits.as_symbol(self.env))
             .unwrap_or_else(|| Map::new(self.env))
     }
 
-    /// Check if nonce is used with bitmap optimization
-    pub fn is_nonce_used(&self, merchant: &Address, nonce: u32) -> bool {
-        let trackers = self.get_nonce_trackers_map();
-        if let Some(tracker) = trackers.get(merchant.clone()) {
-            tracker.is_nonce_used(nonce)
+    /// Set the limits for a scope; an empty list clears them
+    pub fn set_volume_limits(&self, scope: &LimitScope, limits: &Vec<VolumeLimit>) {
+        let mut all = self.get_volume_limits_map();
+        if limits.is_empty() {
+            all.remove(scope.clone());
         } else {
-            false
+            all.set(scope.clone(), limits.clone());
         }
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::VolumeLimits.as_symbol(self.env), &all);
     }
 
-    /// Mark nonce as used with bitmap optimization
-    pub fn mark_nonce_used(&self, merchant: &Address, nonce: u32) {
-        let mut trackers = self.get_nonce_trackers_map();
-        let mut tracker = trackers
-            .get(merchant.clone())
-            .unwrap_or_else(|| NonceTracker::new(self.env));
+    pub fn get_volume_limits(&self, scope: &LimitScope) -> Vec<VolumeLimit> {
+        self.get_volume_limits_map()
+            .get(scope.clone())
+            .unwrap_or_else(|| Vec::new(self.env))
+    }
 
-        tracker.mark_nonce_used(nonce);
-        trackers.set(merchant.clone(), tracker);
+    /// Usage is kept in temporary storage, one entry per counter and window, so past
+    /// windows expire on their own instead of needing cleanup
+    fn volume_usage_key(
+        &self,
+        counter: &VolumeCounter,
+        window: u64,
+        now: u64,
+    ) -> (Symbol, VolumeCounter, u64, u64) {
+        (
+            DataKey::VolumeUsage.as_symbol(self.env),
+            counter.clone(),
+            window,
+            now / window,
+        )
+    }
 
-        // Persist to storage
+    /// Usage of a counter within the window of length `window` containing `now`
+    pub fn get_volume_usage(&self, counter: &VolumeCounter, window: u64, now: u64) -> VolumeUsage {
         self.env
             .storage()
-            .instance()
-            .set(&DataKey::NonceTrackers.as_symbol(self.env), &trackers);
+            .temporary()
+            .get(&self.volume_usage_key(counter, window, now))
+            .unwrap_or_default()
     }
 
-    /// Batch save multiple merchants (gas optimization)
-    pub fn batch_save_merchants(&self, merchants_data: &[(Address, Merchant)]) {
-        let mut merchants = self.get_merchants_map();
+    pub fn save_volume_usage(
+        &self,
+        counter: &VolumeCounter,
+        window: u64,
+        now: u64,
+        usage: &VolumeUsage,
+    ) {
+        let key = self.volume_usage_key(counter, window, now);
+        let temporary = self.env.storage().temporary();
+        temporary.set(&key, usage);
+        // Ledgers close about every 5 seconds, so the window's length in ledgers keeps the
+        // entry alive until the window ends
+        let ttl = u32::try_from(window / SECONDS_PER_LEDGER + 1)
+            .unwrap_or(u32::MAX)
+            .min(self.env.storage().max_ttl());
+        temporary.extend_ttl(&key, ttl, ttl);
+    }
 
-        for (address, merchant) in merchants_data {
-            merchants.set(address.clone(), merchant.clone());
-        }
+    // ===== Subscriptions =====
+    /// Increment the counter stored under `key` and return the new id
+    fn next_id(&self, key: DataKey) -> u64 {
+        let symbol = key.as_symbol(self.env);
+        let next_id = self
+            .env
+            .storage()
+            .instance()
+            .get::<_, u64>(&symbol)
+            .unwrap_or(0)
+            + 1;
+        self.env.storage().instance().set(&symbol, &next_id);
+        next_id
+    }
 
-        // Single storage write for all merchants
+    fn get_subscription_plans_map(&self) -> Map<u64, SubscriptionPlan> {
         self.env
             .storage()
             .instance()
-            .set(&DataKey::Merchants.as_symbol(self.env), &merchants);
+            .get(&DataKey::SubscriptionPlans.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env))
     }
 
-    /// Batch mark multiple nonces as used (gas optimization)
-    pub fn batch_mark_nonces_used(&self, merchant: &Address, nonces: &[u32]) {
-        let mut trackers = self.get_nonce_trackers_map();
-        let mut tracker = trackers
-            .get(merchant.clone())
-            .unwrap_or_else(|| NonceTracker::new(self.env));
-
-        for &nonce in nonces {
-            tracker.mark_nonce_used(nonce);
-        }
-
-        trackers.set(merchant.clone(), tracker);
+    pub fn get_next_subscription_plan_id(&self) -> u64 {
+        self.next_id(DataKey::SubscriptionPlanCounter)
+    }
 
-        // Single storage write
+    pub fn save_subscription_plan(&self, plan: &SubscriptionPlan) {
+        let mut plans = self.get_subscription_plans_map();
+        plans.set(plan.plan_id, plan.clone());
         self.env
             .storage()
             .instance()
-            .set(&DataKey::NonceTrackers.as_symbol(self.env), &trackers);
+            .set(&DataKey::SubscriptionPlans.as_symbol(self.env), &plans);
     }
 
-    pub fn merchant_exists(&self, address: &Address) -> bool {
-        let merchants = self.get_merchants_map();
-        merchants.contains_key(address.clone())
+    pub fn get_subscription_plan(&self, plan_id: u64) -> Result<SubscriptionPlan, PaymentError> {
+        self.get_subscription_plans_map()
+            .get(plan_id)
+            .ok_or(PaymentError::PaymentNotFound)
     }
 
-    /// Get merchant count (for gas estimation)
-    pub fn get_merchant_count(&self) -> u32 {
-        let merchants = self.get_merchants_map();
-        merchants.len() as u32
+    fn get_subscriptions_map(&self) -> Map<u64, Subscription> {
+        self.env
+            .storage()
+            .instance()
+            .get(&DataKey::Subscriptions.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env))
     }
 
-    /// Get nonce tracker for a merchant
-    pub fn get_nonce_tracker(&self, merchant: &Address) -> Option<NonceTracker> {
-        let trackers = self.get_nonce_trackers_map();
-        trackers.get(merchant.clone())
+    pub fn get_next_subscription_id(&self) -> u64 {
+        self.next_id(DataKey::SubscriptionCounter)
     }
 
-    // ===== Multi-signature payment management =====
-    pub fn save_multisig_payment(&self, payment: &MultiSigPayment) {
-        let mut payments = self.get_multisig_payments_map();
-        payments.set(payment.payment_id, payment.clone());
+    pub fn save_subscription(&self, subscription: &Subscription) {
+        let mut subscriptions = self.get_subscriptions_map();
+        subscriptions.set(subscription.subscription_id, subscription.clone());
         self.env
             .storage()
             .instance()
-            .set(&DataKey::MultiSigPayments.as_symbol(self.env), &payments);
+            .set(&DataKey::Subscriptions.as_symbol(self.env), &subscriptions);
     }
 
-    pub fn get_multisig_payment(&self, payment_id: u128) -> Result<MultiSigPayment, PaymentError> {
-        let payments = self.get_multisig_payments_map();
-        payments
-            .get(payment_id)
+    pub fn get_subscription(&self, subscription_id: u64) -> Result<Subscription, PaymentError> {
+        self.get_subscriptions_map()
+            .get(subscription_id)
             .ok_or(PaymentError::PaymentNotFound)
     }
 
-    pub fn remove_multisig_payment(&self, payment_id: u128) {
-        let mut payments = self.get_multisig_payments_map();
-        payments.remove(payment_id);
-        self.env
+    /// Append a subscription id to an address-keyed index stored under `key`
+    fn add_subscription_index(&self, key: DataKey, address: &Address, subscription_id: u64) {
+        let symbol = key.as_symbol(self.env);
+        let mut indices: Map<Address, Vec<u64>> = self
+            .env
             .storage()
             .instance()
-            .set(&DataKey::MultiSigPayments.as_symbol(self.env), &payments);
+            .get(&symbol)
+            .unwrap_or_else(|| Map::new(self.env));
+        let mut ids = indices
+            .get(address.clone())
+            .unwrap_or_else(|| Vec::new(self.env));
+        ids.push_back(subscription_id);
+        indices.set(address.clone(), ids);
+        self.env.storage().instance().set(&symbol, &indices);
     }
 
-    pub fn archive_payment(&self, record: &MultiSigPaymentRecord) {
-        let mut history = self.get_payment_history_map();
-        history.set(record.payment_id, record.clone());
-        self.env
+    /// Subscriptions listed in an address-keyed index stored under `key`
+    fn get_indexed_subscriptions(&self, key: DataKey, address: &Address) -> Vec<Subscription> {
+        let indices: Map<Address, Vec<u64>> = self
+            .env
             .storage()
             .instance()
-            .set(&DataKey::PaymentHistory.as_symbol(self.env), &history);
+            .get(&key.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env));
+        let subscriptions = self.get_subscriptions_map();
+        let mut result = Vec::new(self.env);
+        for id in indices
+            .get(address.clone())
+            .unwrap_or_else(|| Vec::new(self.env))
+            .iter()
+        {
+            if let Some(subscription) = subscriptions.get(id) {
+                result.push_back(subscription);
+            }
+        }
+        result
     }
 
-    #[allow(dead_code)]
-    pub fn get_payment_record(&self, payment_id: u128) -> Option<MultiSigPaymentRecord> {
-        let history = self.get_payment_history_map();
-        history.get(payment_id)
+    pub fn index_subscription(&self, subscription: &Subscription) {
+        self.add_subscription_index(
+            DataKey::MerchantSubscriptions,
+            &subscription.merchant_address,
+            subscription.subscription_id,
+        );
+        self.add_subscription_index(
+            DataKey::PayerSubscriptions,
+            &subscription.payer_address,
+            subscription.subscription_id,
+        );
     }
 
-    pub fn get_next_payment_id(&self) -> u128 {
-        let current_counter: u128 = self
-            .env
+    pub fn get_merchant_subscriptions(&self, merchant: &Address) -> Vec<Subscription> {
+        self.get_indexed_subscriptions(DataKey::MerchantSubscriptions, merchant)
+    }
+
+    pub fn get_payer_subscriptions(&self, payer: &Address) -> Vec<Subscription> {
+        self.get_indexed_subscriptions(DataKey::PayerSubscriptions, payer)
+    }
+
+    // ===== Invoices =====
+    fn get_invoices_map(&self) -> Map<u64, Invoice> {
+        self.env
             .storage()
             .instance()
-            .get(&DataKey::PaymentCounter.as_symbol(self.env))
-            .unwrap_or(0);
-        let next_id = current_counter + 1;
+            .get(&DataKey::Invoices.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env))
+    }
+
+    pub fn get_next_invoice_id(&self) -> u64 {
+        self.next_id(DataKey::InvoiceCounter)
+    }
+
+    pub fn save_invoice(&self, invoice: &Invoice) {
+        let mut invoices = self.get_invoices_map();
+        invoices.set(invoice.invoice_id, invoice.clone());
         self.env
             .storage()
             .instance()
-            .set(&DataKey::PaymentCounter.as_symbol(self.env), &next_id);
-        next_id
+            .set(&DataKey::Invoices.as_symbol(self.env), &invoices);
     }
 
-    // ===== Payment records management =====
-    pub fn save_payment(&self, record: &PaymentRecord) {
-        let mut payments = self.get_payments_map();
-        payments.set(record.order_id.clone(), record.clone());
+    pub fn get_invoice(&self, invoice_id: u64) -> Result<Invoice, PaymentError> {
+        self.get_invoices_map()
+            .get(invoice_id)
+            .ok_or(PaymentError::PaymentNotFound)
+    }
+
+    // ===== Authorization holds =====
+    fn get_authorizations_map(&self) -> Map<u64, PaymentAuthorization> {
         self.env
             .storage()
             .instance()
-            .set(&DataKey::Payments.as_symbol(self.env), &payments);
+            .get(&DataKey::Authorizations.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env))
     }
 
-    pub fn get_payment(
+    pub fn get_next_authorization_id(&self) -> u64 {
+        self.next_id(DataKey::AuthorizationCounter)
+    }
+
+    pub fn save_authorization(&self, authorization: &PaymentAuthorization) {
+        let mut authorizations = self.get_authorizations_map();
+        authorizations.set(authorization.authorization_id, authorization.clone());
+        self.env.storage().instance().set(
+            &DataKey::Authorizations.as_symbol(self.env),
+            &authorizations,
+        );
+    }
+
+    pub fn get_authorization(
         &self,
-        order_id: &soroban_sdk::String,
-    ) -> Result<PaymentRecord, PaymentError> {
-        let payments = self.get_payments_map();
-        payments
-            .get(order_id.clone())
+        authorization_id: u64,
+    ) -> Result<PaymentAuthorization, PaymentError> {
+        self.get_authorizations_map()
+            .get(authorization_id)
             .ok_or(PaymentError::PaymentNotFound)
     }
 
-    pub fn update_payment(&self, record: &PaymentRecord) {
-        let mut payments = self.get_payments_map();
-        payments.set(record.order_id.clone(), record.clone());
+    // ===== Cross-token payments =====
+    pub fn set_swap_router(&self, router: &Address) {
         self.env
             .storage()
             .instance()
-            .set(&DataKey::Payments.as_symbol(self.env), &payments);
+            .set(&DataKey::SwapRouter.as_symbol(self.env), router);
     }
 
-    // ===== Refund requests management =====
-    pub fn save_refund(&self, request: &RefundRequest) {
-        let mut refunds = self.get_refunds_map();
-        refunds.set(request.refund_id.clone(), request.clone());
+    pub fn get_swap_router(&self) -> Option<Address> {
         self.env
             .storage()
             .instance()
-            .set(&DataKey::Refunds.as_symbol(self.env), &refunds);
+            .get(&DataKey::SwapRouter.as_symbol(self.env))
     }
 
-    pub fn get_refund(
-        &self,
-        refund_id: &soroban_sdk::String,
-    ) -> Result<RefundRequest, PaymentError> {
-        let refunds = self.get_refunds_map();
-        refunds
-            .get(refund_id.clone())
-            .ok_or(PaymentError::RefundNotFound)
+    // ===== Relayed payments =====
+    fn get_payer_relay_keys_map(&self) -> Map<Address, BytesN<32>> {
+        self.env
+            .storage()
+            .instance()
+            .get(&DataKey::PayerRelayKeys.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env))
     }
 
-    pub fn update_refund(&self, request: &RefundRequest) {
-        let mut refunds = self.get_refunds_map();
-        refunds.set(request.refund_id.clone(), request.clone());
+    /// Set or, with `None`, remove the key a payer signs relayed payments with
+    pub fn set_payer_relay_key(&self, payer: &Address, public_key: &Option<BytesN<32>>) {
+        let mut keys = self.get_payer_relay_keys_map();
+        match public_key {
+            Some(public_key) => keys.set(payer.clone(), public_key.clone()),
+            None => {
+                keys.remove(payer.clone());
+            }
+        }
         self.env
             .storage()
             .instance()
-            .set(&DataKey::Refunds.as_symbol(self.env), &refunds);
+            .set(&DataKey::PayerRelayKeys.as_symbol(self.env), &keys);
     }
 
-    fn get_multisig_payments_map(&self) -> Map<u128, MultiSigPayment> {
+    pub fn get_payer_relay_key(&self, payer: &Address) -> Option<BytesN<32>> {
+        self.get_payer_relay_keys_map().get(payer.clone())
+    }
+
+    // ===== Fiat-denominated orders =====
+    pub fn set_price_oracle(&self, config: &OracleConfig) {
         self.env
             .storage()
             .instance()
-            .get(&DataKey::MultiSigPayments.as_symbol(self.env))
-            .unwrap_or_else(|| Map::new(self.env))
+            .set(&DataKey::PriceOracle.as_symbol(self.env), config);
     }
 
-    fn get_payment_history_map(&self) -> Map<u128, MultiSigPaymentRecord> {
+    pub fn get_price_oracle(&self) -> Option<OracleConfig> {
         self.env
             .storage()
             .instance()
-            .get(&DataKey::PaymentHistory.as_symbol(self.env))
-            .unwrap_or_else(|| Map::new(self.env))
+            .get(&DataKey::PriceOracle.as_symbol(self.env))
     }
 
-    // ===== Admin and fee management =====
-    pub fn set_admin(&self, admin: &Address) {
+    // ===== Fee custody =====
+    pub fn set_fee_custody(&self, enabled: bool) {
         self.env
             .storage()
             .instance()
-            .set(&DataKey::Admin.as_symbol(self.env), &admin);
+            .set(&DataKey::FeeCustody.as_symbol(self.env), &enabled);
     }
 
-    fn require_admin(&self, admin: &Address) -> Result<(), PaymentError> {
-        let stored_admin: Address = self
-            .env
+    pub fn is_fee_custody_enabled(&self) -> bool {
+        self.env
             .storage()
             .instance()
-            .get(&DataKey::Admin.as_symbol(self.env))
-            .ok_or(PaymentError::AdminNotFound)?;
-        if stored_admin != *admin {
-            return Err(PaymentError::NotAuthorized);
-        }
-        Ok(())
+            .get(&DataKey::FeeCustody.as_symbol(self.env))
+            .unwrap_or(false)
     }
 
-    pub fn set_fee_info(&self, fee: &Fee, admin: &Address) -> Result<(), PaymentError> {
-        self.require_admin(admin)?;
-        if fee.fee_rate > 10 {
-            return Err(PaymentError::InvalidFeeRate);
-        }
+    pub fn set_fee_beneficiaries(&self, beneficiaries: &Vec<FeeBeneficiary>) {
+        self.env.storage().instance().set(
+            &DataKey::FeeBeneficiaries.as_symbol(self.env),
+            beneficiaries,
+        );
+    }
+
+    pub fn get_fee_beneficiaries(&self) -> Vec<FeeBeneficiary> {
         self.env
             .storage()
             .instance()
-            .set(&DataKey::Fee.as_symbol(self.env), &fee.clone());
-        self.env.events().publish(
-            ("fee_info_set",),
-            (
-                fee.fee_rate,
-                fee.fee_collector.clone(),
-                fee.fee_token.clone(),
-            ),
-        );
-        Ok(())
+            .get(&DataKey::FeeBeneficiaries.as_symbol(self.env))
+            .unwrap_or_else(|| Vec::new(self.env))
     }
 
-    pub fn get_fee_rate(&self) -> u64 {
+    fn get_fee_balances_map(&self) -> Map<Address, Map<Address, i128>> {
         self.env
             .storage()
             .instance()
-            .get::<_, Fee>(&DataKey::Fee.as_symbol(self.env))
-            .map(|f| f.fee_rate)
+            .get(&DataKey::FeeBalances.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env))
+    }
+
+    /// Custodied fees `beneficiary` can withdraw in `token`
+    pub fn get_fee_balance(&self, beneficiary: &Address, token: &Address) -> i128 {
+        self.get_fee_balances_map()
+            .get(beneficiary.clone())
+            .and_then(|balances| balances.get(token.clone()))
             .unwrap_or(0)
     }
 
-    pub fn get_fee_collector(&self) -> Option<Address> {
+    /// All custodied fee balances of `beneficiary`, per token
+    pub fn get_fee_balances(&self, beneficiary: &Address) -> Map<Address, i128> {
+        self.get_fee_balances_map()
+            .get(beneficiary.clone())
+            .unwrap_or_else(|| Map::new(self.env))
+    }
+
+    /// Adjust a beneficiary's balance and the contract's total for `token` by `delta`
+    pub fn adjust_fee_balance(&self, beneficiary: &Address, token: &Address, delta: i128) {
+        let mut all_balances = self.get_fee_balances_map();
+        let mut balances = all_balances
+            .get(beneficiary.clone())
+            .unwrap_or_else(|| Map::new(self.env));
+        let balance = balances.get(token.clone()).unwrap_or(0) + delta;
+        if balance == 0 {
+            balances.remove(token.clone());
+        } else {
+            balances.set(token.clone(), balance);
+        }
+        all_balances.set(beneficiary.clone(), balances);
         self.env
             .storage()
             .instance()
-            .get::<_, Fee>(&DataKey::Fee.as_symbol(self.env))
-            .map(|f| f.fee_collector)
+            .set(&DataKey::FeeBalances.as_symbol(self.env), &all_balances);
+
+        let mut accrued = self.get_accrued_fees_map();
+        accrued.set(
+            token.clone(),
+            accrued.get(token.clone()).unwrap_or(0) + delta,
+        );
+        self.env
+            .storage()
+            .instance()
+            .set(&DataKey::AccruedFees.as_symbol(self.env), &accrued);
     }
 
-    pub fn get_fee_token(&self) -> Option<Address> {
+    fn get_accrued_fees_map(&self) -> Map<Address, i128> {
         self.env
             .storage()
             .instance()
-            .get::<_, Fee>(&DataKey::Fee.as_symbol(self.env))
-            .map(|f| f.fee_token)
+            .get(&DataKey::AccruedFees.as_symbol(self.env))
+            .unwrap_or_else(|| Map::new(self.env))
     }
 
-    pub fn calculate_fee(&self, amount: i128) -> i128 {
-        let rate = i128::from(self.get_fee_rate());
-        let quotient = amount / 100;
-        let remainder = amount % 100;
-        quotient * rate + (remainder * rate) / 100
+    /// Total custodied fees held by the contract in `token`
+    pub fn get_accrued_fees(&self, token: &Address) -> i128 {
+        self.get_accrued_fees_map().get(token.clone()).unwrap_or(0)
     }
 
     pub fn get_admin(&self) -> Option<Address> {
@@ -456,7 +1541,7 @@ impl<'a> Storage<'a> {
             .get(&DataKey::Admin.as_symbol(self.env))
     }
 
-    pub fn get_payments_map(&self) -> Map<soroban_sdk::String, PaymentRecord> {
+    pub fn get_payments_map(&self) -> Map<PaymentKey, PaymentRecord> {
         self.env
             .storage()
             .instance()
@@ -484,7 +1569,7 @@ impl<'a> Storage<'a> {
     }
 
     /// Get payer payment indices map
-    fn get_payer_payment_indices_map(&self) -> Map<Address, Vec<String>> {
+    fn get_payer_payment_indices_map(&self) -> Map<Address, Vec<PaymentKey>> {
         self.env
             .storage()
             .instance()
@@ -506,14 +1591,14 @@ impl<'a> Storage<'a> {
         );
     }
 
-    /// Add order_id to payer payment index
-    pub fn save_payer_payment_index(&self, payer: &Address, order_id: &String) {
+    /// Add a payment to the payer payment index
+    pub fn save_payer_payment_index(&self, payer: &Address, key: &PaymentKey) {
         let mut indices = self.get_payer_payment_indices_map();
-        let mut order_ids = indices
+        let mut keys = indices
             .get(payer.clone())
             .unwrap_or_else(|| Vec::new(self.env));
-        order_ids.push_back(order_id.clone());
-        indices.set(payer.clone(), order_ids);
+        keys.push_back(key.clone());
+        indices.set(payer.clone(), keys);
         self.env
             .storage()
             .instance()
@@ -528,8 +1613,17 @@ impl<'a> Storage<'a> {
             .unwrap_or_else(|| Vec::new(self.env))
     }
 
-    /// Get all order_ids for a payer
-    pub fn get_payer_payment_indices(&self, payer: &Address) -> Vec<String> {
+    /// Get the keys of all of a merchant's payments
+    pub fn get_merchant_payment_keys(&self, merchant: &Address) -> Vec<PaymentKey> {
+        let mut keys = Vec::new(self.env);
+        for order_id in self.get_merchant_payment_indices(merchant).iter() {
+            keys.push_back((merchant.clone(), order_id));
+        }
+        keys
+    }
+
+    /// Get the keys of all of a payer's payments
+    pub fn get_payer_payment_indices(&self, payer: &Address) -> Vec<PaymentKey> {
         let indices = self.get_payer_payment_indices_map();
         indices
             .get(payer.clone())
@@ -558,18 +1652,18 @@ impl<'a> Storage<'a> {
         }
     }
 
-    /// Remove order_id from payer payment index
-    pub fn remove_payer_payment_index(&self, payer: &Address, order_id: &String) {
+    /// Remove a payment from the payer payment index
+    pub fn remove_payer_payment_index(&self, payer: &Address, key: &PaymentKey) {
         let mut indices = self.get_payer_payment_indices_map();
-        if let Some(order_ids) = indices.get(payer.clone()) {
-            let mut new_order_ids = Vec::new(self.env);
-            for id in order_ids.iter() {
-                if id != *order_id {
-                    new_order_ids.push_back(id);
+        if let Some(keys) = indices.get(payer.clone()) {
+            let mut new_keys = Vec::new(self.env);
+            for k in keys.iter() {
+                if k != *key {
+                    new_keys.push_back(k);
                 }
             }
-            if new_order_ids.len() > 0 {
-                indices.set(payer.clone(), new_order_ids);
+            if !new_keys.is_empty() {
+                indices.set(payer.clone(), new_keys);
             } else {
                 indices.remove(payer.clone());
             }
@@ -583,14 +1677,14 @@ impl<'a> Storage<'a> {
     /// Query payments with filters
     pub fn query_payments_with_filters(
         &self,
-        order_ids: &Vec<String>,
+        keys: &Vec<PaymentKey>,
         filter: &PaymentQueryFilter,
     ) -> Vec<PaymentRecord> {
         let payments = self.get_payments_map();
         let mut results = Vec::new(self.env);
 
-        for order_id in order_ids.iter() {
-            if let Some(record) = payments.get(order_id.clone()) {
+        for key in keys.iter() {
+            if let Some(record) = payments.get(key) {
                 // Apply date filter
                 if let Some(date_start) = filter.date_start {
                     if record.paid_at < date_start {
@@ -691,15 +1785,15 @@ impl<'a> Storage<'a> {
     pub fn paginate_payments(
         &self,
         records: Vec<PaymentRecord>,
-        cursor: Option<String>,
+        cursor: Option<PaymentKey>,
         limit: u32,
-    ) -> (Vec<PaymentRecord>, Option<String>) {
+    ) -> (Vec<PaymentRecord>, Option<PaymentKey>) {
         let mut start_idx = 0u32;
 
         // Find cursor position if provided
-        if let Some(ref cursor_id) = cursor {
+        if let Some(ref cursor) = cursor {
             for (idx, record) in records.iter().enumerate() {
-                if record.order_id == *cursor_id {
+                if record.key() == *cursor {
                     start_idx = (idx + 1) as u32;
                     break;
                 }
@@ -707,8 +1801,8 @@ impl<'a> Storage<'a> {
         }
 
         let mut paginated = Vec::new(self.env);
-        let mut next_cursor: Option<String> = None;
-        let max_idx = core::cmp::min(start_idx + limit, records.len() as u32);
+        let mut next_cursor: Option<PaymentKey> = None;
+        let max_idx = core::cmp::min(start_idx + limit, records.len());
 
         for i in start_idx..max_idx {
             if let Some(record) = records.get(i) {
@@ -717,11 +1811,9 @@ impl<'a> Storage<'a> {
         }
 
         // Set next cursor if there are more results
-        if max_idx < records.len() as u32 {
-            if paginated.len() > 0 {
-                if let Some(last_record) = paginated.get((paginated.len() - 1) as u32) {
-                    next_cursor = Some(last_record.order_id.clone());
-                }
+        if max_idx < records.len() {
+            if let Some(last_record) = paginated.last() {
+                next_cursor = Some(last_record.key());
             }
         }
 
@@ -746,7 +1838,7 @@ impl<'a> Storage<'a> {
     }
 
     /// Get payment archive map
-    fn get_payment_archive_map(&self) -> Map<String, PaymentRecord> {
+    fn get_payment_archive_map(&self) -> Map<PaymentKey, PaymentRecord> {
         self.env
             .storage()
             .instance()
@@ -757,7 +1849,7 @@ impl<'a> Storage<'a> {
     /// Archive a payment record
     pub fn archive_payment_record(&self, record: &PaymentRecord) {
         let mut archive = self.get_payment_archive_map();
-        archive.set(record.order_id.clone(), record.clone());
+        archive.set(record.key(), record.clone());
         self.env
             .storage()
             .instance()
@@ -765,8 +1857,26 @@ impl<'a> Storage<'a> {
     }
 
     /// Get archived payment record
-    pub fn get_archived_payment(&self, order_id: &String) -> Option<PaymentRecord> {
+    pub fn get_archived_payment(
+        &self,
+        merchant: &Address,
+        order_id: &String,
+    ) -> Option<PaymentRecord> {
         let archive = self.get_payment_archive_map();
-        archive.get(order_id.clone())
+        archive.get((merchant.clone(), order_id.clone()))
+    }
+
+    /// Find the payment that settled a merchant's order_id, whether active or archived
+    pub fn find_order_payment(
+        &self,
+        merchant: &Address,
+        order_id: &String,
+    ) -> Option<(PaymentRecord, bool)> {
+        let key = (merchant.clone(), order_id.clone());
+        if let Some(record) = self.get_payments_map().get(key) {
+            return Some((record, false));
+        }
+        self.get_archived_payment(merchant, order_id)
+            .map(|record| (record, true))
     }
 }
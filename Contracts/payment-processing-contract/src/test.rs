@@ -3,19 +3,28 @@ extern crate alloc;
 
 use crate::{
     error::PaymentError,
-    storage::Storage,
+    helper::{
+        DEFAULT_TRANSACTION_LIMIT, LEGACY_ORDER_SCHEMA_VERSION, ORDER_SCHEMA_VERSION,
+        SPLIT_ORDER_SCHEMA_VERSION, SWAP_ORDER_SCHEMA_VERSION, TYPED_ORDER_SCHEMA_VERSION,
+    },
+    storage::{DataKey, Storage},
+    testutils::MerchantKeypair,
     types::{
-        BatchMerchantRegistration, BatchPayment, BatchTokenAddition, GasEstimate, MerchantCategory,
-        NonceTracker, PaymentOrder, PaymentQueryFilter, PaymentQueryResult, PaymentRecordStatus,
-        PaymentStats, PaymentStatus, ProfileUpdateData, RefundRequest, RefundStatus, SortField,
-        SortOrder,
+        AdminCouncil, AuthorizationStatus, BatchMerchantRegistration, BatchMode, BatchPayment,
+        BatchSignatures, BatchTokenAddition, CouncilAction, DisputeRuling, DisputeStatus,
+        EscrowStatus, FeeBeneficiary, FeeMode, FeeSchedule, FeeScope, InvoiceStatus, InvoiceTerms,
+        LimitScope, MerchantCategory, OracleAsset, OracleConfig, OrderStatus, PaymentOrder,
+        PaymentQueryFilter, PaymentRecord, PaymentRecordStatus, PaymentStatus, RefundStatus,
+        RelayedPayment, ReserveScope, Role, RollingReserveConfig, SettlementSchedule, SortField,
+        SortOrder, SplitPayout, SplitRecipient, SubscriptionStatus, VolumeCounter, VolumeLimit,
+        VolumeUsage,
     },
     PaymentProcessingContract, PaymentProcessingContractClient,
 };
 use alloc::format;
 use soroban_sdk::{
     testutils::{Address as _, Ledger},
-    token, Address, BytesN, Env, String, Symbol, Vec,
+    token, Address, BytesN, Env, Map, String, Symbol, Vec,
 };
 
 fn create_token_contract<'a>(
@@ -44,6 +53,11 @@ fn create_payment_order(
         expiration,
         order_id: String::from_str(&env, "TEST_ORDER_1"),
         fee_amount: 0, // Initial fee amount, will be calculated during processing
+        schema_version: ORDER_SCHEMA_VERSION,
+        splits: Vec::new(env),
+        input_token: None,
+        max_input_amount: 0,
+        currency: None,
     }
 }
 
@@ -72,13 +86,13 @@ fn test_fee_management() {
     env.mock_all_auths();
     client.set_admin(&admin);
 
-    // Set fee (5%)
+    // Set fee (500 bps = 5%)
     env.mock_all_auths();
-    client.set_fee(&5, &fee_collector, &fee_token);
+    client.set_fee(&admin, &500, &fee_collector, &fee_token);
 
     // Get fee info and verify
     let (rate, collector, token) = client.get_fee_info();
-    assert_eq!(rate, 5);
+    assert_eq!(rate, 500);
     assert_eq!(collector, fee_collector);
     assert_eq!(token, fee_token);
 }
@@ -90,12 +104,13 @@ fn test_set_fee_no_admin() {
     let contract_id = env.register(PaymentProcessingContract {}, ());
     let client = PaymentProcessingContractClient::new(&env, &contract_id);
 
+    let admin = Address::generate(&env);
     let fee_collector = Address::generate(&env);
     let fee_token = Address::generate(&env);
 
     // Try to set fee without setting admin first
     env.mock_all_auths();
-    client.set_fee(&5, &fee_collector, &fee_token);
+    client.set_fee(&admin, &500, &fee_collector, &fee_token);
 }
 
 #[test]
@@ -115,7 +130,848 @@ fn test_invalid_fee_rate() {
 
     // Try to set invalid fee rate (11% > 10% max)
     env.mock_all_auths();
-    client.set_fee(&11, &fee_collector, &fee_token);
+    client.set_fee(&admin, &1100, &fee_collector, &fee_token);
+}
+
+#[test]
+fn test_basis_point_fee_schedule() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+    let token_client = token::Client::new(&env, &token);
+    token::StellarAssetClient::new(&env, &token).mint(&payer, &9_000);
+
+    // 1.9% plus a flat 5, at least 30 and at most 100 per transaction
+    client.set_fee_schedule(
+        &admin,
+        &FeeSchedule {
+            rate_bps: 190,
+            fixed_fee: 5,
+            min_fee: 30,
+            max_fee: Some(100),
+        },
+    );
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let amounts = [1_000i64, 5_000, 100];
+    let expected_fees = [30i128, 100, 30]; // 24 -> min, 100 -> exact, 6 -> min
+    let mut collected = 0i128;
+    for (i, amount) in amounts.iter().enumerate() {
+        let mut order = create_payment_order(&env, &merchant, *amount, &token, expiration);
+        order.nonce = i as u32 + 1;
+        order.order_id = String::from_str(&env, &format!("FEE_ORDER_{}", i));
+        let signature = merchant_keys.sign_order(&env, &client.address, &order);
+        client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+
+        collected += expected_fees[i];
+        assert_eq!(token_client.balance(&admin), collected);
+    }
+    assert_eq!(token_client.balance(&merchant), 6_100 - collected);
+}
+
+#[test]
+fn test_fee_override_resolution() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+    let token_client = token::Client::new(&env, &token);
+
+    client.set_fee_schedule(&admin, &FeeSchedule::from_rate(100));
+    assert_eq!(
+        client.get_effective_fee_schedule(&merchant, &token),
+        FeeSchedule::from_rate(100)
+    );
+
+    // Category, token and merchant overrides, from least to most specific
+    client.set_fee_override(
+        &admin,
+        &FeeScope::Category(MerchantCategory::Retail),
+        &Some(FeeSchedule::from_rate(200)),
+    );
+    assert_eq!(
+        client
+            .get_effective_fee_schedule(&merchant, &token)
+            .rate_bps,
+        200
+    );
+    client.set_fee_override(
+        &admin,
+        &FeeScope::Token(token.clone()),
+        &Some(FeeSchedule::from_rate(300)),
+    );
+    assert_eq!(
+        client
+            .get_effective_fee_schedule(&merchant, &token)
+            .rate_bps,
+        300
+    );
+    client.set_fee_override(
+        &admin,
+        &FeeScope::Merchant(merchant.clone()),
+        &Some(FeeSchedule::from_rate(25)),
+    );
+    assert_eq!(
+        client
+            .get_effective_fee_schedule(&merchant, &token)
+            .rate_bps,
+        25
+    );
+
+    // 0.25% of 1000 goes to the collector
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let order = create_payment_order(&env, &merchant, 1000, &token, expiration);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(token_client.balance(&admin), 2);
+    assert_eq!(token_client.balance(&merchant), 998);
+
+    // Clearing the merchant override falls back to the token override
+    client.set_fee_override(&admin, &FeeScope::Merchant(merchant.clone()), &None);
+    assert_eq!(
+        client
+            .get_effective_fee_schedule(&merchant, &token)
+            .rate_bps,
+        300
+    );
+}
+
+#[test]
+fn test_fees_collected_per_token() {
+    let env = Env::default();
+    let (client, admin, merchant, token_a, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+    let (token_b, token_b_client, token_b_admin) = create_token_contract(&env, &admin);
+    let token_a_client = token::Client::new(&env, &token_a);
+    client.add_supported_token(&merchant, &token_b);
+    token_b_admin.mint(&payer, &1000);
+
+    client.set_fee_schedule(&admin, &FeeSchedule::from_rate(100));
+    let collector_b = Address::generate(&env);
+    client.set_token_fee(&admin, &token_b, &collector_b, &FeeSchedule::from_rate(250));
+
+    let info = client.get_token_fee_info(&token_b);
+    assert_eq!(info.fee_collector, collector_b);
+    assert_eq!(info.schedule.rate_bps, 250);
+    let info = client.get_token_fee_info(&token_a);
+    assert_eq!(info.fee_collector, admin);
+    assert_eq!(info.schedule.rate_bps, 100);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let order_a = create_payment_order(&env, &merchant, 1000, &token_a, expiration);
+    let mut order_b = create_payment_order(&env, &merchant, 1000, &token_b, expiration);
+    order_b.nonce += 1;
+    order_b.order_id = String::from_str(&env, "TOKEN_B_ORDER");
+    for order in [order_a, order_b] {
+        let signature = merchant_keys.sign_order(&env, &client.address, &order);
+        client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    }
+
+    // Default collector takes 1% of token A, token B's collector takes 2.5% of token B
+    assert_eq!(token_a_client.balance(&admin), 10);
+    assert_eq!(token_a_client.balance(&merchant), 990);
+    assert_eq!(token_b_client.balance(&collector_b), 25);
+    assert_eq!(token_b_client.balance(&merchant), 975);
+
+    // Removing the token fee falls back to the default collector and schedule
+    client.remove_token_fee(&admin, &token_b);
+    let info = client.get_token_fee_info(&token_b);
+    assert_eq!(info.fee_collector, admin);
+    assert_eq!(info.schedule.rate_bps, 100);
+}
+
+#[test]
+fn test_fee_custody_and_withdrawal() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+    let token_client = token::Client::new(&env, &token);
+
+    client.set_fee_schedule(&admin, &FeeSchedule::from_rate(500));
+    client.set_fee_custody(&admin, &true);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    for i in 0..2u32 {
+        let mut order = create_payment_order(&env, &merchant, 300, &token, expiration);
+        order.nonce = i + 1;
+        order.order_id = String::from_str(&env, &format!("CUSTODY_{}", i));
+        let signature = merchant_keys.sign_order(&env, &client.address, &order);
+        client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    }
+
+    // Fees stay in the contract and accrue to the collector's ledger balance
+    assert_eq!(token_client.balance(&client.address), 30);
+    assert_eq!(token_client.balance(&admin), 0);
+    assert_eq!(client.get_accrued_fees(&token), 30);
+    assert_eq!(client.get_fee_balance(&admin, &token), 30);
+    assert_eq!(client.get_fee_balances(&admin).get(token.clone()), Some(30));
+
+    // One withdrawal collects everything accrued
+    assert_eq!(client.withdraw_fees(&admin, &token), 30);
+    assert_eq!(token_client.balance(&admin), 30);
+    assert_eq!(client.get_accrued_fees(&token), 0);
+    assert_eq!(client.get_fee_balance(&admin, &token), 0);
+
+    let result = client.try_withdraw_fees(&admin, &token);
+    assert_eq!(result, Err(Ok(PaymentError::InsufficientBalance)));
+}
+
+#[test]
+fn test_fee_beneficiary_shares() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+    let token_client = token::Client::new(&env, &token);
+
+    let treasury = Address::generate(&env);
+    let partner = Address::generate(&env);
+    client.set_fee_schedule(&admin, &FeeSchedule::from_rate(500));
+    client.set_fee_custody(&admin, &true);
+
+    // Shares must total 100%
+    let result = client.try_set_fee_beneficiaries(
+        &admin,
+        &Vec::from_array(
+            &env,
+            [
+                FeeBeneficiary {
+                    address: treasury.clone(),
+                    share_bps: 7_000,
+                },
+                FeeBeneficiary {
+                    address: partner.clone(),
+                    share_bps: 2_000,
+                },
+            ],
+        ),
+    );
+    assert_eq!(result, Err(Ok(PaymentError::InvalidBeneficiary)));
+
+    // Each beneficiary appears once
+    let result = client.try_set_fee_beneficiaries(
+        &admin,
+        &Vec::from_array(
+            &env,
+            [
+                FeeBeneficiary {
+                    address: treasury.clone(),
+                    share_bps: 5_000,
+                },
+                FeeBeneficiary {
+                    address: treasury.clone(),
+                    share_bps: 5_000,
+                },
+            ],
+        ),
+    );
+    assert_eq!(result, Err(Ok(PaymentError::InvalidBeneficiary)));
+
+    client.set_fee_beneficiaries(
+        &admin,
+        &Vec::from_array(
+            &env,
+            [
+                FeeBeneficiary {
+                    address: treasury.clone(),
+                    share_bps: 7_000,
+                },
+                FeeBeneficiary {
+                    address: partner.clone(),
+                    share_bps: 3_000,
+                },
+            ],
+        ),
+    );
+
+    // 5% of 990 is 49; the partner gets 30% rounded down, the treasury the rest
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let order = create_payment_order(&env, &merchant, 990, &token, expiration);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+
+    assert_eq!(client.get_accrued_fees(&token), 49);
+    assert_eq!(client.get_fee_balance(&partner, &token), 14);
+    assert_eq!(client.get_fee_balance(&treasury, &token), 35);
+    assert_eq!(client.get_fee_balance(&admin, &token), 0);
+
+    client.withdraw_fees(&partner, &token);
+    assert_eq!(token_client.balance(&partner), 14);
+    assert_eq!(client.get_accrued_fees(&token), 35);
+}
+
+#[test]
+fn test_surcharge_fee_mode() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+    let token_client = token::Client::new(&env, &token);
+    client.set_fee_schedule(&admin, &FeeSchedule::from_rate(500));
+
+    // Default mode deducts the fee from the merchant
+    assert_eq!(client.get_merchant_fee_mode(&merchant), FeeMode::Deduct);
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let order = create_payment_order(&env, &merchant, 200, &token, expiration);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let record =
+        client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(
+        (record.gross_amount, record.fee_amount, record.net_amount),
+        (200, 10, 190)
+    );
+
+    // Surcharge mode adds the signed fee on top for the payer
+    client.set_merchant_fee_mode(&merchant, &FeeMode::Surcharge);
+    let mut order = create_payment_order(&env, &merchant, 600, &token, expiration);
+    order.nonce += 1;
+    order.order_id = String::from_str(&env, "SURCHARGE_ORDER");
+
+    // A signed fee that doesn't match the computed fee is rejected
+    order.fee_amount = 20;
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let result =
+        client.try_process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidAmount)));
+
+    order.fee_amount = 30;
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let record =
+        client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(record.amount, 600);
+    assert_eq!(
+        (record.gross_amount, record.fee_amount, record.net_amount),
+        (630, 30, 600)
+    );
+
+    assert_eq!(token_client.balance(&payer), 1000 - 200 - 630);
+    assert_eq!(token_client.balance(&merchant), 190 + 600);
+    assert_eq!(token_client.balance(&admin), 10 + 30);
+}
+
+fn escrow_order(
+    env: &Env,
+    merchant: &Address,
+    token: &Address,
+    order_id: &str,
+    nonce: u32,
+) -> PaymentOrder {
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let mut order = create_payment_order(env, merchant, 0, token, expiration);
+    order.order_id = String::from_str(env, order_id);
+    order.nonce += nonce;
+    order
+}
+
+#[test]
+fn test_escrow_release_on_delivery_and_timeout() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+    let token_client = token::Client::new(&env, &token);
+
+    // Timeouts must be positive and bounded
+    let result = client.try_set_merchant_escrow(&admin, &merchant, &Some(0));
+    assert_eq!(result, Err(Ok(PaymentError::InvalidDateRange)));
+    client.set_merchant_escrow(&admin, &merchant, &Some(3600));
+    assert_eq!(client.get_merchant_escrow(&merchant), Some(3600));
+
+    // Escrowed payments are held by the contract
+    let mut order = escrow_order(&env, &merchant, &token, "ESCROW_1", 0);
+    order.amount = 200;
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    let order_id = order.order_id;
+    let record = client.get_payment_by_id(&merchant, &merchant, &order_id);
+    assert_eq!(record.escrow_status, EscrowStatus::Held);
+    assert_eq!(record.escrow_release_at, env.ledger().timestamp() + 3600);
+    assert_eq!(token_client.balance(&merchant), 0);
+    assert_eq!(token_client.balance(&client.address), 200);
+
+    // Only the payer can confirm delivery
+    let result = client.try_confirm_delivery(&merchant, &merchant, &order_id);
+    assert_eq!(result, Err(Ok(PaymentError::NotAuthorized)));
+    client.confirm_delivery(&payer, &merchant, &order_id);
+    let record = client.get_payment_by_id(&merchant, &merchant, &order_id);
+    assert_eq!(record.escrow_status, EscrowStatus::Released);
+    assert_eq!(token_client.balance(&merchant), 200);
+
+    // A second escrow can't be released before its timeout passes
+    let mut order = escrow_order(&env, &merchant, &token, "ESCROW_2", 1);
+    order.amount = 300;
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    let order_id = order.order_id;
+    let result = client.try_release_escrow(&merchant, &order_id);
+    assert_eq!(result, Err(Ok(PaymentError::NotAuthorized)));
+
+    env.ledger().with_mut(|li| li.timestamp += 3600);
+    client.release_escrow(&merchant, &order_id);
+    assert_eq!(token_client.balance(&merchant), 500);
+    assert_eq!(token_client.balance(&client.address), 0);
+
+    // Released escrows can't be paid out twice
+    let result = client.try_release_escrow(&merchant, &order_id);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidStatus)));
+}
+
+#[test]
+fn test_escrow_dispute_freezes_funds() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+    let token_client = token::Client::new(&env, &token);
+    client.set_merchant_escrow(&admin, &merchant, &Some(3600));
+
+    let mut order = escrow_order(&env, &merchant, &token, "ESCROW_1", 0);
+    order.amount = 400;
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    let order_id = order.order_id;
+
+    // Escrowed funds can't go through the merchant-funded refund flow
+    let result = client.try_initiate_refund(
+        &payer,
+        &String::from_str(&env, "REFUND_1"),
+        &merchant,
+        &order_id,
+        &100,
+        &String::from_str(&env, "Not delivered"),
+    );
+    assert_eq!(result, Err(Ok(PaymentError::NotRefundable)));
+
+    client.dispute_escrow(&payer, &merchant, &order_id);
+    let record = client.get_payment_by_id(&merchant, &merchant, &order_id);
+    assert_eq!(record.escrow_status, EscrowStatus::Disputed);
+
+    // A disputed escrow stays frozen past its timeout
+    env.ledger().with_mut(|li| li.timestamp += 7200);
+    let result = client.try_release_escrow(&merchant, &order_id);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidStatus)));
+    let result = client.try_confirm_delivery(&payer, &merchant, &order_id);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidStatus)));
+
+    // The refund can't exceed what the escrow holds
+    let result = client.try_resolve_escrow_dispute(&admin, &merchant, &order_id, &401);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidAmount)));
+
+    client.resolve_escrow_dispute(&admin, &merchant, &order_id, &150);
+    let record = client.get_payment_by_id(&merchant, &merchant, &order_id);
+    assert_eq!(record.escrow_status, EscrowStatus::Released);
+    assert_eq!(record.refunded_amount, 150);
+    assert_eq!(token_client.balance(&payer), 1000 - 400 + 150);
+    assert_eq!(token_client.balance(&merchant), 250);
+    assert_eq!(token_client.balance(&client.address), 0);
+}
+
+#[test]
+fn test_dispute_arbitration_from_reserve() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+    let token_client = token::Client::new(&env, &token);
+    let arbitrator = Address::generate(&env);
+    client.add_arbitrator(&admin, &arbitrator);
+    assert_eq!(
+        client.get_arbitrators(),
+        Vec::from_array(&env, [arbitrator.clone()])
+    );
+
+    let mut order = escrow_order(&env, &merchant, &token, "DISPUTED_ORDER", 0);
+    order.amount = 400;
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    let order_id = order.order_id;
+    client.deposit_reserve(&merchant, &token, &300);
+
+    // Only the payer can open a dispute, for at most the refundable amount
+    let reason = String::from_str(&env, "Item not as described");
+    let result = client.try_open_dispute(&merchant, &merchant, &order_id, &200, &reason);
+    assert_eq!(result, Err(Ok(PaymentError::NotAuthorized)));
+    let result = client.try_open_dispute(&payer, &merchant, &order_id, &401, &reason);
+    assert_eq!(result, Err(Ok(PaymentError::ExceedsOriginalAmount)));
+    client.open_dispute(&payer, &merchant, &order_id, &200, &reason);
+    let result = client.try_open_dispute(&payer, &merchant, &order_id, &200, &reason);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidStatus)));
+
+    // The disputed amount is held in the reserve and blocks the refund flow
+    let result = client.try_withdraw_reserve(&merchant, &token, &101);
+    assert_eq!(result, Err(Ok(PaymentError::InsufficientBalance)));
+    let result = client.try_initiate_refund(
+        &payer,
+        &String::from_str(&env, "REFUND_1"),
+        &merchant,
+        &order_id,
+        &100,
+        &reason,
+    );
+    assert_eq!(result, Err(Ok(PaymentError::NotRefundable)));
+
+    // Both sides submit evidence within the deadline
+    let payer_evidence = BytesN::from_array(&env, &[1; 32]);
+    let merchant_evidence = BytesN::from_array(&env, &[2; 32]);
+    client.submit_dispute_evidence(&payer, &merchant, &order_id, &payer_evidence);
+    client.submit_dispute_evidence(&merchant, &merchant, &order_id, &merchant_evidence);
+    let result =
+        client.try_submit_dispute_evidence(&arbitrator, &merchant, &order_id, &payer_evidence);
+    assert_eq!(result, Err(Ok(PaymentError::NotAuthorized)));
+
+    // Rulings wait for the evidence period and come only from arbitrators
+    let ruling = DisputeRuling::Partial(120);
+    let result = client.try_resolve_dispute(&arbitrator, &merchant, &order_id, &ruling);
+    assert_eq!(result, Err(Ok(PaymentError::NotAuthorized)));
+    env.ledger()
+        .with_mut(|li| li.timestamp += 7 * 24 * 60 * 60 + 1);
+    let result = client.try_submit_dispute_evidence(&payer, &merchant, &order_id, &payer_evidence);
+    assert_eq!(result, Err(Ok(PaymentError::PaymentExpired)));
+    let result = client.try_resolve_dispute(&admin, &merchant, &order_id, &ruling);
+    assert_eq!(result, Err(Ok(PaymentError::NotAuthorized)));
+
+    client.resolve_dispute(&arbitrator, &merchant, &order_id, &ruling);
+    let dispute = client.get_dispute(&merchant, &order_id);
+    assert_eq!(dispute.status, DisputeStatus::PartiallyRefunded);
+    assert_eq!(dispute.refund_amount, 120);
+    assert_eq!(dispute.arbitrator, Some(arbitrator.clone()));
+    assert_eq!(
+        dispute.payer_evidence,
+        Vec::from_array(&env, [payer_evidence])
+    );
+    assert_eq!(
+        dispute.merchant_evidence,
+        Vec::from_array(&env, [merchant_evidence])
+    );
+    assert_eq!(
+        client
+            .get_payment_by_id(&payer, &merchant, &order_id)
+            .refunded_amount,
+        120
+    );
+    assert_eq!(token_client.balance(&payer), 1000 - 400 + 120);
+
+    // The hold is lifted once ruled on
+    assert_eq!(client.get_merchant_reserve(&merchant, &token), 180);
+    client.withdraw_reserve(&merchant, &token, &180);
+    assert_eq!(token_client.balance(&merchant), 400 - 120);
+
+    let result =
+        client.try_resolve_dispute(&arbitrator, &merchant, &order_id, &DisputeRuling::Denied);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidStatus)));
+}
+
+#[test]
+fn test_dispute_arbitration_from_escrow() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+    let token_client = token::Client::new(&env, &token);
+    let arbitrator = Address::generate(&env);
+    client.add_arbitrator(&admin, &arbitrator);
+    client.set_merchant_escrow(&admin, &merchant, &Some(3600));
+
+    let mut order = escrow_order(&env, &merchant, &token, "ESCROW_1", 0);
+    order.amount = 300;
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    let order_id = order.order_id;
+
+    // Opening a dispute freezes the escrow until the arbitrator rules
+    let reason = String::from_str(&env, "Never delivered");
+    client.open_dispute(&payer, &merchant, &order_id, &300, &reason);
+    let record = client.get_payment_by_id(&payer, &merchant, &order_id);
+    assert_eq!(record.escrow_status, EscrowStatus::Disputed);
+    let result = client.try_resolve_escrow_dispute(&admin, &merchant, &order_id, &0);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidStatus)));
+
+    env.ledger()
+        .with_mut(|li| li.timestamp += 7 * 24 * 60 * 60 + 1);
+    client.resolve_dispute(&arbitrator, &merchant, &order_id, &DisputeRuling::Full);
+
+    let record = client.get_payment_by_id(&payer, &merchant, &order_id);
+    assert_eq!(record.escrow_status, EscrowStatus::Released);
+    assert_eq!(record.refunded_amount, 300);
+    assert_eq!(
+        client.get_dispute(&merchant, &order_id).status,
+        DisputeStatus::Refunded
+    );
+    assert_eq!(token_client.balance(&payer), 1000);
+    assert_eq!(token_client.balance(&merchant), 0);
+    assert_eq!(token_client.balance(&client.address), 0);
+}
+
+#[test]
+fn test_subscription_charges_each_period() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, _merchant_keys) = setup_signed_payment_test(&env);
+    let token_client = token::Client::new(&env, &token);
+    let day = 24 * 60 * 60;
+
+    let plan_id =
+        client.create_subscription_plan(&merchant, &token, &100, &(30 * day), &(7 * day), &2);
+    let subscription_id = client.subscribe(&payer, &plan_id);
+    token_client.approve(
+        &payer,
+        &client.address,
+        &1000,
+        &(env.ledger().sequence() + 1000),
+    );
+
+    // Nothing is due during the trial
+    let result = client.try_charge_due(&subscription_id);
+    assert_eq!(result, Err(Ok(PaymentError::NotDue)));
+
+    env.ledger().with_mut(|li| li.timestamp += 7 * day);
+    let record = client.charge_due(&subscription_id);
+    assert_eq!(record.order_id, String::from_str(&env, "SUB-1-1"));
+    assert_eq!(record.payer_address, payer);
+    assert_eq!(token_client.balance(&merchant), 100);
+    assert_eq!(token_client.allowance(&payer, &client.address), 900);
+
+    // The next cycle is due one period later
+    let result = client.try_charge_due(&subscription_id);
+    assert_eq!(result, Err(Ok(PaymentError::NotDue)));
+    env.ledger().with_mut(|li| li.timestamp += 30 * day);
+    let record = client.charge_due(&subscription_id);
+    assert_eq!(record.order_id, String::from_str(&env, "SUB-1-2"));
+
+    // The plan's last cycle completes the subscription
+    let subscription = client.get_subscription(&subscription_id);
+    assert_eq!(subscription.cycles_charged, 2);
+    assert_eq!(subscription.status, SubscriptionStatus::Completed);
+    env.ledger().with_mut(|li| li.timestamp += 30 * day);
+    let result = client.try_charge_due(&subscription_id);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidStatus)));
+
+    assert_eq!(token_client.balance(&payer), 800);
+    assert_eq!(client.get_merchant_subscriptions(&merchant).len(), 1);
+    assert_eq!(
+        client.get_payer_subscriptions(&payer),
+        Vec::from_array(&env, [subscription])
+    );
+}
+
+#[test]
+fn test_subscription_pause_resume_and_cancel() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, _merchant_keys) = setup_signed_payment_test(&env);
+    let token_client = token::Client::new(&env, &token);
+    let day = 24 * 60 * 60;
+
+    let plan_id = client.create_subscription_plan(&merchant, &token, &100, &(30 * day), &0, &0);
+    let subscription_id = client.subscribe(&payer, &plan_id);
+    let expiration_ledger = env.ledger().sequence() + 1000;
+    token_client.approve(&payer, &client.address, &1000, &expiration_ledger);
+    client.charge_due(&subscription_id);
+
+    // Paused subscriptions aren't charged, and missed periods are skipped on resume
+    client.pause_subscription(&payer, &subscription_id);
+    env.ledger().with_mut(|li| li.timestamp += 90 * day);
+    let result = client.try_charge_due(&subscription_id);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidStatus)));
+    client.resume_subscription(&payer, &subscription_id);
+    client.charge_due(&subscription_id);
+    let result = client.try_charge_due(&subscription_id);
+    assert_eq!(result, Err(Ok(PaymentError::NotDue)));
+    assert_eq!(token_client.balance(&merchant), 200);
+
+    // Charges stop when the payer withdraws their allowance
+    token_client.approve(&payer, &client.address, &0, &expiration_ledger);
+    env.ledger().with_mut(|li| li.timestamp += 30 * day);
+    let result = client.try_charge_due(&subscription_id);
+    assert_eq!(result, Err(Ok(PaymentError::InsufficientBalance)));
+
+    // Only the subscription's parties can cancel it
+    let outsider = Address::generate(&env);
+    let result = client.try_cancel_subscription(&outsider, &subscription_id);
+    assert_eq!(result, Err(Ok(PaymentError::NotAuthorized)));
+    client.cancel_subscription(&merchant, &subscription_id);
+    assert_eq!(
+        client.get_subscription(&subscription_id).status,
+        SubscriptionStatus::Cancelled
+    );
+
+    // Deactivated plans take no new subscribers
+    client.set_subscription_plan_active(&merchant, &plan_id, &false);
+    let result = client.try_subscribe(&payer, &plan_id);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidStatus)));
+}
+
+fn invoice_terms(env: &Env, token: &Address, amount: i128, payer: Option<Address>) -> InvoiceTerms {
+    InvoiceTerms {
+        token: token.clone(),
+        amount,
+        due_date: env.ledger().timestamp() + 1000,
+        payer,
+        memo: String::from_str(env, "Consulting, March"),
+        line_items_hash: BytesN::from_array(env, &[7; 32]),
+    }
+}
+
+#[test]
+fn test_invoice_partial_payment() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, _merchant_keys) = setup_signed_payment_test(&env);
+    let token_client = token::Client::new(&env, &token);
+
+    let invoice_id = client.create_invoice(
+        &merchant,
+        &invoice_terms(&env, &token, 500, Some(payer.clone())),
+    );
+
+    // Restricted invoices can only be paid by their payer
+    let other_payer = Address::generate(&env);
+    let result = client.try_pay_invoice(&other_payer, &invoice_id, &500);
+    assert_eq!(result, Err(Ok(PaymentError::NotAuthorized)));
+
+    let record = client.pay_invoice(&payer, &invoice_id, &300);
+    assert_eq!(record.order_id, String::from_str(&env, "INV-1-1"));
+    assert_eq!(record.invoice_id, Some(invoice_id));
+    let invoice = client.get_invoice(&invoice_id);
+    assert_eq!(invoice.status, InvoiceStatus::PartiallyPaid);
+    assert_eq!(invoice.amount_paid, 300);
+
+    // Paying more than is still owed is rejected
+    let result = client.try_pay_invoice(&payer, &invoice_id, &201);
+    assert_eq!(result, Err(Ok(PaymentError::ExceedsOriginalAmount)));
+
+    let record = client.pay_invoice(&payer, &invoice_id, &200);
+    assert_eq!(record.order_id, String::from_str(&env, "INV-1-2"));
+    assert_eq!(client.get_invoice(&invoice_id).status, InvoiceStatus::Paid);
+    assert_eq!(token_client.balance(&merchant), 500);
+
+    let result = client.try_pay_invoice(&payer, &invoice_id, &1);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidStatus)));
+}
+
+#[test]
+fn test_invoice_cancellation_and_expiry() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, _merchant_keys) = setup_signed_payment_test(&env);
+
+    let mut terms = invoice_terms(&env, &token, 500, None);
+    terms.due_date = env.ledger().timestamp();
+    let result = client.try_create_invoice(&merchant, &terms);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidDateRange)));
+
+    // Only the issuing merchant can cancel
+    let invoice_id = client.create_invoice(&merchant, &invoice_terms(&env, &token, 500, None));
+    let result = client.try_cancel_invoice(&payer, &invoice_id);
+    assert_eq!(result, Err(Ok(PaymentError::NotAuthorized)));
+    client.cancel_invoice(&merchant, &invoice_id);
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Cancelled
+    );
+    let result = client.try_pay_invoice(&payer, &invoice_id, &500);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidStatus)));
+
+    // Unpaid invoices expire at their due date
+    let invoice_id = client.create_invoice(&merchant, &invoice_terms(&env, &token, 500, None));
+    env.ledger().with_mut(|li| li.timestamp += 1001);
+    assert_eq!(
+        client.get_invoice(&invoice_id).status,
+        InvoiceStatus::Expired
+    );
+    let result = client.try_pay_invoice(&payer, &invoice_id, &500);
+    assert_eq!(result, Err(Ok(PaymentError::OrderExpired)));
+}
+
+#[test]
+fn test_authorize_and_partial_captures() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, _merchant_keys) = setup_signed_payment_test(&env);
+    let token_client = token::Client::new(&env, &token);
+    client.set_fee_schedule(&admin, &FeeSchedule::from_rate(500));
+
+    let expires_at = env.ledger().timestamp() + 1000;
+    let authorization_id = client.authorize_payment(&payer, &merchant, &token, &500, &expires_at);
+    assert_eq!(token_client.balance(&payer), 500);
+    assert_eq!(token_client.balance(&client.address), 500);
+
+    // Captures go through the normal fee logic
+    let record = client.capture_payment(&merchant, &authorization_id, &200, &false);
+    assert_eq!(record.order_id, String::from_str(&env, "AUTH-1-1"));
+    assert_eq!(
+        (record.gross_amount, record.fee_amount, record.net_amount),
+        (200, 10, 190)
+    );
+    assert_eq!(token_client.balance(&merchant), 190);
+    assert_eq!(token_client.balance(&admin), 10);
+
+    let result = client.try_capture_payment(&merchant, &authorization_id, &301, &false);
+    assert_eq!(result, Err(Ok(PaymentError::ExceedsOriginalAmount)));
+
+    // A final capture voids the remainder back to the payer
+    let record = client.capture_payment(&merchant, &authorization_id, &100, &true);
+    assert_eq!(record.order_id, String::from_str(&env, "AUTH-1-2"));
+    let authorization = client.get_authorization(&authorization_id);
+    assert_eq!(authorization.status, AuthorizationStatus::Captured);
+    assert_eq!(authorization.captured_amount, 300);
+    assert_eq!(token_client.balance(&payer), 700);
+    assert_eq!(token_client.balance(&client.address), 0);
+
+    let result = client.try_capture_payment(&merchant, &authorization_id, &100, &false);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidStatus)));
+}
+
+#[test]
+fn test_authorization_void_and_expiry() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, _merchant_keys) = setup_signed_payment_test(&env);
+    let token_client = token::Client::new(&env, &token);
+    let expires_at = env.ledger().timestamp() + 1000;
+
+    let authorization_id = client.authorize_payment(&payer, &merchant, &token, &300, &expires_at);
+    let other_merchant = Address::generate(&env);
+    let result = client.try_capture_payment(&other_merchant, &authorization_id, &100, &false);
+    assert_eq!(result, Err(Ok(PaymentError::NotAuthorized)));
+
+    // Anyone can release a hold, but only once it has lapsed
+    let result = client.try_release_expired_authorization(&authorization_id);
+    assert_eq!(result, Err(Ok(PaymentError::NotDue)));
+    env.ledger().with_mut(|li| li.timestamp += 1001);
+    let result = client.try_capture_payment(&merchant, &authorization_id, &100, &false);
+    assert_eq!(result, Err(Ok(PaymentError::PaymentExpired)));
+    client.release_expired_authorization(&authorization_id);
+    assert_eq!(
+        client.get_authorization(&authorization_id).status,
+        AuthorizationStatus::Expired
+    );
+    assert_eq!(token_client.balance(&payer), 1000);
+
+    // The merchant can void a hold at any time
+    let expires_at = env.ledger().timestamp() + 1000;
+    let authorization_id = client.authorize_payment(&payer, &merchant, &token, &300, &expires_at);
+    client.void_authorization(&merchant, &authorization_id);
+    assert_eq!(
+        client.get_authorization(&authorization_id).status,
+        AuthorizationStatus::Voided
+    );
+    assert_eq!(token_client.balance(&payer), 1000);
+    assert_eq!(token_client.balance(&client.address), 0);
+}
+
+#[test]
+fn test_invalid_fee_schedule() {
+    let env = Env::default();
+    let (client, admin, merchant, _token, _payer, _merchant_keys) = setup_signed_payment_test(&env);
+
+    let mut schedule = FeeSchedule::from_rate(100);
+    schedule.min_fee = 50;
+    schedule.max_fee = Some(10);
+    let result = client.try_set_fee_override(
+        &admin,
+        &FeeScope::Merchant(merchant.clone()),
+        &Some(schedule),
+    );
+    assert_eq!(result, Err(Ok(PaymentError::InvalidFeeSchedule)));
+
+    let result = client.try_set_fee_schedule(&admin, &FeeSchedule::from_rate(1_001));
+    assert_eq!(result, Err(Ok(PaymentError::InvalidFeeRate)));
+
+    // Only the admin can set overrides
+    let result = client.try_set_fee_override(
+        &merchant,
+        &FeeScope::Merchant(merchant.clone()),
+        &Some(FeeSchedule::from_rate(0)),
+    );
+    assert_eq!(result, Err(Ok(PaymentError::NotAuthorized)));
 }
 
 #[test]
@@ -126,7 +982,8 @@ fn test_payment_with_fees() {
 
     // Setup merchant
     let merchant = Address::generate(&env);
-    let merchant_public = BytesN::from_array(&env, &[1u8; 32]);
+    let merchant_keys = MerchantKeypair::generate(&env);
+    let merchant_public = merchant_keys.public_key(&env);
 
     // Setup admin and fee collector
     let admin = Address::generate(&env);
@@ -153,7 +1010,7 @@ fn test_payment_with_fees() {
     env.mock_all_auths();
     client.set_admin(&admin);
     env.mock_all_auths();
-    client.set_fee(&5, &fee_collector, &token);
+    client.set_fee(&admin, &500, &fee_collector, &token);
 
     // Create payment order
     let order = PaymentOrder {
@@ -163,11 +1020,18 @@ fn test_payment_with_fees() {
         nonce: 12345u32,
         expiration: (env.ledger().timestamp() + 1000) as u32,
         fee_amount: 0, // Initial fee amount, will be calculated during processing
+        schema_version: ORDER_SCHEMA_VERSION,
+        splits: Vec::new(&env),
+        input_token: None,
+        max_input_amount: 0,
+        currency: None,
         order_id: String::from_str(&env, "TEST_ORDER_1"),
     };
 
     // Process payment
-    let signature = BytesN::from_array(&env, &[2u8; 64]);
+    env.mock_all_auths();
+    client.register_signing_key(&merchant, &merchant_public, &None, &None);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
     env.mock_all_auths();
     client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
 
@@ -244,7 +1108,8 @@ fn test_successful_payment_with_signature() {
 
     // Setup merchant with keys
     let merchant = Address::generate(&env);
-    let merchant_public = BytesN::from_array(&env, &[1u8; 32]);
+    let merchant_keys = MerchantKeypair::generate(&env);
+    let merchant_public = merchant_keys.public_key(&env);
 
     // Setup admin and fee collector
     let admin = Address::generate(&env);
@@ -266,7 +1131,7 @@ fn test_successful_payment_with_signature() {
     env.mock_all_auths();
     client.set_admin(&admin);
     env.mock_all_auths();
-    client.set_fee(&5, &fee_collector, &token); // 5% fee
+    client.set_fee(&admin, &500, &fee_collector, &token); // 500 bps = 5% fee
 
     // Create payment order
     let order = PaymentOrder {
@@ -277,19 +1142,23 @@ fn test_successful_payment_with_signature() {
         expiration: (env.ledger().timestamp() + 1000) as u32,
         order_id: String::from_str(&env, "TEST_ORDER_1"),
         fee_amount: 0, // Will be calculated during processing
+        schema_version: ORDER_SCHEMA_VERSION,
+        splits: Vec::new(&env),
+        input_token: None,
+        max_input_amount: 0,
+        currency: None,
     };
 
     // Setup token balances
     env.mock_all_auths();
     token_admin_client.mint(&payer, &amount);
 
-    // Use any 64-byte array for signature
-    let signature = BytesN::from_array(&env, &[2u8; 64]);
-
     // Mock all auths for the payment including fee collector
     env.mock_all_auths();
+    client.register_signing_key(&merchant, &merchant_public, &None, &None);
 
     // Process payment
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
     client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
 
     // Verify balances
@@ -323,7 +1192,7 @@ fn test_successful_refund_flow() {
     env.mock_all_auths();
     client.set_admin(&admin);
     env.mock_all_auths();
-    client.set_fee(&0, &admin, &token); // 0% fee for this test
+    client.set_fee(&admin, &0, &admin, &token); // 0% fee for this test
 
     // Mint tokens to payer and pay
     let amount = 200_i128;
@@ -336,10 +1205,18 @@ fn test_successful_refund_flow() {
         expiration: (env.ledger().timestamp() + 1000) as u32,
         order_id: String::from_str(&env, "ORDER_1"),
         fee_amount: 0,
+        schema_version: ORDER_SCHEMA_VERSION,
+        splits: Vec::new(&env),
+        input_token: None,
+        max_input_amount: 0,
+        currency: None,
     };
-    let signature = BytesN::from_array(&env, &[7u8; 64]);
-    let merchant_public = BytesN::from_array(&env, &[5u8; 32]);
+    let merchant_keys = MerchantKeypair::generate(&env);
+    let merchant_public = merchant_keys.public_key(&env);
 
+    env.mock_all_auths();
+    client.register_signing_key(&merchant, &merchant_public, &None, &None);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
     env.mock_all_auths();
     client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
 
@@ -354,6 +1231,7 @@ fn test_successful_refund_flow() {
     client.initiate_refund(
         &merchant,
         &refund_id,
+        &merchant,
         &order.order_id,
         &refund_amount,
         &String::from_str(&env, "Customer request"),
@@ -399,7 +1277,7 @@ fn test_over_refund_prevented() {
     env.mock_all_auths();
     client.set_admin(&admin);
     env.mock_all_auths();
-    client.set_fee(&0, &admin, &token); // 0% fee for this test
+    client.set_fee(&admin, &0, &admin, &token); // 0% fee for this test
 
     let amount = 100_i128;
     token_admin.mint(&payer, &amount);
@@ -411,10 +1289,18 @@ fn test_over_refund_prevented() {
         expiration: (env.ledger().timestamp() + 1000) as u32,
         order_id: String::from_str(&env, "ORDER_2"),
         fee_amount: 0,
+        schema_version: ORDER_SCHEMA_VERSION,
+        splits: Vec::new(&env),
+        input_token: None,
+        max_input_amount: 0,
+        currency: None,
     };
-    let signature = BytesN::from_array(&env, &[9u8; 64]);
-    let merchant_public = BytesN::from_array(&env, &[6u8; 32]);
+    let merchant_keys = MerchantKeypair::generate(&env);
+    let merchant_public = merchant_keys.public_key(&env);
 
+    env.mock_all_auths();
+    client.register_signing_key(&merchant, &merchant_public, &None, &None);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
     env.mock_all_auths();
     client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
 
@@ -425,6 +1311,7 @@ fn test_over_refund_prevented() {
     client.initiate_refund(
         &payer,
         &refund_id,
+        &merchant,
         &order.order_id,
         &refund_amount,
         &String::from_str(&env, "Over refund"),
@@ -452,7 +1339,7 @@ fn test_admin_can_approve_and_execute_refund() {
     env.mock_all_auths();
     client.set_admin(&admin);
     env.mock_all_auths();
-    client.set_fee(&0, &admin, &token); // 0% fee for this test
+    client.set_fee(&admin, &0, &admin, &token); // 0% fee for this test
 
     // Mint tokens and make payment
     let amount = 120_i128;
@@ -465,10 +1352,18 @@ fn test_admin_can_approve_and_execute_refund() {
         expiration: (env.ledger().timestamp() + 1000) as u32,
         order_id: String::from_str(&env, "ORDER_3"),
         fee_amount: 0,
+        schema_version: ORDER_SCHEMA_VERSION,
+        splits: Vec::new(&env),
+        input_token: None,
+        max_input_amount: 0,
+        currency: None,
     };
-    let signature = BytesN::from_array(&env, &[10u8; 64]);
-    let merchant_public = BytesN::from_array(&env, &[7u8; 32]);
+    let merchant_keys = MerchantKeypair::generate(&env);
+    let merchant_public = merchant_keys.public_key(&env);
 
+    env.mock_all_auths();
+    client.register_signing_key(&merchant, &merchant_public, &None, &None);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
     env.mock_all_auths();
     client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
 
@@ -479,6 +1374,7 @@ fn test_admin_can_approve_and_execute_refund() {
     client.initiate_refund(
         &payer,
         &refund_id,
+        &merchant,
         &order.order_id,
         &refund_amount,
         &String::from_str(&env, "Dispute"),
@@ -517,7 +1413,7 @@ fn test_unauthorized_cannot_approve_refund() {
     env.mock_all_auths();
     client.set_admin(&admin);
     env.mock_all_auths();
-    client.set_fee(&0, &admin, &token); // 0% fee for this test
+    client.set_fee(&admin, &0, &admin, &token); // 0% fee for this test
 
     let amount = 80_i128;
     token_admin.mint(&payer, &amount);
@@ -529,10 +1425,18 @@ fn test_unauthorized_cannot_approve_refund() {
         expiration: (env.ledger().timestamp() + 1000) as u32,
         order_id: String::from_str(&env, "ORDER_4"),
         fee_amount: 0,
+        schema_version: ORDER_SCHEMA_VERSION,
+        splits: Vec::new(&env),
+        input_token: None,
+        max_input_amount: 0,
+        currency: None,
     };
-    let signature = BytesN::from_array(&env, &[11u8; 64]);
-    let merchant_public = BytesN::from_array(&env, &[8u8; 32]);
+    let merchant_keys = MerchantKeypair::generate(&env);
+    let merchant_public = merchant_keys.public_key(&env);
 
+    env.mock_all_auths();
+    client.register_signing_key(&merchant, &merchant_public, &None, &None);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
     env.mock_all_auths();
     client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
 
@@ -541,6 +1445,7 @@ fn test_unauthorized_cannot_approve_refund() {
     client.initiate_refund(
         &payer,
         &refund_id,
+        &merchant,
         &order.order_id,
         &40_i128,
         &String::from_str(&env, "Test"),
@@ -560,14 +1465,8 @@ fn test_expired_order() {
 
     // Setup merchant with keys
     let merchant = Address::generate(&env);
-    let merchant_public = BytesN::from_array(
-        &env,
-        &[
-            0xd7, 0x5a, 0x98, 0x01, 0x82, 0xb1, 0x0a, 0xb7, 0xd5, 0x4b, 0xfe, 0xd3, 0xc9, 0x64,
-            0x07, 0x3a, 0x0e, 0xe1, 0x72, 0xf3, 0xda, 0xa6, 0x23, 0x25, 0xaf, 0x02, 0x1a, 0x68,
-            0xf7, 0x07, 0x51, 0x1a,
-        ],
-    );
+    let merchant_keys = MerchantKeypair::generate(&env);
+    let merchant_public = merchant_keys.public_key(&env);
 
     // Setup token
     let admin = Address::generate(&env);
@@ -583,10 +1482,10 @@ fn test_expired_order() {
     let expired_time = (current_time - 1000) as u32; // Set expiration in the past
     let order = create_payment_order(&env, &merchant, 100, &token, expired_time);
 
-    // Create test signature
-    let signature = BytesN::from_array(&env, &[3u8; 64]); // Test signature
+    client.register_signing_key(&merchant, &merchant_public, &None, &None);
 
     // Should fail due to expired order
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
     client.process_payment_with_signature(
         &Address::generate(&env),
         &order,
@@ -604,14 +1503,8 @@ fn test_duplicate_nonce() {
 
     // Setup merchant with keys
     let merchant = Address::generate(&env);
-    let merchant_public = BytesN::from_array(
-        &env,
-        &[
-            0xd7, 0x5a, 0x98, 0x01, 0x82, 0xb1, 0x0a, 0xb7, 0xd5, 0x4b, 0xfe, 0xd3, 0xc9, 0x64,
-            0x07, 0x3a, 0x0e, 0xe1, 0x72, 0xf3, 0xda, 0xa6, 0x23, 0x25, 0xaf, 0x02, 0x1a, 0x68,
-            0xf7, 0x07, 0x51, 0x1a,
-        ],
-    );
+    let merchant_keys = MerchantKeypair::generate(&env);
+    let merchant_public = merchant_keys.public_key(&env);
 
     // Setup token
     let admin = Address::generate(&env);
@@ -629,14 +1522,14 @@ fn test_duplicate_nonce() {
     // Create order
     let expiration = (env.ledger().timestamp() + 1000) as u32;
     let order = create_payment_order(&env, &merchant, amount as i64, &token, expiration);
-    // Create test signature
-    let signature = BytesN::from_array(&env, &[3u8; 64]); // Test signature
-
     // Setup token balances
     token_admin.mint(&payer, &(amount * 2));
 
     // First payment should succeed
     env.mock_all_auths();
+    client.register_signing_key(&merchant, &merchant_public, &None, &None);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    env.mock_all_auths();
     client.process_payment_with_signature(&payer, &order.clone(), &signature, &merchant_public);
 
     // Second payment with same nonce should fail
@@ -652,14 +1545,8 @@ fn test_unsupported_token() {
 
     // Setup merchant with keys
     let merchant = Address::generate(&env);
-    let merchant_public = BytesN::from_array(
-        &env,
-        &[
-            0xd7, 0x5a, 0x98, 0x01, 0x82, 0xb1, 0x0a, 0xb7, 0xd5, 0x4b, 0xfe, 0xd3, 0xc9, 0x64,
-            0x07, 0x3a, 0x0e, 0xe1, 0x72, 0xf3, 0xda, 0xa6, 0x23, 0x25, 0xaf, 0x02, 0x1a, 0x68,
-            0xf7, 0x07, 0x51, 0x1a,
-        ],
-    );
+    let merchant_keys = MerchantKeypair::generate(&env);
+    let merchant_public = merchant_keys.public_key(&env);
 
     // Setup token (but don't add it as supported)
     let admin = Address::generate(&env);
@@ -672,10 +1559,10 @@ fn test_unsupported_token() {
     let expiration = (env.ledger().timestamp() + 1000) as u32;
     let order = create_payment_order(&env, &merchant, 100, &token, expiration);
 
-    // Create test signature
-    let signature = BytesN::from_array(&env, &[3u8; 64]); // Test signature
+    client.register_signing_key(&merchant, &merchant_public, &None, &None);
 
     // Should fail due to unsupported token
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
     client.process_payment_with_signature(
         &Address::generate(&env),
         &order,
@@ -713,6 +1600,10 @@ fn test_batch_register_merchants() {
     for merchant in merchants.iter() {
         let merchant_info = client.get_merchant_info(&merchant);
         assert!(merchant_info.active);
+        assert_eq!(
+            merchant_info.max_transaction_limit,
+            DEFAULT_TRANSACTION_LIMIT
+        );
     }
 }
 
@@ -782,6 +1673,11 @@ fn test_batch_process_payments() {
                 expiration: (env.ledger().timestamp() + 1000) as u32,
                 order_id: String::from_str(&env, "ORDER_1"),
                 fee_amount: 0,
+                schema_version: ORDER_SCHEMA_VERSION,
+                splits: Vec::new(&env),
+                input_token: None,
+                max_input_amount: 0,
+                currency: None,
             },
             PaymentOrder {
                 merchant_address: merchant.clone(),
@@ -791,6 +1687,11 @@ fn test_batch_process_payments() {
                 expiration: (env.ledger().timestamp() + 1000) as u32,
                 order_id: String::from_str(&env, "ORDER_2"),
                 fee_amount: 0,
+                schema_version: ORDER_SCHEMA_VERSION,
+                splits: Vec::new(&env),
+                input_token: None,
+                max_input_amount: 0,
+                currency: None,
             },
             PaymentOrder {
                 merchant_address: merchant.clone(),
@@ -800,37 +1701,55 @@ fn test_batch_process_payments() {
                 expiration: (env.ledger().timestamp() + 1000) as u32,
                 order_id: String::from_str(&env, "ORDER_3"),
                 fee_amount: 0,
+                schema_version: ORDER_SCHEMA_VERSION,
+                splits: Vec::new(&env),
+                input_token: None,
+                max_input_amount: 0,
+                currency: None,
             },
         ],
     );
 
-    let signatures = Vec::from_array(
-        &env,
-        [
-            BytesN::from_array(&env, &[4u8; 64]),
-            BytesN::from_array(&env, &[5u8; 64]),
-            BytesN::from_array(&env, &[6u8; 64]),
-        ],
-    );
-    let merchant_public = BytesN::from_array(&env, &[7u8; 32]);
+    let merchant_keys = MerchantKeypair::generate(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+    let mut signatures = Vec::new(&env);
+    for order in orders.iter() {
+        signatures.push_back(merchant_keys.sign_order(&env, &client.address, &order));
+    }
+    client.register_signing_key(&merchant, &merchant_public, &None, &None);
+
+    // 5% fee, collected by the same pipeline as single payments
+    let fee_collector = Address::generate(&env);
+    client.set_admin(&admin);
+    client.set_fee(&admin, &500, &fee_collector, &token);
 
     let batch = BatchPayment {
         payer: payer.clone(),
         orders: orders.clone(),
-        signatures,
-        merchant_public_key: merchant_public,
+        signatures: BatchSignatures::PerOrder(signatures),
+        merchant_public_keys: Map::from_array(&env, [(merchant.clone(), merchant_public)]),
+        mode: BatchMode::Atomic,
     };
 
     // Setup token balances
     token_admin.mint(&payer, &600);
 
     env.mock_all_auths();
-    client.batch_process_payments(&batch);
+    let results = client.batch_process_payments(&batch);
+    assert_eq!(results.len(), 3);
+    assert!(results.iter().all(|r| r.success));
 
     // Verify balances
-    assert_eq!(token_client.balance(&merchant), 600);
+    assert_eq!(token_client.balance(&merchant), 570);
+    assert_eq!(token_client.balance(&fee_collector), 30);
     assert_eq!(token_client.balance(&payer), 0);
 
+    // Verify payment records and indices were written
+    let history = client.get_merchant_payment_history(&merchant, &None, &10, &None, &None, &None);
+    assert_eq!(history.total_count, 3);
+    let record = client.get_payment_by_id(&payer, &merchant, &String::from_str(&env, "ORDER_2"));
+    assert_eq!(record.amount, 200);
+
     // Verify nonces were marked as used
     for order in orders.iter() {
         let tracker = client.get_nonce_tracker(&merchant);
@@ -855,6 +1774,11 @@ fn test_gas_estimation() {
         expiration: (env.ledger().timestamp() + 1000) as u32,
         order_id: String::from_str(&env, "TEST_ORDER"),
         fee_amount: 0,
+        schema_version: ORDER_SCHEMA_VERSION,
+        splits: Vec::new(&env),
+        input_token: None,
+        max_input_amount: 0,
+        currency: None,
     };
 
     // Test payment gas estimation
@@ -982,15 +1906,17 @@ fn test_nonce_bitmap_optimization() {
     env.mock_all_auths();
     client.set_admin(&admin);
     env.mock_all_auths();
-    client.set_fee(&0, &admin, &token); // 0% fee for this test
+    client.set_fee(&admin, &0, &admin, &token); // 0% fee for this test
 
     // Setup token balance
     token_admin.mint(&payer, &1000);
 
-    let signature = BytesN::from_array(&env, &[6u8; 64]);
-    let merchant_public = BytesN::from_array(&env, &[7u8; 32]);
+    let merchant_keys = MerchantKeypair::generate(&env);
+    let merchant_public = merchant_keys.public_key(&env);
 
     // Process multiple payments with different nonces
+    env.mock_all_auths();
+    client.register_signing_key(&merchant, &merchant_public, &None, &None);
     for i in 1..=10 {
         let order = PaymentOrder {
             merchant_address: merchant.clone(),
@@ -998,10 +1924,16 @@ fn test_nonce_bitmap_optimization() {
             token: token.clone(),
             nonce: i,
             expiration: (env.ledger().timestamp() + 1000) as u32,
-            order_id: String::from_str(&env, "ORDER_TEST"),
+            order_id: String::from_str(&env, &format!("ORDER_TEST_{}", i)),
             fee_amount: 0,
+            schema_version: ORDER_SCHEMA_VERSION,
+            splits: Vec::new(&env),
+            input_token: None,
+            max_input_amount: 0,
+            currency: None,
         };
 
+        let signature = merchant_keys.sign_order(&env, &client.address, &order);
         env.mock_all_auths();
         client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
     }
@@ -1725,13 +2657,15 @@ fn test_get_merchant_payment_history() {
     register_test_merchant(&client, &env, &merchant);
     client.add_supported_token(&merchant, &token);
     client.set_admin(&admin);
-    client.set_fee(&0, &admin, &token);
+    client.set_fee(&admin, &0, &admin, &token);
 
     // Create multiple payments
     token_admin.mint(&payer, &10000);
-    let signature = BytesN::from_array(&env, &[1u8; 64]);
-    let merchant_public = BytesN::from_array(&env, &[2u8; 32]);
+    let merchant_keys = MerchantKeypair::generate(&env);
+    let merchant_public = merchant_keys.public_key(&env);
 
+    env.mock_all_auths();
+    client.register_signing_key(&merchant, &merchant_public, &None, &None);
     for i in 1..=5 {
         let order = PaymentOrder {
             merchant_address: merchant.clone(),
@@ -1741,7 +2675,13 @@ fn test_get_merchant_payment_history() {
             expiration: (env.ledger().timestamp() + 1000) as u32,
             order_id: String::from_str(&env, &format!("ORDER_{}", i)),
             fee_amount: 0,
+            schema_version: ORDER_SCHEMA_VERSION,
+            splits: Vec::new(&env),
+            input_token: None,
+            max_input_amount: 0,
+            currency: None,
         };
+        let signature = merchant_keys.sign_order(&env, &client.address, &order);
         env.mock_all_auths();
         client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
     }
@@ -1777,13 +2717,15 @@ fn test_get_payer_payment_history() {
     register_test_merchant(&client, &env, &merchant);
     client.add_supported_token(&merchant, &token);
     client.set_admin(&admin);
-    client.set_fee(&0, &admin, &token);
+    client.set_fee(&admin, &0, &admin, &token);
 
     // Create payments
     token_admin.mint(&payer, &5000);
-    let signature = BytesN::from_array(&env, &[3u8; 64]);
-    let merchant_public = BytesN::from_array(&env, &[4u8; 32]);
+    let merchant_keys = MerchantKeypair::generate(&env);
+    let merchant_public = merchant_keys.public_key(&env);
 
+    env.mock_all_auths();
+    client.register_signing_key(&merchant, &merchant_public, &None, &None);
     for i in 1..=3 {
         let order = PaymentOrder {
             merchant_address: merchant.clone(),
@@ -1793,7 +2735,13 @@ fn test_get_payer_payment_history() {
             expiration: (env.ledger().timestamp() + 1000) as u32,
             order_id: String::from_str(&env, &format!("PAYER_ORDER_{}", i)),
             fee_amount: 0,
+            schema_version: ORDER_SCHEMA_VERSION,
+            splits: Vec::new(&env),
+            input_token: None,
+            max_input_amount: 0,
+            currency: None,
         };
+        let signature = merchant_keys.sign_order(&env, &client.address, &order);
         env.mock_all_auths();
         client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
     }
@@ -1829,7 +2777,7 @@ fn test_get_payment_by_id() {
     register_test_merchant(&client, &env, &merchant);
     client.add_supported_token(&merchant, &token);
     client.set_admin(&admin);
-    client.set_fee(&0, &admin, &token);
+    client.set_fee(&admin, &0, &admin, &token);
 
     // Create payment
     token_admin.mint(&payer, &1000);
@@ -1841,22 +2789,30 @@ fn test_get_payment_by_id() {
         expiration: (env.ledger().timestamp() + 1000) as u32,
         order_id: String::from_str(&env, "TEST_ORDER_1"),
         fee_amount: 0,
+        schema_version: ORDER_SCHEMA_VERSION,
+        splits: Vec::new(&env),
+        input_token: None,
+        max_input_amount: 0,
+        currency: None,
     };
-    let signature = BytesN::from_array(&env, &[5u8; 64]);
-    let merchant_public = BytesN::from_array(&env, &[6u8; 32]);
+    let merchant_keys = MerchantKeypair::generate(&env);
+    let merchant_public = merchant_keys.public_key(&env);
 
+    env.mock_all_auths();
+    client.register_signing_key(&merchant, &merchant_public, &None, &None);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
     env.mock_all_auths();
     client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
 
     // Query by ID as payer
     env.mock_all_auths();
-    let payment = client.get_payment_by_id(&payer, &order.order_id);
+    let payment = client.get_payment_by_id(&payer, &merchant, &order.order_id);
     assert_eq!(payment.order_id, order.order_id);
     assert_eq!(payment.amount, 1000);
 
     // Query by ID as merchant
     env.mock_all_auths();
-    let payment2 = client.get_payment_by_id(&merchant, &order.order_id);
+    let payment2 = client.get_payment_by_id(&merchant, &merchant, &order.order_id);
     assert_eq!(payment2.order_id, order.order_id);
 }
 
@@ -1876,13 +2832,15 @@ fn test_get_global_payment_stats() {
     register_test_merchant(&client, &env, &merchant);
     client.add_supported_token(&merchant, &token);
     client.set_admin(&admin);
-    client.set_fee(&0, &admin, &token);
+    client.set_fee(&admin, &0, &admin, &token);
 
     // Create payments
     token_admin.mint(&payer, &10000);
-    let signature = BytesN::from_array(&env, &[7u8; 64]);
-    let merchant_public = BytesN::from_array(&env, &[8u8; 32]);
+    let merchant_keys = MerchantKeypair::generate(&env);
+    let merchant_public = merchant_keys.public_key(&env);
 
+    env.mock_all_auths();
+    client.register_signing_key(&merchant, &merchant_public, &None, &None);
     for i in 1..=5 {
         let order = PaymentOrder {
             merchant_address: merchant.clone(),
@@ -1892,7 +2850,13 @@ fn test_get_global_payment_stats() {
             expiration: (env.ledger().timestamp() + 1000) as u32,
             order_id: String::from_str(&env, &format!("STATS_ORDER_{}", i)),
             fee_amount: 0,
+            schema_version: ORDER_SCHEMA_VERSION,
+            splits: Vec::new(&env),
+            input_token: None,
+            max_input_amount: 0,
+            currency: None,
         };
+        let signature = merchant_keys.sign_order(&env, &client.address, &order);
         env.mock_all_auths();
         client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
     }
@@ -1922,7 +2886,7 @@ fn test_update_payment_status() {
     register_test_merchant(&client, &env, &merchant);
     client.add_supported_token(&merchant, &token);
     client.set_admin(&admin);
-    client.set_fee(&0, &admin, &token);
+    client.set_fee(&admin, &0, &admin, &token);
 
     // Create payment
     token_admin.mint(&payer, &1000);
@@ -1934,20 +2898,28 @@ fn test_update_payment_status() {
         expiration: (env.ledger().timestamp() + 1000) as u32,
         order_id: String::from_str(&env, "UPDATE_ORDER_1"),
         fee_amount: 0,
+        schema_version: ORDER_SCHEMA_VERSION,
+        splits: Vec::new(&env),
+        input_token: None,
+        max_input_amount: 0,
+        currency: None,
     };
-    let signature = BytesN::from_array(&env, &[9u8; 64]);
-    let merchant_public = BytesN::from_array(&env, &[10u8; 32]);
+    let merchant_keys = MerchantKeypair::generate(&env);
+    let merchant_public = merchant_keys.public_key(&env);
 
+    env.mock_all_auths();
+    client.register_signing_key(&merchant, &merchant_public, &None, &None);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
     env.mock_all_auths();
     client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
 
     // Update payment status (refunded amount)
     env.mock_all_auths();
-    client.update_payment_status(&merchant, &order.order_id, &500);
+    client.update_payment_status(&merchant, &merchant, &order.order_id, &500);
 
     // Verify update
     env.mock_all_auths();
-    let payment = client.get_payment_by_id(&merchant, &order.order_id);
+    let payment = client.get_payment_by_id(&merchant, &merchant, &order.order_id);
     assert_eq!(payment.refunded_amount, 500);
 }
 
@@ -1967,7 +2939,7 @@ fn test_archive_payment_record() {
     register_test_merchant(&client, &env, &merchant);
     client.add_supported_token(&merchant, &token);
     client.set_admin(&admin);
-    client.set_fee(&0, &admin, &token);
+    client.set_fee(&admin, &0, &admin, &token);
 
     // Create payment
     token_admin.mint(&payer, &1000);
@@ -1979,16 +2951,24 @@ fn test_archive_payment_record() {
         expiration: (env.ledger().timestamp() + 1000) as u32,
         order_id: String::from_str(&env, "ARCHIVE_ORDER_1"),
         fee_amount: 0,
+        schema_version: ORDER_SCHEMA_VERSION,
+        splits: Vec::new(&env),
+        input_token: None,
+        max_input_amount: 0,
+        currency: None,
     };
-    let signature = BytesN::from_array(&env, &[11u8; 64]);
-    let merchant_public = BytesN::from_array(&env, &[12u8; 32]);
+    let merchant_keys = MerchantKeypair::generate(&env);
+    let merchant_public = merchant_keys.public_key(&env);
 
+    env.mock_all_auths();
+    client.register_signing_key(&merchant, &merchant_public, &None, &None);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
     env.mock_all_auths();
     client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
 
     // Archive payment
     env.mock_all_auths();
-    client.archive_payment_record(&admin, &order.order_id);
+    client.archive_payment_record(&admin, &merchant, &order.order_id);
 
     // Verify payment is archived (should not be found in active payments)
     // Note: Archived payments are removed from active storage, so querying will fail
@@ -2032,13 +3012,15 @@ fn test_payment_history_with_filters() {
     register_test_merchant(&client, &env, &merchant);
     client.add_supported_token(&merchant, &token);
     client.set_admin(&admin);
-    client.set_fee(&0, &admin, &token);
+    client.set_fee(&admin, &0, &admin, &token);
 
     // Create payments with different amounts
     token_admin.mint(&payer, &10000);
-    let signature = BytesN::from_array(&env, &[13u8; 64]);
-    let merchant_public = BytesN::from_array(&env, &[14u8; 32]);
+    let merchant_keys = MerchantKeypair::generate(&env);
+    let merchant_public = merchant_keys.public_key(&env);
 
+    env.mock_all_auths();
+    client.register_signing_key(&merchant, &merchant_public, &None, &None);
     for i in 1..=5 {
         let order = PaymentOrder {
             merchant_address: merchant.clone(),
@@ -2048,7 +3030,13 @@ fn test_payment_history_with_filters() {
             expiration: (env.ledger().timestamp() + 1000) as u32,
             order_id: String::from_str(&env, &format!("FILTER_ORDER_{}", i)),
             fee_amount: 0,
+            schema_version: ORDER_SCHEMA_VERSION,
+            splits: Vec::new(&env),
+            input_token: None,
+            max_input_amount: 0,
+            currency: None,
         };
+        let signature = merchant_keys.sign_order(&env, &client.address, &order);
         env.mock_all_auths();
         client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
     }
@@ -2111,3 +3099,2476 @@ fn test_invalid_query_limit() {
         &None, &None, &None,
     );
 }
+
+// Merchant Signing Key Tests
+
+#[test]
+fn test_register_and_get_signing_keys() {
+    let env = Env::default();
+    let contract_id = env.register(PaymentProcessingContract {}, ());
+    let client = PaymentProcessingContractClient::new(&env, &contract_id);
+
+    let merchant = Address::generate(&env);
+    let key1 = BytesN::from_array(&env, &[1u8; 32]);
+    let key2 = BytesN::from_array(&env, &[2u8; 32]);
+
+    env.mock_all_auths();
+    register_test_merchant(&client, &env, &merchant);
+    client.register_signing_key(&merchant, &key1, &None, &None);
+    client.register_signing_key(&merchant, &key2, &Some(100), &Some(200));
+
+    let keys = client.get_signing_keys(&merchant);
+    assert_eq!(keys.len(), 2);
+    assert_eq!(keys.get(0).unwrap().public_key, key1);
+    assert_eq!(keys.get(0).unwrap().valid_until, None);
+    assert_eq!(keys.get(1).unwrap().valid_from, 100);
+    assert_eq!(keys.get(1).unwrap().valid_until, Some(200));
+
+    // Registering the same key twice is rejected
+    let result = client.try_register_signing_key(&merchant, &key1, &None, &None);
+    assert_eq!(result, Err(Ok(PaymentError::SigningKeyAlreadyRegistered)));
+
+    // Validity window must not end before it starts
+    let key3 = BytesN::from_array(&env, &[3u8; 32]);
+    let result = client.try_register_signing_key(&merchant, &key3, &Some(200), &Some(100));
+    assert_eq!(result, Err(Ok(PaymentError::InvalidKeyValidity)));
+}
+
+#[test]
+fn test_payment_with_unregistered_key_rejected() {
+    let env = Env::default();
+    let contract_id = env.register(PaymentProcessingContract {}, ());
+    let client = PaymentProcessingContractClient::new(&env, &contract_id);
+
+    let merchant = Address::generate(&env);
+    let other_merchant = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (token, _token_client, token_admin) = create_token_contract(&env, &admin);
+    let payer = Address::generate(&env);
+
+    env.mock_all_auths();
+    register_test_merchant(&client, &env, &merchant);
+    register_test_merchant(&client, &env, &other_merchant);
+    client.add_supported_token(&merchant, &token);
+    client.set_admin(&admin);
+    client.set_fee(&admin, &0, &admin, &token);
+    token_admin.mint(&payer, &100);
+
+    // The key belongs to a different merchant, so it cannot sign for this one
+    let payer_key = BytesN::from_array(&env, &[9u8; 32]);
+    client.register_signing_key(&other_merchant, &payer_key, &None, &None);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let order = create_payment_order(&env, &merchant, 100, &token, expiration);
+    let signature = BytesN::from_array(&env, &[3u8; 64]);
+
+    let result = client.try_process_payment_with_signature(&payer, &order, &signature, &payer_key);
+    assert_eq!(result, Err(Ok(PaymentError::SigningKeyNotFound)));
+}
+
+#[test]
+fn test_revoked_signing_key_rejected() {
+    let env = Env::default();
+    let contract_id = env.register(PaymentProcessingContract {}, ());
+    let client = PaymentProcessingContractClient::new(&env, &contract_id);
+
+    let merchant = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (token, _token_client, token_admin) = create_token_contract(&env, &admin);
+    let payer = Address::generate(&env);
+    let merchant_keys = MerchantKeypair::generate(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+
+    env.mock_all_auths();
+    register_test_merchant(&client, &env, &merchant);
+    client.add_supported_token(&merchant, &token);
+    client.set_admin(&admin);
+    client.set_fee(&admin, &0, &admin, &token);
+    token_admin.mint(&payer, &100);
+
+    client.register_signing_key(&merchant, &merchant_public, &None, &None);
+    client.revoke_signing_key(&merchant, &merchant_public);
+
+    let keys = client.get_signing_keys(&merchant);
+    assert!(keys.get(0).unwrap().revoked_at.is_some());
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let order = create_payment_order(&env, &merchant, 100, &token, expiration);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+
+    let result =
+        client.try_process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(result, Err(Ok(PaymentError::SigningKeyInactive)));
+
+    // Revoking twice is rejected
+    let result = client.try_revoke_signing_key(&merchant, &merchant_public);
+    assert_eq!(result, Err(Ok(PaymentError::SigningKeyInactive)));
+}
+
+#[test]
+fn test_rotate_signing_key_with_grace_period() {
+    let env = Env::default();
+    let contract_id = env.register(PaymentProcessingContract {}, ());
+    let client = PaymentProcessingContractClient::new(&env, &contract_id);
+
+    let merchant = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (token, token_client, token_admin) = create_token_contract(&env, &admin);
+    let payer = Address::generate(&env);
+    let old_keys = MerchantKeypair::generate(&env);
+    let new_keys = MerchantKeypair::generate(&env);
+    let old_key = old_keys.public_key(&env);
+    let new_key = new_keys.public_key(&env);
+
+    env.mock_all_auths();
+    register_test_merchant(&client, &env, &merchant);
+    client.add_supported_token(&merchant, &token);
+    client.set_admin(&admin);
+    client.set_fee(&admin, &0, &admin, &token);
+    token_admin.mint(&payer, &300);
+
+    client.register_signing_key(&merchant, &old_key, &None, &None);
+    client.rotate_signing_key(&merchant, &old_key, &new_key, &100);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+
+    // Old key still works within the grace period
+    let mut order = create_payment_order(&env, &merchant, 100, &token, expiration);
+    order.nonce = 1;
+    order.order_id = String::from_str(&env, "ROTATE_ORDER_1");
+    let signature = old_keys.sign_order(&env, &client.address, &order);
+    client.process_payment_with_signature(&payer, &order, &signature, &old_key);
+
+    // After the grace period only the new key is accepted
+    env.ledger().with_mut(|li| li.timestamp += 101);
+    order.nonce = 2;
+    order.order_id = String::from_str(&env, "ROTATE_ORDER_2");
+    let signature = old_keys.sign_order(&env, &client.address, &order);
+    let result = client.try_process_payment_with_signature(&payer, &order, &signature, &old_key);
+    assert_eq!(result, Err(Ok(PaymentError::SigningKeyInactive)));
+
+    let signature = new_keys.sign_order(&env, &client.address, &order);
+    client.process_payment_with_signature(&payer, &order, &signature, &new_key);
+    assert_eq!(token_client.balance(&merchant), 200);
+}
+
+#[test]
+fn test_revoked_signing_key_cannot_be_registered_again() {
+    let env = Env::default();
+    let contract_id = env.register(PaymentProcessingContract {}, ());
+    let client = PaymentProcessingContractClient::new(&env, &contract_id);
+
+    let merchant = Address::generate(&env);
+    let revoked_key = BytesN::from_array(&env, &[1u8; 32]);
+    let other_key = BytesN::from_array(&env, &[2u8; 32]);
+    let new_key = BytesN::from_array(&env, &[3u8; 32]);
+
+    env.mock_all_auths();
+    register_test_merchant(&client, &env, &merchant);
+    client.register_signing_key(&merchant, &revoked_key, &None, &None);
+    client.register_signing_key(&merchant, &other_key, &None, &None);
+    client.revoke_signing_key(&merchant, &revoked_key);
+
+    // Rotating prunes the revoked key from the list, but it stays blocked
+    client.rotate_signing_key(&merchant, &other_key, &new_key, &0);
+    let keys = client.get_signing_keys(&merchant);
+    assert!(!keys.iter().any(|key| key.public_key == revoked_key));
+
+    let result = client.try_register_signing_key(&merchant, &revoked_key, &None, &None);
+    assert_eq!(result, Err(Ok(PaymentError::SigningKeyInactive)));
+    let result = client.try_rotate_signing_key(&merchant, &new_key, &revoked_key, &0);
+    assert_eq!(result, Err(Ok(PaymentError::SigningKeyInactive)));
+}
+
+#[test]
+fn test_rotate_signing_key_while_paused() {
+    let env = Env::default();
+    let contract_id = env.register(PaymentProcessingContract {}, ());
+    let client = PaymentProcessingContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let pause_admin = Address::generate(&env);
+    let merchant = Address::generate(&env);
+    let old_key = BytesN::from_array(&env, &[1u8; 32]);
+    let new_key = BytesN::from_array(&env, &[2u8; 32]);
+
+    env.mock_all_auths();
+    client.set_admin(&admin);
+    client.set_pause_admin(&admin, &pause_admin);
+    register_test_merchant(&client, &env, &merchant);
+    client.register_signing_key(&merchant, &old_key, &None, &None);
+    client.pause(&pause_admin);
+
+    let result = client.try_rotate_signing_key(&merchant, &old_key, &new_key, &100);
+    assert_eq!(result, Err(Ok(PaymentError::ContractPaused)));
+
+    // Revocation still works so a compromised key can be disabled
+    client.revoke_signing_key(&merchant, &old_key);
+}
+
+#[test]
+fn test_signing_key_not_yet_valid() {
+    let env = Env::default();
+    let contract_id = env.register(PaymentProcessingContract {}, ());
+    let client = PaymentProcessingContractClient::new(&env, &contract_id);
+
+    let merchant = Address::generate(&env);
+    let admin = Address::generate(&env);
+    let (token, _token_client, token_admin) = create_token_contract(&env, &admin);
+    let payer = Address::generate(&env);
+    let merchant_keys = MerchantKeypair::generate(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+
+    env.mock_all_auths();
+    register_test_merchant(&client, &env, &merchant);
+    client.add_supported_token(&merchant, &token);
+    client.set_admin(&admin);
+    client.set_fee(&admin, &0, &admin, &token);
+    token_admin.mint(&payer, &100);
+
+    let valid_from = env.ledger().timestamp() + 500;
+    client.register_signing_key(&merchant, &merchant_public, &Some(valid_from), &None);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let order = create_payment_order(&env, &merchant, 100, &token, expiration);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+
+    let result =
+        client.try_process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(result, Err(Ok(PaymentError::SigningKeyInactive)));
+
+    env.ledger().with_mut(|li| li.timestamp = valid_from);
+    client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+}
+
+// Order Signature Verification Tests
+
+fn setup_signed_payment_test(
+    env: &Env,
+) -> (
+    PaymentProcessingContractClient<'_>,
+    Address,
+    Address,
+    Address,
+    Address,
+    MerchantKeypair,
+) {
+    let contract_id = env.register(PaymentProcessingContract {}, ());
+    let client = PaymentProcessingContractClient::new(env, &contract_id);
+
+    let merchant = Address::generate(env);
+    let admin = Address::generate(env);
+    let (token, _token_client, token_admin) = create_token_contract(env, &admin);
+    let payer = Address::generate(env);
+    let merchant_keys = MerchantKeypair::generate(env);
+
+    env.mock_all_auths();
+    register_test_merchant(&client, env, &merchant);
+    client.add_supported_token(&merchant, &token);
+    client.set_admin(&admin);
+    client.set_fee(&admin, &0, &admin, &token);
+    client.register_signing_key(&merchant, &merchant_keys.public_key(env), &None, &None);
+    token_admin.mint(&payer, &1000);
+
+    (client, admin, merchant, token, payer, merchant_keys)
+}
+
+/// Second merchant on the same contract and token, with its own registered key
+fn add_signed_merchant(
+    client: &PaymentProcessingContractClient,
+    env: &Env,
+    token: &Address,
+) -> (Address, MerchantKeypair) {
+    let merchant = Address::generate(env);
+    let merchant_keys = MerchantKeypair::generate(env);
+    register_test_merchant(client, env, &merchant);
+    client.add_supported_token(&merchant, token);
+    client.register_signing_key(&merchant, &merchant_keys.public_key(env), &None, &None);
+    (merchant, merchant_keys)
+}
+
+#[test]
+#[should_panic(expected = "Error(Crypto, InvalidInput)")]
+fn test_forged_signature_rejected() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+
+    // Payer signs the order with their own key but presents the merchant's registered key
+    let payer_keys = MerchantKeypair::generate(&env);
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let order = create_payment_order(&env, &merchant, 100, &token, expiration);
+    let signature = payer_keys.sign_order(&env, &client.address, &order);
+
+    client.process_payment_with_signature(
+        &payer,
+        &order,
+        &signature,
+        &merchant_keys.public_key(&env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Crypto, InvalidInput)")]
+fn test_tampered_order_rejected() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let mut order = create_payment_order(&env, &merchant, 500, &token, expiration);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+
+    // Lower the amount after the merchant signed
+    order.amount = 1;
+    client.process_payment_with_signature(
+        &payer,
+        &order,
+        &signature,
+        &merchant_keys.public_key(&env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Crypto, InvalidInput)")]
+fn test_signature_reuse_for_new_nonce_rejected() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let mut order = create_payment_order(&env, &merchant, 100, &token, expiration);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+
+    // Replaying the same signature under a fresh nonce must not verify
+    order.nonce += 1;
+    order.order_id = String::from_str(&env, "TEST_ORDER_2");
+    client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+}
+
+#[test]
+#[should_panic(expected = "Error(Crypto, InvalidInput)")]
+fn test_batch_with_invalid_signature_rejected() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let mut first = create_payment_order(&env, &merchant, 100, &token, expiration);
+    first.nonce = 1;
+    let mut second = create_payment_order(&env, &merchant, 200, &token, expiration);
+    second.nonce = 2;
+    second.order_id = String::from_str(&env, "TEST_ORDER_2");
+
+    // Second signature covers the first order, not the second
+    let signatures = Vec::from_array(
+        &env,
+        [
+            merchant_keys.sign_order(&env, &client.address, &first),
+            merchant_keys.sign_order(&env, &client.address, &first),
+        ],
+    );
+    let batch = BatchPayment {
+        payer,
+        orders: Vec::from_array(&env, [first, second]),
+        signatures: BatchSignatures::PerOrder(signatures),
+        merchant_public_keys: Map::from_array(&env, [(merchant, merchant_keys.public_key(&env))]),
+        // A bad signature aborts the batch even in best-effort mode
+        mode: BatchMode::BestEffort,
+    };
+
+    client.batch_process_payments(&batch);
+}
+
+// Order Signing Format Tests
+
+#[test]
+fn test_legacy_order_format_migration_window() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+
+    // Legacy orders are rejected until the admin opens a migration window
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let mut order = create_payment_order(&env, &merchant, 100, &token, expiration);
+    order.schema_version = LEGACY_ORDER_SCHEMA_VERSION;
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let result =
+        client.try_process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(result, Err(Ok(PaymentError::LegacySignatureExpired)));
+
+    let deadline = env.ledger().timestamp() + 100;
+    client.set_legacy_signature_deadline(&admin, &deadline);
+    assert_eq!(client.get_legacy_signature_deadline(), Some(deadline));
+    client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+
+    // Once the window has passed, legacy orders are rejected again
+    env.ledger().with_mut(|li| li.timestamp = deadline + 1);
+    order.nonce += 1;
+    order.order_id = String::from_str(&env, "TEST_ORDER_2");
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let result =
+        client.try_process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(result, Err(Ok(PaymentError::LegacySignatureExpired)));
+
+    // Versioned orders keep working
+    order.schema_version = ORDER_SCHEMA_VERSION;
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+}
+
+#[test]
+fn test_unsupported_schema_version() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let mut order = create_payment_order(&env, &merchant, 100, &token, expiration);
+    order.schema_version = ORDER_SCHEMA_VERSION + 1;
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+
+    let result = client.try_process_payment_with_signature(
+        &payer,
+        &order,
+        &signature,
+        &merchant_keys.public_key(&env),
+    );
+    assert_eq!(result, Err(Ok(PaymentError::UnsupportedSchemaVersion)));
+}
+
+#[test]
+fn test_split_payment_distributes_net_amount() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let token_client = token::Client::new(&env, &token);
+    client.set_fee_schedule(&admin, &FeeSchedule::from_rate(500));
+    let platform = Address::generate(&env);
+    let affiliate = Address::generate(&env);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let mut order = create_payment_order(&env, &merchant, 400, &token, expiration);
+    order.splits = Vec::from_array(
+        &env,
+        [
+            SplitRecipient {
+                recipient: platform.clone(),
+                share_bps: 1000,
+            },
+            SplitRecipient {
+                recipient: affiliate.clone(),
+                share_bps: 250,
+            },
+        ],
+    );
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let record = client.process_payment_with_signature(
+        &payer,
+        &order,
+        &signature,
+        &merchant_keys.public_key(&env),
+    );
+
+    // Shares come out of the 380 net amount, rounding down; the merchant keeps the rest
+    assert_eq!(
+        record.splits,
+        Vec::from_array(
+            &env,
+            [
+                SplitPayout {
+                    recipient: platform.clone(),
+                    amount: 38,
+                },
+                SplitPayout {
+                    recipient: affiliate.clone(),
+                    amount: 9,
+                },
+            ],
+        )
+    );
+    assert_eq!(token_client.balance(&platform), 38);
+    assert_eq!(token_client.balance(&affiliate), 9);
+    assert_eq!(token_client.balance(&merchant), 333);
+    assert_eq!(token_client.balance(&admin), 20);
+}
+
+#[test]
+fn test_invalid_split_orders_rejected() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+    let partner = Address::generate(&env);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let mut order = create_payment_order(&env, &merchant, 400, &token, expiration);
+
+    // Shares can't exceed the whole net amount
+    order.splits = Vec::from_array(
+        &env,
+        [SplitRecipient {
+            recipient: partner.clone(),
+            share_bps: 10_001,
+        }],
+    );
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let result =
+        client.try_process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidSplit)));
+
+    // The merchant can't be a split recipient, and each recipient is listed once
+    order.splits = Vec::from_array(
+        &env,
+        [SplitRecipient {
+            recipient: merchant.clone(),
+            share_bps: 1000,
+        }],
+    );
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let result =
+        client.try_process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidSplit)));
+
+    let share = SplitRecipient {
+        recipient: partner.clone(),
+        share_bps: 1000,
+    };
+    order.splits = Vec::from_array(&env, [share.clone(), share]);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let result =
+        client.try_process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidSplit)));
+
+    // Older schema versions don't commit to splits, so they can't carry any
+    order.splits = Vec::from_array(
+        &env,
+        [SplitRecipient {
+            recipient: partner,
+            share_bps: 1000,
+        }],
+    );
+    order.schema_version = TYPED_ORDER_SCHEMA_VERSION;
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let result =
+        client.try_process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(result, Err(Ok(PaymentError::UnsupportedSchemaVersion)));
+}
+
+#[test]
+#[should_panic(expected = "Error(Crypto, InvalidInput)")]
+fn test_altered_split_rejected() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let mut order = create_payment_order(&env, &merchant, 400, &token, expiration);
+    order.splits = Vec::from_array(
+        &env,
+        [SplitRecipient {
+            recipient: Address::generate(&env),
+            share_bps: 1000,
+        }],
+    );
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+
+    // Redirecting the share after signing breaks the signature
+    order.splits = Vec::from_array(
+        &env,
+        [SplitRecipient {
+            recipient: payer.clone(),
+            share_bps: 1000,
+        }],
+    );
+    client.process_payment_with_signature(
+        &payer,
+        &order,
+        &signature,
+        &merchant_keys.public_key(&env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Crypto, InvalidInput)")]
+fn test_signature_replay_across_deployments_rejected() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+
+    // Same merchant and key registered on a second deployment
+    let other_contract = env.register(PaymentProcessingContract {}, ());
+    let other_client = PaymentProcessingContractClient::new(&env, &other_contract);
+    register_test_merchant(&other_client, &env, &merchant);
+    other_client.add_supported_token(&merchant, &token);
+    other_client.register_signing_key(&merchant, &merchant_keys.public_key(&env), &None, &None);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let order = create_payment_order(&env, &merchant, 100, &token, expiration);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+
+    other_client.process_payment_with_signature(
+        &payer,
+        &order,
+        &signature,
+        &merchant_keys.public_key(&env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Crypto, InvalidInput)")]
+fn test_tampered_fee_amount_rejected() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let mut order = create_payment_order(&env, &merchant, 100, &token, expiration);
+    order.fee_amount = 5;
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+
+    order.fee_amount = 0;
+    client.process_payment_with_signature(
+        &payer,
+        &order,
+        &signature,
+        &merchant_keys.public_key(&env),
+    );
+}
+
+#[test]
+fn test_set_legacy_signature_deadline_unauthorized() {
+    let env = Env::default();
+    let contract_id = env.register(PaymentProcessingContract {}, ());
+    let client = PaymentProcessingContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    env.mock_all_auths();
+    client.set_admin(&admin);
+
+    let result = client.try_set_legacy_signature_deadline(&outsider, &0);
+    assert_eq!(result, Err(Ok(PaymentError::NotAuthorized)));
+    assert_eq!(client.get_legacy_signature_deadline(), None);
+}
+
+// Order Uniqueness & Idempotency Tests
+
+#[test]
+fn test_idempotent_order_resubmission() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+    let token_client = token::Client::new(&env, &token);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let order = create_payment_order(&env, &merchant, 100, &token, expiration);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+
+    let first = client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    let second =
+        client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+
+    // Charged once, same record returned, indexed once
+    assert_eq!(first, second);
+    assert_eq!(token_client.balance(&merchant), 100);
+    assert_eq!(token_client.balance(&payer), 900);
+    let result = client.get_merchant_payment_history(&merchant, &None, &10, &None, &None, &None);
+    assert_eq!(result.total_count, 1);
+}
+
+#[test]
+fn test_duplicate_order_id_rejected() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let mut order = create_payment_order(&env, &merchant, 100, &token, expiration);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+
+    // Same order_id with a different amount and nonce must not overwrite the first record
+    order.amount = 300;
+    order.nonce += 1;
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let result =
+        client.try_process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(result, Err(Ok(PaymentError::DuplicateOrderId)));
+
+    let record = client.get_payment_by_id(&merchant, &merchant, &order.order_id);
+    assert_eq!(record.amount, 100);
+
+    // Another payer cannot replay the order either
+    let other_payer = Address::generate(&env);
+    order.amount = 100;
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let result = client.try_process_payment_with_signature(
+        &other_payer,
+        &order,
+        &signature,
+        &merchant_public,
+    );
+    assert_eq!(result, Err(Ok(PaymentError::DuplicateOrderId)));
+}
+
+#[test]
+fn test_get_order_status() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let order = create_payment_order(&env, &merchant, 100, &token, expiration);
+    assert_eq!(
+        client.get_order_status(&merchant, &order.order_id),
+        OrderStatus::Unpaid
+    );
+
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(
+        client.get_order_status(&merchant, &order.order_id),
+        OrderStatus::Paid
+    );
+
+    client.update_payment_status(&merchant, &merchant, &order.order_id, &40);
+    assert_eq!(
+        client.get_order_status(&merchant, &order.order_id),
+        OrderStatus::PartiallyRefunded
+    );
+
+    client.update_payment_status(&merchant, &merchant, &order.order_id, &100);
+    assert_eq!(
+        client.get_order_status(&merchant, &order.order_id),
+        OrderStatus::Refunded
+    );
+
+    client.archive_payment_record(&admin, &merchant, &order.order_id);
+    assert_eq!(
+        client.get_order_status(&merchant, &order.order_id),
+        OrderStatus::Expired
+    );
+
+    // Order IDs are scoped per merchant, so another merchant can reuse the same ID
+    let (other_merchant, other_keys) = add_signed_merchant(&client, &env, &token);
+    assert_eq!(
+        client.get_order_status(&other_merchant, &order.order_id),
+        OrderStatus::Unpaid
+    );
+    let other_order = create_payment_order(&env, &other_merchant, 250, &token, expiration);
+    assert_eq!(other_order.order_id, order.order_id);
+    let signature = other_keys.sign_order(&env, &client.address, &other_order);
+    client.process_payment_with_signature(
+        &payer,
+        &other_order,
+        &signature,
+        &other_keys.public_key(&env),
+    );
+    assert_eq!(
+        client.get_order_status(&other_merchant, &order.order_id),
+        OrderStatus::Paid
+    );
+    let record = client.get_payment_by_id(&payer, &other_merchant, &order.order_id);
+    assert_eq!(record.amount, 250);
+    assert_eq!(
+        client.get_order_status(&merchant, &order.order_id),
+        OrderStatus::Expired
+    );
+}
+
+#[test]
+fn test_payment_history_cursor_with_shared_order_id() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let (other_merchant, other_keys) = add_signed_merchant(&client, &env, &token);
+
+    // Both merchants use TEST_ORDER_1; the first merchant also has TEST_ORDER_2
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let order = create_payment_order(&env, &merchant, 100, &token, expiration);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    client.process_payment_with_signature(
+        &payer,
+        &order,
+        &signature,
+        &merchant_keys.public_key(&env),
+    );
+    let other_order = create_payment_order(&env, &other_merchant, 250, &token, expiration);
+    let signature = other_keys.sign_order(&env, &client.address, &other_order);
+    client.process_payment_with_signature(
+        &payer,
+        &other_order,
+        &signature,
+        &other_keys.public_key(&env),
+    );
+    let mut second_order = create_payment_order(&env, &merchant, 300, &token, expiration);
+    second_order.nonce += 1;
+    second_order.order_id = String::from_str(&env, "TEST_ORDER_2");
+    let signature = merchant_keys.sign_order(&env, &client.address, &second_order);
+    client.process_payment_with_signature(
+        &payer,
+        &second_order,
+        &signature,
+        &merchant_keys.public_key(&env),
+    );
+
+    // Page through one payment at a time; the cursor must tell the shared order IDs apart
+    let mut cursor = None;
+    let mut amounts = Vec::new(&env);
+    for _ in 0..3 {
+        let page = client.get_payer_payment_history(
+            &payer,
+            &cursor,
+            &1,
+            &None,
+            &Some(SortField::Amount),
+            &Some(SortOrder::Ascending),
+        );
+        assert_eq!(page.records.len(), 1);
+        let record = page.records.get(0).unwrap();
+        amounts.push_back(record.amount);
+        cursor = page.next_cursor_merchant.zip(page.next_cursor);
+    }
+    assert_eq!(amounts, Vec::from_array(&env, [100i128, 250, 300]));
+    assert_eq!(cursor, None);
+
+    let page = client.get_payer_payment_history(
+        &payer,
+        &Some((other_merchant.clone(), other_order.order_id.clone())),
+        &10,
+        &None,
+        &Some(SortField::Amount),
+        &Some(SortOrder::Ascending),
+    );
+    assert_eq!(page.records.len(), 1);
+    assert_eq!(page.records.get(0).unwrap().merchant_address, merchant);
+
+    // The order_id alone doesn't make a cursor; it must name the payment's merchant too
+    let stranger = Address::generate(&env);
+    let result = client.try_get_payer_payment_history(
+        &payer,
+        &Some((stranger, order.order_id.clone())),
+        &10,
+        &None,
+        &None,
+        &None,
+    );
+    assert_eq!(result.err(), Some(Ok(PaymentError::InvalidCursor)));
+}
+
+#[test]
+fn test_batch_rejects_duplicate_order_ids() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let first = create_payment_order(&env, &merchant, 100, &token, expiration);
+    let mut second = first.clone();
+    second.nonce += 1;
+
+    let orders = Vec::from_array(&env, [first, second]);
+    let mut signatures = Vec::new(&env);
+    for order in orders.iter() {
+        signatures.push_back(merchant_keys.sign_order(&env, &client.address, &order));
+    }
+    let batch = BatchPayment {
+        payer: payer.clone(),
+        orders,
+        signatures: BatchSignatures::PerOrder(signatures),
+        merchant_public_keys: Map::from_array(&env, [(merchant.clone(), merchant_public)]),
+        mode: BatchMode::Atomic,
+    };
+
+    let result = client.try_batch_process_payments(&batch);
+    assert_eq!(result, Err(Ok(PaymentError::DuplicateOrderId)));
+}
+
+#[test]
+fn test_batch_process_payments_best_effort() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+    let token_client = token::Client::new(&env, &token);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let ok_order = create_payment_order(&env, &merchant, 100, &token, expiration);
+    let mut unaffordable = ok_order.clone();
+    unaffordable.order_id = String::from_str(&env, "ORDER_UNAFFORDABLE");
+    unaffordable.nonce += 1;
+    unaffordable.amount = 5_000;
+    let mut reused_nonce = ok_order.clone();
+    reused_nonce.order_id = String::from_str(&env, "ORDER_REUSED_NONCE");
+
+    let orders = Vec::from_array(&env, [ok_order, unaffordable, reused_nonce]);
+    let mut signatures = Vec::new(&env);
+    for order in orders.iter() {
+        signatures.push_back(merchant_keys.sign_order(&env, &client.address, &order));
+    }
+    let mut batch = BatchPayment {
+        payer: payer.clone(),
+        orders,
+        signatures: BatchSignatures::PerOrder(signatures),
+        merchant_public_keys: Map::from_array(&env, [(merchant.clone(), merchant_public)]),
+        mode: BatchMode::Atomic,
+    };
+
+    // Atomic mode reverts everything on the first failure
+    let result = client.try_batch_process_payments(&batch);
+    assert_eq!(result, Err(Ok(PaymentError::InsufficientBalance)));
+    assert_eq!(token_client.balance(&merchant), 0);
+
+    // Best-effort mode settles what it can and reports the rest
+    batch.mode = BatchMode::BestEffort;
+    let results = client.batch_process_payments(&batch);
+    assert_eq!(results.len(), 3);
+    assert!(results.get(0).unwrap().success);
+    assert_eq!(
+        results.get(1).unwrap().error_code,
+        Some(PaymentError::InsufficientBalance as u32)
+    );
+    assert_eq!(
+        results.get(2).unwrap().error_code,
+        Some(PaymentError::NonceAlreadyUsed as u32)
+    );
+    assert_eq!(token_client.balance(&merchant), 100);
+    assert_eq!(token_client.balance(&payer), 900);
+}
+
+#[test]
+fn test_batch_best_effort_bad_signature_aborts_batch() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+    let token_client = token::Client::new(&env, &token);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let first = create_payment_order(&env, &merchant, 100, &token, expiration);
+    let mut forged = first.clone();
+    forged.order_id = String::from_str(&env, "ORDER_FORGED");
+    forged.nonce += 1;
+    let mut last = first.clone();
+    last.order_id = String::from_str(&env, "ORDER_LAST");
+    last.nonce += 2;
+
+    let orders = Vec::from_array(&env, [first.clone(), forged.clone(), last]);
+    let mut signatures = Vec::new(&env);
+    for order in orders.iter() {
+        signatures.push_back(merchant_keys.sign_order(&env, &client.address, &order));
+    }
+    signatures.set(1, BytesN::from_array(&env, &[7u8; 64]));
+    let mut batch = BatchPayment {
+        payer: payer.clone(),
+        orders,
+        signatures: BatchSignatures::PerOrder(signatures),
+        merchant_public_keys: Map::from_array(&env, [(merchant.clone(), merchant_public)]),
+        mode: BatchMode::BestEffort,
+    };
+
+    // The bad signature traps before anything settles, so the earlier order isn't kept
+    assert!(client.try_batch_process_payments(&batch).is_err());
+    assert_eq!(
+        client.get_order_status(&merchant, &first.order_id),
+        OrderStatus::Unpaid
+    );
+    assert_eq!(token_client.balance(&merchant), 0);
+    assert_eq!(token_client.balance(&payer), 1000);
+
+    // An order that fails a cheap check is reported without its signature being verified
+    env.as_contract(&client.address, || {
+        Storage::new(&env).mark_nonce_used(&merchant, forged.nonce);
+    });
+    let results = client.batch_process_payments(&batch);
+    assert!(results.get(0).unwrap().success);
+    assert_eq!(
+        results.get(1).unwrap().error_code,
+        Some(PaymentError::NonceAlreadyUsed as u32)
+    );
+    assert!(results.get(2).unwrap().success);
+    assert_eq!(token_client.balance(&merchant), 200);
+
+    batch.mode = BatchMode::Atomic;
+    let result = client.try_batch_process_payments(&batch);
+    assert_eq!(result, Err(Ok(PaymentError::NonceAlreadyUsed)));
+}
+
+// Multi-merchant Batch Tests
+
+#[test]
+fn test_batch_verifies_each_order_against_its_merchant_key() {
+    let env = Env::default();
+    let (client, _admin, merchant_a, token, payer, keys_a) = setup_signed_payment_test(&env);
+    let (merchant_b, keys_b) = add_signed_merchant(&client, &env, &token);
+    let token_client = token::Client::new(&env, &token);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let order_a = create_payment_order(&env, &merchant_a, 100, &token, expiration);
+    let mut order_b = create_payment_order(&env, &merchant_b, 250, &token, expiration);
+    order_b.order_id = String::from_str(&env, "ORDER_B");
+
+    let signatures = Vec::from_array(
+        &env,
+        [
+            keys_a.sign_order(&env, &client.address, &order_a),
+            keys_b.sign_order(&env, &client.address, &order_b),
+        ],
+    );
+    let batch = BatchPayment {
+        payer: payer.clone(),
+        orders: Vec::from_array(&env, [order_a, order_b]),
+        signatures: BatchSignatures::PerOrder(signatures),
+        merchant_public_keys: Map::from_array(
+            &env,
+            [
+                (merchant_a.clone(), keys_a.public_key(&env)),
+                (merchant_b.clone(), keys_b.public_key(&env)),
+            ],
+        ),
+        mode: BatchMode::Atomic,
+    };
+
+    client.batch_process_payments(&batch);
+    assert_eq!(token_client.balance(&merchant_a), 100);
+    assert_eq!(token_client.balance(&merchant_b), 250);
+}
+
+#[test]
+fn test_batch_missing_merchant_key() {
+    let env = Env::default();
+    let (client, _admin, merchant_a, token, payer, keys_a) = setup_signed_payment_test(&env);
+    let (merchant_b, keys_b) = add_signed_merchant(&client, &env, &token);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let order_a = create_payment_order(&env, &merchant_a, 100, &token, expiration);
+    let mut order_b = create_payment_order(&env, &merchant_b, 250, &token, expiration);
+    order_b.order_id = String::from_str(&env, "ORDER_B");
+
+    let signatures = Vec::from_array(
+        &env,
+        [
+            keys_a.sign_order(&env, &client.address, &order_a),
+            keys_b.sign_order(&env, &client.address, &order_b),
+        ],
+    );
+    // Only merchant A's key is supplied
+    let batch = BatchPayment {
+        payer,
+        orders: Vec::from_array(&env, [order_a, order_b]),
+        signatures: BatchSignatures::PerOrder(signatures),
+        merchant_public_keys: Map::from_array(&env, [(merchant_a, keys_a.public_key(&env))]),
+        mode: BatchMode::Atomic,
+    };
+
+    let result = client.try_batch_process_payments(&batch);
+    assert_eq!(result, Err(Ok(PaymentError::SigningKeyNotFound)));
+}
+
+#[test]
+fn test_batch_per_merchant_digest_signatures() {
+    let env = Env::default();
+    let (client, _admin, merchant_a, token, payer, keys_a) = setup_signed_payment_test(&env);
+    let (merchant_b, keys_b) = add_signed_merchant(&client, &env, &token);
+    let token_client = token::Client::new(&env, &token);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let mut orders = Vec::new(&env);
+    for i in 0..3u32 {
+        let mut order = create_payment_order(&env, &merchant_a, 50, &token, expiration);
+        order.nonce = i + 1;
+        order.order_id = String::from_str(&env, &format!("CART_A_{}", i));
+        orders.push_back(order);
+    }
+    let mut order_b = create_payment_order(&env, &merchant_b, 200, &token, expiration);
+    order_b.order_id = String::from_str(&env, "CART_B_0");
+    orders.push_back(order_b);
+
+    let signatures = Map::from_array(
+        &env,
+        [
+            (
+                merchant_a.clone(),
+                keys_a.sign_batch_digest(&env, &client.address, &merchant_a, &orders),
+            ),
+            (
+                merchant_b.clone(),
+                keys_b.sign_batch_digest(&env, &client.address, &merchant_b, &orders),
+            ),
+        ],
+    );
+    let batch = BatchPayment {
+        payer: payer.clone(),
+        orders,
+        signatures: BatchSignatures::PerMerchant(signatures),
+        merchant_public_keys: Map::from_array(
+            &env,
+            [
+                (merchant_a.clone(), keys_a.public_key(&env)),
+                (merchant_b.clone(), keys_b.public_key(&env)),
+            ],
+        ),
+        mode: BatchMode::Atomic,
+    };
+
+    let results = client.batch_process_payments(&batch);
+    assert!(results.iter().all(|r| r.success));
+    assert_eq!(token_client.balance(&merchant_a), 150);
+    assert_eq!(token_client.balance(&merchant_b), 200);
+    assert_eq!(token_client.balance(&payer), 650);
+}
+
+#[test]
+fn test_batch_digest_does_not_cover_added_order() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let token_client = token::Client::new(&env, &token);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let first = create_payment_order(&env, &merchant, 100, &token, expiration);
+    let mut orders = Vec::from_array(&env, [first.clone()]);
+    let digest_signature =
+        merchant_keys.sign_batch_digest(&env, &client.address, &merchant, &orders);
+
+    // An order slipped in after the merchant signed must invalidate the digest
+    let mut extra = first.clone();
+    extra.nonce += 1;
+    extra.order_id = String::from_str(&env, "EXTRA_ORDER");
+    orders.push_back(extra);
+
+    let mut batch = BatchPayment {
+        payer: payer.clone(),
+        orders,
+        signatures: BatchSignatures::PerMerchant(Map::from_array(
+            &env,
+            [(merchant.clone(), digest_signature.clone())],
+        )),
+        merchant_public_keys: Map::from_array(
+            &env,
+            [(merchant.clone(), merchant_keys.public_key(&env))],
+        ),
+        mode: BatchMode::BestEffort,
+    };
+
+    assert!(client.try_batch_process_payments(&batch).is_err());
+    assert_eq!(
+        client.get_order_status(&merchant, &first.order_id),
+        OrderStatus::Unpaid
+    );
+    assert_eq!(token_client.balance(&merchant), 0);
+
+    // An order for a merchant without a digest entry is rejected before anything settles
+    let (other_merchant, other_keys) = add_signed_merchant(&client, &env, &token);
+    let other_order = create_payment_order(&env, &other_merchant, 250, &token, expiration);
+    batch.orders = Vec::from_array(&env, [first.clone(), other_order]);
+    batch
+        .merchant_public_keys
+        .set(other_merchant.clone(), other_keys.public_key(&env));
+    let result = client.try_batch_process_payments(&batch);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidSignature)));
+
+    // So is a digest entry for a merchant with no orders in the batch
+    batch.orders = Vec::from_array(&env, [first.clone()]);
+    batch.signatures = BatchSignatures::PerMerchant(Map::from_array(
+        &env,
+        [
+            (merchant.clone(), digest_signature),
+            (other_merchant, BytesN::from_array(&env, &[0u8; 64])),
+        ],
+    ));
+    let result = client.try_batch_process_payments(&batch);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidSignature)));
+    assert_eq!(token_client.balance(&merchant), 0);
+    assert_eq!(token_client.balance(&payer), 1000);
+}
+
+mod mock_swap_router {
+    use soroban_sdk::{contract, contractimpl, token, Address, Env, Symbol};
+
+    /// Swaps at a fixed price of `price_bps` input per 10_000 output, paying from its own balances
+    #[contract]
+    pub struct MockSwapRouter;
+
+    #[contractimpl]
+    impl MockSwapRouter {
+        pub fn set_price(env: Env, price_bps: i128) {
+            env.storage()
+                .instance()
+                .set(&Symbol::new(&env, "price"), &price_bps);
+        }
+
+        pub fn swap_exact_out(
+            env: Env,
+            token_in: Address,
+            token_out: Address,
+            amount_out: i128,
+            max_amount_in: i128,
+            recipient: Address,
+        ) -> i128 {
+            let price_bps: i128 = env
+                .storage()
+                .instance()
+                .get(&Symbol::new(&env, "price"))
+                .unwrap();
+            let amount_in = (amount_out * price_bps + 9_999) / 10_000;
+            if amount_in > max_amount_in {
+                panic!("slippage limit exceeded");
+            }
+
+            let router = env.current_contract_address();
+            token::Client::new(&env, &token_out).transfer(&router, &recipient, &amount_out);
+            token::Client::new(&env, &token_in).transfer(
+                &router,
+                &recipient,
+                &(max_amount_in - amount_in),
+            );
+            amount_in
+        }
+    }
+}
+
+/// Register a router quoting `price_bps` input per 10_000 of `token_out`, stocked with `token_out`
+fn setup_swap_router(
+    env: &Env,
+    client: &PaymentProcessingContractClient,
+    admin: &Address,
+    token_out: &Address,
+    price_bps: i128,
+) -> Address {
+    let router = env.register(mock_swap_router::MockSwapRouter, ());
+    mock_swap_router::MockSwapRouterClient::new(env, &router).set_price(&price_bps);
+    token::StellarAssetClient::new(env, token_out).mint(&router, &10_000);
+    client.set_swap_router(admin, &router);
+    router
+}
+
+#[test]
+fn test_cross_token_payment_swaps_through_router() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let token_client = token::Client::new(&env, &token);
+    client.set_fee_schedule(&admin, &FeeSchedule::from_rate(500));
+    let (input_token, input_client, input_admin) = create_token_contract(&env, &admin);
+    input_admin.mint(&payer, &1000);
+    let router = setup_swap_router(&env, &client, &admin, &token, 12_500);
+    assert_eq!(client.get_swap_router(), Some(router.clone()));
+
+    // The merchant quotes 400 in its own token and caps the payer's input at 550
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let mut order = create_payment_order(&env, &merchant, 400, &token, expiration);
+    order.input_token = Some(input_token.clone());
+    order.max_input_amount = 550;
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let record = client.process_payment_with_signature(
+        &payer,
+        &order,
+        &signature,
+        &merchant_keys.public_key(&env),
+    );
+
+    // Both legs are recorded: 500 of the input token bought the 400 gross amount
+    assert_eq!(record.input_token, Some(input_token.clone()));
+    assert_eq!(record.input_amount, 500);
+    assert_eq!(record.token, token);
+    assert_eq!(record.gross_amount, 400);
+
+    // The merchant receives its token exactly, and the unused input is returned to the payer
+    assert_eq!(token_client.balance(&merchant), 380);
+    assert_eq!(token_client.balance(&admin), 20);
+    assert_eq!(token_client.balance(&payer), 1000);
+    assert_eq!(input_client.balance(&payer), 500);
+    assert_eq!(input_client.balance(&router), 500);
+    assert_eq!(input_client.balance(&client.address), 0);
+    assert_eq!(token_client.balance(&client.address), 0);
+}
+
+#[test]
+fn test_cross_token_payment_limits() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+    let (input_token, input_client, input_admin) = create_token_contract(&env, &admin);
+    input_admin.mint(&payer, &1000);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let mut order = create_payment_order(&env, &merchant, 400, &token, expiration);
+    order.input_token = Some(input_token.clone());
+    order.max_input_amount = 450;
+
+    // No router registered yet
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let result =
+        client.try_process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidToken)));
+
+    // The router needs 500 of the input, above the signed slippage limit
+    setup_swap_router(&env, &client, &admin, &token, 12_500);
+    let result =
+        client.try_process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert!(result.is_err());
+    assert_eq!(input_client.balance(&payer), 1000);
+
+    // Swapping a token into itself is not a cross-token payment
+    order.input_token = Some(token.clone());
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let result =
+        client.try_process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidToken)));
+
+    // Older schema versions don't commit to swap terms
+    order.input_token = Some(input_token);
+    order.schema_version = SPLIT_ORDER_SCHEMA_VERSION;
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let result =
+        client.try_process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(result, Err(Ok(PaymentError::UnsupportedSchemaVersion)));
+}
+
+mod mock_price_oracle {
+    use crate::types::{OracleAsset, PriceData};
+    use soroban_sdk::{contract, contractimpl, Env, Vec};
+
+    /// SEP-40 oracle quoting with 14 decimals, fed prices by the test
+    #[contract]
+    pub struct MockPriceOracle;
+
+    #[contractimpl]
+    impl MockPriceOracle {
+        pub fn set_price(env: Env, asset: OracleAsset, price: i128, timestamp: u64) {
+            let mut history: Vec<PriceData> = env
+                .storage()
+                .instance()
+                .get(&asset)
+                .unwrap_or_else(|| Vec::new(&env));
+            history.push_front(PriceData { price, timestamp });
+            env.storage().instance().set(&asset, &history);
+        }
+
+        pub fn decimals(_env: Env) -> u32 {
+            14
+        }
+
+        pub fn lastprice(env: Env, asset: OracleAsset) -> Option<PriceData> {
+            Self::prices(env, asset, 1).and_then(|history| history.first())
+        }
+
+        pub fn prices(env: Env, asset: OracleAsset, records: u32) -> Option<Vec<PriceData>> {
+            let history: Vec<PriceData> = env.storage().instance().get(&asset)?;
+            Some(history.slice(0..records.min(history.len())))
+        }
+    }
+}
+
+/// Register a USD-based oracle accepting prices up to 5 minutes old that moved at most 10%
+fn setup_price_oracle<'a>(
+    env: &'a Env,
+    client: &PaymentProcessingContractClient,
+    admin: &Address,
+) -> mock_price_oracle::MockPriceOracleClient<'a> {
+    let oracle = env.register(mock_price_oracle::MockPriceOracle, ());
+    client.set_price_oracle(
+        admin,
+        &OracleConfig {
+            oracle: oracle.clone(),
+            base_currency: Symbol::new(env, "USD"),
+            max_price_age: 300,
+            max_deviation_bps: 1000,
+        },
+    );
+    mock_price_oracle::MockPriceOracleClient::new(env, &oracle)
+}
+
+#[test]
+fn test_fiat_order_converted_through_oracle() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let token_client = token::Client::new(&env, &token);
+    let merchant_public = merchant_keys.public_key(&env);
+    env.ledger().with_mut(|li| li.timestamp = 10_000);
+    let now = env.ledger().timestamp();
+
+    // The token trades at 0.50 USD and the euro at 1.25 USD
+    let oracle = setup_price_oracle(&env, &client, &admin);
+    let eur = Symbol::new(&env, "EUR");
+    oracle.set_price(
+        &OracleAsset::Stellar(token.clone()),
+        &50_000_000_000_000,
+        &now,
+    );
+    oracle.set_price(&OracleAsset::Other(eur.clone()), &125_000_000_000_000, &now);
+
+    let expiration = (now + 1000) as u32;
+    let mut order = create_payment_order(&env, &merchant, 100, &token, expiration);
+    order.currency = Some(eur.clone());
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let record =
+        client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+
+    // 100 EUR at 0.40 EUR per token settles 250 tokens and records the applied rate
+    assert_eq!(record.amount, 250);
+    assert_eq!(record.fiat_currency, Some(eur));
+    assert_eq!(record.fiat_amount, 100);
+    assert_eq!(record.fiat_rate, 40_000_000_000_000);
+    assert_eq!(token_client.balance(&merchant), 250);
+
+    // Resubmitting the fiat order returns the settled record without charging again
+    let resubmitted =
+        client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(resubmitted, record);
+    assert_eq!(token_client.balance(&payer), 750);
+
+    // Orders in the oracle's base currency need only the token price
+    order.currency = Some(Symbol::new(&env, "USD"));
+    order.nonce += 1;
+    order.order_id = String::from_str(&env, "TEST_ORDER_2");
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let record =
+        client.process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(record.amount, 200);
+    assert_eq!(record.fiat_rate, 50_000_000_000_000);
+    assert_eq!(token_client.balance(&merchant), 450);
+}
+
+#[test]
+fn test_fiat_order_oracle_checks() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+    env.ledger().with_mut(|li| li.timestamp = 10_000);
+    let now = env.ledger().timestamp();
+    let eur = Symbol::new(&env, "EUR");
+
+    let expiration = (now + 1000) as u32;
+    let mut order = create_payment_order(&env, &merchant, 100, &token, expiration);
+    order.currency = Some(eur.clone());
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+
+    // No oracle configured
+    let result =
+        client.try_process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidToken)));
+
+    // The oracle has no euro price yet
+    let oracle = setup_price_oracle(&env, &client, &admin);
+    let token_asset = OracleAsset::Stellar(token.clone());
+    oracle.set_price(&token_asset, &50_000_000_000_000, &now);
+    let result =
+        client.try_process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidToken)));
+
+    // Once both prices exist, older schema versions are still rejected since they don't commit
+    // to a currency
+    oracle.set_price(&OracleAsset::Other(eur.clone()), &125_000_000_000_000, &now);
+    order.schema_version = SWAP_ORDER_SCHEMA_VERSION;
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let result =
+        client.try_process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(result, Err(Ok(PaymentError::UnsupportedSchemaVersion)));
+    order.schema_version = ORDER_SCHEMA_VERSION;
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+
+    // Prices older than the configured age are stale
+    env.ledger().with_mut(|li| li.timestamp += 301);
+    let result =
+        client.try_process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(result, Err(Ok(PaymentError::PaymentExpired)));
+
+    // A fresh token price that doubled since the previous one exceeds the 10% deviation limit
+    let later = env.ledger().timestamp();
+    oracle.set_price(&token_asset, &100_000_000_000_000, &later);
+    oracle.set_price(&OracleAsset::Other(eur), &125_000_000_000_000, &later);
+    let result =
+        client.try_process_payment_with_signature(&payer, &order, &signature, &merchant_public);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidAmount)));
+}
+
+/// Register `payer_keys` for relayed payments and approve the contract to pull `allowance`
+fn setup_relayed_payer(
+    env: &Env,
+    client: &PaymentProcessingContractClient,
+    token: &Address,
+    payer: &Address,
+    allowance: i128,
+) -> MerchantKeypair {
+    let payer_keys = MerchantKeypair::generate(env);
+    client.set_payer_relay_key(payer, &Some(payer_keys.public_key(env)));
+    token::Client::new(env, token).approve(payer, &client.address, &allowance, &1000);
+    payer_keys
+}
+
+#[test]
+fn test_relayed_payment_pulls_from_allowance() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let token_client = token::Client::new(&env, &token);
+    let merchant_public = merchant_keys.public_key(&env);
+    let payer_keys = setup_relayed_payer(&env, &client, &token, &payer, 500);
+    let relayer = Address::generate(&env);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let order = create_payment_order(&env, &merchant, 400, &token, expiration);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let relay = RelayedPayment {
+        payer: payer.clone(),
+        relay_fee: 4,
+        payer_signature: payer_keys.sign_relayed_payment(
+            &env,
+            &client.address,
+            &relayer,
+            &payer,
+            4,
+            &order,
+        ),
+    };
+    let record =
+        client.process_relayed_payment(&relayer, &order, &signature, &merchant_public, &relay);
+
+    // Only the relayer authorized the transaction; the payer's funds moved through the allowance
+    let auths = env.auths();
+    assert_eq!(auths.len(), 1);
+    assert_eq!(auths[0].0, relayer);
+    assert_eq!(record.payer_address, payer);
+    assert_eq!(token_client.balance(&merchant), 400);
+    assert_eq!(token_client.balance(&relayer), 4);
+    assert_eq!(token_client.balance(&payer), 596);
+    assert_eq!(token_client.allowance(&payer, &client.address), 96);
+
+    // Resubmitting returns the settled record without paying the relayer again
+    let resubmitted =
+        client.process_relayed_payment(&relayer, &order, &signature, &merchant_public, &relay);
+    assert_eq!(resubmitted, record);
+    assert_eq!(token_client.balance(&relayer), 4);
+}
+
+#[test]
+fn test_relayed_payment_limits() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let merchant_public = merchant_keys.public_key(&env);
+    let relayer = Address::generate(&env);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let order = create_payment_order(&env, &merchant, 400, &token, expiration);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let payer_keys = MerchantKeypair::generate(&env);
+    let relay = |relay_fee: i128| RelayedPayment {
+        payer: payer.clone(),
+        relay_fee,
+        payer_signature: payer_keys.sign_relayed_payment(
+            &env,
+            &client.address,
+            &relayer,
+            &payer,
+            relay_fee,
+            &order,
+        ),
+    };
+
+    // The payer hasn't registered a relay key
+    let result = client.try_process_relayed_payment(
+        &relayer,
+        &order,
+        &signature,
+        &merchant_public,
+        &relay(0),
+    );
+    assert_eq!(result, Err(Ok(PaymentError::SigningKeyNotFound)));
+
+    // The allowance doesn't cover the order
+    client.set_payer_relay_key(&payer, &Some(payer_keys.public_key(&env)));
+    token::Client::new(&env, &token).approve(&payer, &client.address, &100, &1000);
+    let result = client.try_process_relayed_payment(
+        &relayer,
+        &order,
+        &signature,
+        &merchant_public,
+        &relay(0),
+    );
+    assert_eq!(result, Err(Ok(PaymentError::InsufficientBalance)));
+
+    // Relay fees are capped at 1% of the settled amount
+    token::Client::new(&env, &token).approve(&payer, &client.address, &500, &1000);
+    let result = client.try_process_relayed_payment(
+        &relayer,
+        &order,
+        &signature,
+        &merchant_public,
+        &relay(5),
+    );
+    assert_eq!(result, Err(Ok(PaymentError::InvalidAmount)));
+}
+
+#[test]
+#[should_panic(expected = "Error(Crypto, InvalidInput)")]
+fn test_relayed_payment_bound_to_relayer() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let payer_keys = setup_relayed_payer(&env, &client, &token, &payer, 500);
+    let relayer = Address::generate(&env);
+
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let order = create_payment_order(&env, &merchant, 400, &token, expiration);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let relay = RelayedPayment {
+        payer: payer.clone(),
+        relay_fee: 4,
+        payer_signature: payer_keys.sign_relayed_payment(
+            &env,
+            &client.address,
+            &relayer,
+            &payer,
+            4,
+            &order,
+        ),
+    };
+
+    // Another relayer can't claim the fee with the payer's authorization
+    client.process_relayed_payment(
+        &Address::generate(&env),
+        &order,
+        &signature,
+        &merchant_keys.public_key(&env),
+        &relay,
+    );
+}
+
+/// Sign `order` with the merchant's key and pay it
+fn sign_and_pay(
+    env: &Env,
+    client: &PaymentProcessingContractClient,
+    payer: &Address,
+    merchant_keys: &MerchantKeypair,
+    order: PaymentOrder,
+) -> PaymentRecord {
+    let signature = merchant_keys.sign_order(env, &client.address, &order);
+    client.process_payment_with_signature(payer, &order, &signature, &merchant_keys.public_key(env))
+}
+
+/// Unexpired order with nonce `nonce` and order ID `ORDER_<nonce>`
+fn numbered_order(
+    env: &Env,
+    merchant: &Address,
+    token: &Address,
+    nonce: u32,
+    amount: i64,
+) -> PaymentOrder {
+    let expiration = (env.ledger().timestamp() + 1000) as u32;
+    let mut order = create_payment_order(env, merchant, amount, token, expiration);
+    order.nonce = nonce;
+    order.order_id = String::from_str(env, &format!("ORDER_{}", nonce));
+    order
+}
+
+#[test]
+fn test_merchant_balance_withdrawal_and_scheduled_settlement() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let token_client = token::Client::new(&env, &token);
+    token::StellarAssetClient::new(&env, &token).mint(&payer, &1000);
+    client.set_fee_schedule(&admin, &FeeSchedule::from_rate(500));
+    let payout = Address::generate(&env);
+    client.set_settlement_config(&merchant, &payout, &SettlementSchedule::Daily, &300);
+    let pay = |nonce, amount| {
+        sign_and_pay(
+            &env,
+            &client,
+            &payer,
+            &merchant_keys,
+            numbered_order(&env, &merchant, &token, nonce, amount),
+        )
+    };
+
+    // Net amounts are credited to the internal balance instead of the merchant's wallet
+    pay(1, 400);
+    assert_eq!(client.get_merchant_balance(&merchant, &token), 380);
+    assert_eq!(token_client.balance(&merchant), 0);
+    assert_eq!(token_client.balance(&client.address), 380);
+
+    // Anyone can trigger the first settlement, which pays the payout address
+    assert_eq!(client.settle(&merchant, &token), 380);
+    assert_eq!(token_client.balance(&payout), 380);
+    assert_eq!(client.get_merchant_balance(&merchant, &token), 0);
+
+    // The next settlement of the token waits a full day
+    pay(2, 400);
+    let result = client.try_settle(&merchant, &token);
+    assert_eq!(result, Err(Ok(PaymentError::NotDue)));
+
+    // Withdrawals go to any address at any time, within the balance
+    let treasury = Address::generate(&env);
+    client.withdraw_balance(&merchant, &token, &200, &treasury);
+    assert_eq!(token_client.balance(&treasury), 200);
+    let result = client.try_withdraw_balance(&merchant, &token, &181, &treasury);
+    assert_eq!(result, Err(Ok(PaymentError::InsufficientBalance)));
+
+    // A due settlement still waits for the minimum payout
+    env.ledger().with_mut(|li| li.timestamp += 24 * 60 * 60);
+    let result = client.try_settle(&merchant, &token);
+    assert_eq!(result, Err(Ok(PaymentError::InsufficientBalance)));
+    pay(3, 200);
+    assert_eq!(client.settle(&merchant, &token), 370);
+    assert_eq!(token_client.balance(&payout), 750);
+
+    // Disabling the balance sends later payments straight to the merchant
+    client.disable_settlement(&merchant);
+    assert_eq!(client.get_settlement_config(&merchant), None);
+    pay(4, 100);
+    assert_eq!(token_client.balance(&merchant), 95);
+    assert_eq!(client.get_merchant_balance(&merchant, &token), 0);
+}
+
+#[test]
+fn test_refund_paid_from_merchant_balance() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let token_client = token::Client::new(&env, &token);
+    client.set_settlement_config(&merchant, &merchant, &SettlementSchedule::Weekly, &0);
+
+    let order = numbered_order(&env, &merchant, &token, 1, 400);
+    let record = sign_and_pay(&env, &client, &payer, &merchant_keys, order);
+    let refund_id = String::from_str(&env, "REFUND_1");
+    client.initiate_refund(
+        &merchant,
+        &refund_id,
+        &merchant,
+        &record.order_id,
+        &150,
+        &String::from_str(&env, "Customer request"),
+    );
+    client.approve_refund(&merchant, &refund_id);
+    client.execute_refund(&refund_id);
+
+    // The refund came out of the internal balance held by the contract
+    assert_eq!(client.get_merchant_balance(&merchant, &token), 250);
+    assert_eq!(token_client.balance(&client.address), 250);
+    assert_eq!(token_client.balance(&payer), 750);
+    assert_eq!(
+        client.get_refund_status(&refund_id),
+        RefundStatus::Completed
+    );
+}
+
+#[test]
+fn test_refund_from_merchant_balance_needs_merchant_signature() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let token_client = token::Client::new(&env, &token);
+    client.set_settlement_config(&merchant, &merchant, &SettlementSchedule::Weekly, &0);
+    let agent = Address::generate(&env);
+    client.grant_role(&admin, &agent, &Role::RefundAgent);
+
+    let order = numbered_order(&env, &merchant, &token, 1, 400);
+    let record = sign_and_pay(&env, &client, &payer, &merchant_keys, order);
+    let refund_id = String::from_str(&env, "REFUND_1");
+    client.initiate_refund(
+        &payer,
+        &refund_id,
+        &merchant,
+        &record.order_id,
+        &150,
+        &String::from_str(&env, "Customer request"),
+    );
+    client.approve_refund(&agent, &refund_id);
+
+    // The agent's approval doesn't let anyone spend the balance without the merchant
+    env.set_auths(&[]);
+    assert!(client.try_execute_refund(&refund_id).is_err());
+    assert_eq!(client.get_merchant_balance(&merchant, &token), 400);
+    assert_eq!(token_client.balance(&payer), 600);
+
+    env.mock_all_auths();
+    client.execute_refund(&refund_id);
+    assert_eq!(client.get_merchant_balance(&merchant, &token), 250);
+    assert_eq!(token_client.balance(&payer), 750);
+}
+
+#[test]
+fn test_rolling_reserve_held_and_released() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let token_client = token::Client::new(&env, &token);
+    let day = 24 * 60 * 60;
+    let pay = |nonce, amount| {
+        sign_and_pay(
+            &env,
+            &client,
+            &payer,
+            &merchant_keys,
+            numbered_order(&env, &merchant, &token, nonce, amount),
+        )
+    };
+
+    let category = ReserveScope::Category(MerchantCategory::Retail);
+    let result = client.try_set_rolling_reserve(
+        &admin,
+        &category,
+        &Some(RollingReserveConfig {
+            rate_bps: 0,
+            hold_period: 30 * day,
+        }),
+    );
+    assert_eq!(result, Err(Ok(PaymentError::InvalidFeeRate)));
+    let result = client.try_set_rolling_reserve(
+        &admin,
+        &category,
+        &Some(RollingReserveConfig {
+            rate_bps: 1000,
+            hold_period: 0,
+        }),
+    );
+    assert_eq!(result, Err(Ok(PaymentError::InvalidDateRange)));
+
+    // The category's reserve holds back 10% of each payment for 30 days
+    client.set_rolling_reserve(
+        &admin,
+        &category,
+        &Some(RollingReserveConfig {
+            rate_bps: 1000,
+            hold_period: 30 * day,
+        }),
+    );
+    pay(1, 400);
+    assert_eq!(token_client.balance(&merchant), 360);
+    assert_eq!(client.get_rolling_reserve_balance(&merchant, &token), 40);
+    assert_eq!(token_client.balance(&client.address), 40);
+
+    // A merchant's own setting takes precedence over its category's
+    let own = ReserveScope::Merchant(merchant.clone());
+    client.set_rolling_reserve(
+        &admin,
+        &own,
+        &Some(RollingReserveConfig {
+            rate_bps: 2000,
+            hold_period: 30 * day,
+        }),
+    );
+    env.ledger().with_mut(|li| li.timestamp += 10 * day);
+    pay(2, 200);
+    assert_eq!(token_client.balance(&merchant), 520);
+    assert_eq!(client.get_rolling_reserve_balance(&merchant, &token), 80);
+    let tranches = client.get_reserve_tranches(&merchant);
+    assert_eq!(tranches.len(), 2);
+    assert_eq!(tranches.get(1).unwrap().amount, 40);
+
+    // Nothing is released before the hold period ends
+    let result = client.try_release_rolling_reserve(&merchant, &token);
+    assert_eq!(result, Err(Ok(PaymentError::InsufficientBalance)));
+
+    // Anyone can release matured amounts to the merchant
+    env.ledger().with_mut(|li| li.timestamp += 20 * day);
+    assert_eq!(client.release_rolling_reserve(&merchant, &token), 40);
+    assert_eq!(token_client.balance(&merchant), 560);
+    assert_eq!(client.get_rolling_reserve_balance(&merchant, &token), 40);
+
+    // Later payments release matured amounts along the way
+    env.ledger().with_mut(|li| li.timestamp += 10 * day);
+    pay(3, 100);
+    assert_eq!(token_client.balance(&merchant), 680);
+    assert_eq!(client.get_rolling_reserve_balance(&merchant, &token), 20);
+    assert_eq!(client.get_reserve_tranches(&merchant).len(), 1);
+
+    // Clearing the merchant's setting falls back to the category
+    client.set_rolling_reserve(&admin, &own, &None);
+    assert_eq!(client.get_rolling_reserve(&own), None);
+    pay(4, 100);
+    assert_eq!(client.get_rolling_reserve_balance(&merchant, &token), 30);
+}
+
+#[test]
+fn test_refund_drawn_from_rolling_reserve() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let token_client = token::Client::new(&env, &token);
+    client.set_rolling_reserve(
+        &admin,
+        &ReserveScope::Merchant(merchant.clone()),
+        &Some(RollingReserveConfig {
+            rate_bps: 1000,
+            hold_period: 30 * 24 * 60 * 60,
+        }),
+    );
+
+    let order = numbered_order(&env, &merchant, &token, 1, 400);
+    let record = sign_and_pay(&env, &client, &payer, &merchant_keys, order);
+    // The merchant has moved its revenue out of the wallet
+    token_client.transfer(&merchant, Address::generate(&env), &360);
+
+    let refund = |id: &str, amount: i128| {
+        let refund_id = String::from_str(&env, id);
+        client.initiate_refund(
+            &merchant,
+            &refund_id,
+            &merchant,
+            &record.order_id,
+            &amount,
+            &String::from_str(&env, "Customer request"),
+        );
+        client.approve_refund(&merchant, &refund_id);
+        client.try_execute_refund(&refund_id)
+    };
+
+    // The wallet can't cover the refund, so it comes out of the reserve
+    assert_eq!(refund("REFUND_1", 30), Ok(Ok(())));
+    assert_eq!(token_client.balance(&payer), 630);
+    assert_eq!(client.get_rolling_reserve_balance(&merchant, &token), 10);
+
+    // The reserve can't cover more than it holds
+    assert_eq!(
+        refund("REFUND_2", 50),
+        Err(Ok(PaymentError::InsufficientBalance))
+    );
+    assert_eq!(client.get_rolling_reserve_balance(&merchant, &token), 10);
+
+    // An agent's approval doesn't let anyone draw the reserve without the merchant
+    let agent = Address::generate(&env);
+    client.grant_role(&admin, &agent, &Role::RefundAgent);
+    let refund_id = String::from_str(&env, "REFUND_3");
+    client.initiate_refund(
+        &payer,
+        &refund_id,
+        &merchant,
+        &record.order_id,
+        &10,
+        &String::from_str(&env, "Customer request"),
+    );
+    client.approve_refund(&agent, &refund_id);
+    env.set_auths(&[]);
+    assert!(client.try_execute_refund(&refund_id).is_err());
+    assert_eq!(client.get_rolling_reserve_balance(&merchant, &token), 10);
+
+    env.mock_all_auths();
+    client.execute_refund(&refund_id);
+    assert_eq!(client.get_rolling_reserve_balance(&merchant, &token), 0);
+    assert_eq!(token_client.balance(&payer), 640);
+}
+
+#[test]
+fn test_dispute_ruling_split_across_reserves() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let token_client = token::Client::new(&env, &token);
+    let arbitrator = Address::generate(&env);
+    client.add_arbitrator(&admin, &arbitrator);
+    client.set_rolling_reserve(
+        &admin,
+        &ReserveScope::Merchant(merchant.clone()),
+        &Some(RollingReserveConfig {
+            rate_bps: 1000,
+            hold_period: 30 * 24 * 60 * 60,
+        }),
+    );
+
+    let order = numbered_order(&env, &merchant, &token, 1, 400);
+    let record = sign_and_pay(&env, &client, &payer, &merchant_keys, order);
+    client.deposit_reserve(&merchant, &token, &50);
+    client.open_dispute(
+        &payer,
+        &merchant,
+        &record.order_id,
+        &80,
+        &String::from_str(&env, "Item not as described"),
+    );
+    env.ledger()
+        .with_mut(|li| li.timestamp += 7 * 24 * 60 * 60 + 1);
+
+    // Neither reserve covers the ruling alone, so the deposit pays first and the rolling reserve the rest
+    client.resolve_dispute(
+        &arbitrator,
+        &merchant,
+        &record.order_id,
+        &DisputeRuling::Full,
+    );
+    assert_eq!(
+        client.get_dispute(&merchant, &record.order_id).status,
+        DisputeStatus::Refunded
+    );
+    assert_eq!(token_client.balance(&payer), 1000 - 400 + 80);
+    assert_eq!(client.get_merchant_reserve(&merchant, &token), 0);
+    assert_eq!(client.get_rolling_reserve_balance(&merchant, &token), 10);
+}
+
+#[test]
+fn test_volume_and_velocity_limits() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let day = 24 * 60 * 60;
+    let limit = |window, max_amount, max_payments| VolumeLimit {
+        window,
+        max_amount,
+        max_payments,
+    };
+    let pay = |payer: &Address, nonce, amount| {
+        let order = numbered_order(&env, &merchant, &token, nonce, amount);
+        let signature = merchant_keys.sign_order(&env, &client.address, &order);
+        client.try_process_payment_with_signature(
+            payer,
+            &order,
+            &signature,
+            &merchant_keys.public_key(&env),
+        )
+    };
+
+    let merchant_scope = LimitScope::Merchant(merchant.clone(), token.clone());
+    let result = client.try_set_volume_limits(
+        &admin,
+        &merchant_scope,
+        &Vec::from_array(&env, [limit(0, Some(500), None)]),
+    );
+    assert_eq!(result, Err(Ok(PaymentError::InvalidDateRange)));
+    let result = client.try_set_volume_limits(
+        &admin,
+        &merchant_scope,
+        &Vec::from_array(&env, [limit(day, None, None)]),
+    );
+    assert_eq!(result, Err(Ok(PaymentError::InvalidTransactionLimit)));
+
+    // The merchant can take at most 500 a day, and each payer two payments an hour
+    client.set_volume_limits(
+        &admin,
+        &merchant_scope,
+        &Vec::from_array(&env, [limit(day, Some(500), None)]),
+    );
+    client.set_volume_limits(
+        &admin,
+        &LimitScope::Payer(token.clone()),
+        &Vec::from_array(&env, [limit(60 * 60, None, Some(2))]),
+    );
+
+    assert!(pay(&payer, 1, 300).is_ok());
+    assert_eq!(
+        pay(&payer, 2, 250),
+        Err(Ok(PaymentError::MerchantVolumeLimitExceeded))
+    );
+    let merchant_counter = VolumeCounter::Merchant(merchant.clone(), token.clone());
+    assert_eq!(
+        client.get_volume_usage(&merchant_counter, &day),
+        VolumeUsage {
+            amount: 300,
+            payments: 1,
+        }
+    );
+
+    // The payer's third payment within the hour is refused until the next window
+    assert!(pay(&payer, 2, 100).is_ok());
+    assert_eq!(
+        pay(&payer, 3, 50),
+        Err(Ok(PaymentError::PayerVelocityLimitExceeded))
+    );
+    env.ledger().with_mut(|li| li.timestamp += 60 * 60);
+    assert!(pay(&payer, 3, 50).is_ok());
+
+    // Each payer can spend at most 40 a day with the merchant
+    client.set_volume_limits(
+        &admin,
+        &LimitScope::PayerMerchant(merchant.clone(), token.clone()),
+        &Vec::from_array(&env, [limit(day, Some(40), None)]),
+    );
+    let other_payer = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token).mint(&other_payer, &1000);
+    assert!(pay(&other_payer, 4, 30).is_ok());
+    assert_eq!(
+        pay(&other_payer, 5, 20),
+        Err(Ok(PaymentError::PayerMerchantLimitExceeded))
+    );
+
+    // A payment past the merchant's daily cap is refused until the next day
+    assert_eq!(client.get_volume_usage(&merchant_counter, &day).amount, 480);
+    assert_eq!(
+        pay(&payer, 5, 21),
+        Err(Ok(PaymentError::MerchantVolumeLimitExceeded))
+    );
+    env.ledger().with_mut(|li| li.timestamp += day);
+    assert!(pay(&other_payer, 5, 20).is_ok());
+
+    // Clearing a scope's limits lifts them
+    client.set_volume_limits(&admin, &merchant_scope, &Vec::new(&env));
+    assert_eq!(client.get_volume_limits(&merchant_scope).len(), 0);
+
+    // A token-wide cap counts every merchant's volume in the token
+    client.set_volume_limits(
+        &admin,
+        &LimitScope::Token(token.clone()),
+        &Vec::from_array(&env, [limit(day, Some(30), None)]),
+    );
+    assert!(pay(&payer, 6, 20).is_ok());
+    assert_eq!(
+        pay(&payer, 7, 20),
+        Err(Ok(PaymentError::TokenVolumeLimitExceeded))
+    );
+    assert!(pay(&payer, 7, 10).is_ok());
+}
+
+mod mock_metadata_token {
+    use soroban_sdk::{contract, contractimpl, Env, Symbol};
+
+    /// Token exposing only its decimals, which the test can change
+    #[contract]
+    pub struct MockMetadataToken;
+
+    #[contractimpl]
+    impl MockMetadataToken {
+        pub fn set_decimals(env: Env, decimals: u32) {
+            env.storage()
+                .instance()
+                .set(&Symbol::new(&env, "decimals"), &decimals);
+        }
+
+        pub fn decimals(env: Env) -> u32 {
+            env.storage()
+                .instance()
+                .get(&Symbol::new(&env, "decimals"))
+                .unwrap()
+        }
+    }
+}
+
+#[test]
+fn test_per_token_transaction_limits() {
+    let env = Env::default();
+    let (client, _admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let metadata_token = |decimals| {
+        let token = env.register(mock_metadata_token::MockMetadataToken, ());
+        mock_metadata_token::MockMetadataTokenClient::new(&env, &token).set_decimals(&decimals);
+        token
+    };
+    let wide_token = metadata_token(18);
+    let narrow_token = metadata_token(2);
+
+    // The default limit is scaled to each token's decimals
+    assert_eq!(
+        client.get_transaction_limit(&merchant, &token),
+        DEFAULT_TRANSACTION_LIMIT
+    );
+    assert_eq!(
+        client.get_transaction_limit(&merchant, &wide_token),
+        DEFAULT_TRANSACTION_LIMIT * 100_000_000_000
+    );
+    assert_eq!(
+        client.get_transaction_limit(&merchant, &narrow_token),
+        DEFAULT_TRANSACTION_LIMIT / 100_000
+    );
+
+    let result = client.try_set_merchant_limits(
+        &merchant,
+        &5_000,
+        &Map::from_array(&env, [(token.clone(), 0)]),
+    );
+    assert_eq!(result, Err(Ok(PaymentError::InvalidTransactionLimit)));
+
+    // A token's own limit is in its base units and replaces the scaled default
+    client.set_merchant_limits(
+        &merchant,
+        &5_000,
+        &Map::from_array(&env, [(token.clone(), 300)]),
+    );
+    assert_eq!(client.get_transaction_limit(&merchant, &token), 300);
+    assert_eq!(
+        client.get_transaction_limit(&merchant, &wide_token),
+        500_000_000_000_000
+    );
+
+    let order = numbered_order(&env, &merchant, &token, 1, 400);
+    let signature = merchant_keys.sign_order(&env, &client.address, &order);
+    let result = client.try_process_payment_with_signature(
+        &payer,
+        &order,
+        &signature,
+        &merchant_keys.public_key(&env),
+    );
+    assert_eq!(result, Err(Ok(PaymentError::TransactionLimitExceeded)));
+    let order = numbered_order(&env, &merchant, &token, 1, 300);
+    sign_and_pay(&env, &client, &payer, &merchant_keys, order);
+
+    // Decimals are read once and cached
+    mock_metadata_token::MockMetadataTokenClient::new(&env, &wide_token).set_decimals(&7);
+    assert_eq!(
+        client.get_transaction_limit(&merchant, &wide_token),
+        500_000_000_000_000
+    );
+}
+
+#[test]
+fn test_role_based_access_control() {
+    let env = Env::default();
+    let (client, admin, merchant, token, payer, merchant_keys) = setup_signed_payment_test(&env);
+    let fee_manager = Address::generate(&env);
+    let agent = Address::generate(&env);
+
+    // Only the super-admin grants roles, and holds all of them itself
+    let result = client.try_grant_role(&fee_manager, &fee_manager, &Role::FeeManager);
+    assert_eq!(result, Err(Ok(PaymentError::NotAuthorized)));
+    client.grant_role(&admin, &fee_manager, &Role::FeeManager);
+    client.grant_role(&admin, &agent, &Role::RefundAgent);
+    let result = client.try_grant_role(&admin, &fee_manager, &Role::FeeManager);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidStatus)));
+    assert!(client.has_role(&admin, &Role::Archiver));
+    assert!(!client.has_role(&fee_manager, &Role::RefundAgent));
+    assert_eq!(
+        client.get_role_members(&Role::FeeManager),
+        Vec::from_array(&env, [fee_manager.clone()])
+    );
+
+    // Each role reaches only the operations it covers
+    client.set_fee(&fee_manager, &100, &fee_manager, &token);
+    client.set_fee_schedule(&fee_manager, &FeeSchedule::from_rate(200));
+    let result = client.try_pause(&fee_manager);
+    assert_eq!(result, Err(Ok(PaymentError::NotAuthorized)));
+    let result = client.try_set_fee(&agent, &300, &agent, &token);
+    assert_eq!(result, Err(Ok(PaymentError::NotAuthorized)));
+    let result = client.try_set_fee_schedule(&agent, &FeeSchedule::from_rate(300));
+    assert_eq!(result, Err(Ok(PaymentError::NotAuthorized)));
+
+    let order = numbered_order(&env, &merchant, &token, 1, 100);
+    let record = sign_and_pay(&env, &client, &payer, &merchant_keys, order);
+    let refund_id = String::from_str(&env, "REFUND_1");
+    client.initiate_refund(
+        &payer,
+        &refund_id,
+        &merchant,
+        &record.order_id,
+        &50,
+        &String::from_str(&env, "Damaged"),
+    );
+    let result = client.try_approve_refund(&fee_manager, &refund_id);
+    assert_eq!(result, Err(Ok(PaymentError::NotAuthorized)));
+    client.approve_refund(&agent, &refund_id);
+    assert_eq!(
+        client
+            .get_payment_by_id(&agent, &merchant, &record.order_id)
+            .order_id,
+        record.order_id
+    );
+
+    // Revoked roles stop working
+    client.revoke_role(&admin, &fee_manager, &Role::FeeManager);
+    let result = client.try_set_fee_schedule(&fee_manager, &FeeSchedule::from_rate(200));
+    assert_eq!(result, Err(Ok(PaymentError::NotAuthorized)));
+    let result = client.try_revoke_role(&admin, &fee_manager, &Role::FeeManager);
+    assert_eq!(result, Err(Ok(PaymentError::InvalidStatus)));
+}
+
+#[test]
+fn test_set_pause_admin_replaces_previous_guardian() {
+    let env = Env::default();
+    let contract_id = env.register(PaymentProcessingContract {}, ());
+    let client = PaymentProcessingContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    let old_pause_admin = Address::generate(&env);
+    let new_pause_admin = Address::generate(&env);
+    env.mock_all_auths();
+    client.set_admin(&admin);
+
+    client.set_pause_admin(&admin, &old_pause_admin);
+    client.set_pause_admin(&admin, &new_pause_admin);
+    assert_eq!(
+        client.get_role_members(&Role::PauseGuardian),
+        Vec::from_array(&env, [new_pause_admin.clone()])
+    );
+
+    // The rotated-out pause admin can no longer pause
+    let result = client.try_pause(&old_pause_admin);
+    assert_eq!(result, Err(Ok(PaymentError::NotAuthorized)));
+    client.pause(&new_pause_admin);
+    assert!(client.is_paused());
+}
+
+#[test]
+fn test_legacy_pause_admin_migrated_to_role() {
+    let env = Env::default();
+    let contract_id = env.register(PaymentProcessingContract {}, ());
+    let client = PaymentProcessingContractClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    let pause_admin = Address::generate(&env);
+    env.mock_all_auths();
+    client.set_admin(&admin);
+
+    // A contract upgraded from before roles still has its pause admin under the old key
+    let legacy_key = DataKey::PausedAdmin.as_symbol(&env);
+    env.as_contract(&contract_id, || {
+        env.storage().instance().set(&legacy_key, &pause_admin);
+    });
+
+    assert!(client.has_role(&pause_admin, &Role::PauseGuardian));
+    env.as_contract(&contract_id, || {
+        assert!(!env.storage().instance().has(&legacy_key));
+    });
+    client.pause(&pause_admin);
+    assert!(client.is_paused());
+}
+
+#[test]
+fn test_super_admin_handover() {
+    let env = Env::default();
+    let (client, admin, _merchant, _token, _payer, _merchant_keys) =
+        setup_signed_payment_test(&env);
+    let successor = Address::generate(&env);
+
+    // The super-admin can't simply be overwritten
+    let result = client.try_set_admin(&successor);
+    assert_eq!(result, Err(Ok(PaymentError::NotAuthorized)));
+
+    // A handover lapses if it isn't accepted within a week
+    let typo = Address::generate(&env);
+    client.propose_super_admin(&admin, &typo);
+    env.ledger()
+        .with_mut(|li| li.timestamp += 7 * 24 * 60 * 60 + 1);
+    let result = client.try_accept_super_admin(&typo);
+    assert_eq!(result, Err(Ok(PaymentError::PaymentExpired)));
+
+    // A new proposal replaces the pending one
+    client.propose_super_admin(&admin, &successor);
+    let handover = client.get_pending_super_admin().unwrap();
+    assert_eq!(handover.new_admin, successor);
+    assert_eq!(
+        handover.expires_at,
+        env.ledger().timestamp() + 7 * 24 * 60 * 60
+    );
+
+    // Only the proposed address can accept
+    let result = client.try_accept_super_admin(&admin);
+    assert_eq!(result, Err(Ok(PaymentError::NotAuthorized)));
+    client.accept_super_admin(&successor);
+    assert_eq!(client.get_pending_super_admin(), None);
+
+    // The previous super-admin lost its access
+    assert!(!client.has_role(&admin, &Role::FeeManager));
+    let result = client.try_grant_role(&admin, &admin, &Role::FeeManager);
+    assert_eq!(result, Err(Ok(PaymentError::NotAuthorized)));
+    client.grant_role(&successor, &admin, &Role::FeeManager);
+    assert!(client.has_role(&admin, &Role::FeeManager));
+}
+
+#[test]
+fn test_admin_council_approves_sensitive_actions() {
+    let env = Env::default();
+    let (client, admin, _merchant, token, _payer, _merchant_keys) = setup_signed_payment_test(&env);
+    let members = [
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    ];
+    let agent = Address::generate(&env);
+
+    let result = client.try_set_admin_council(
+        &admin,
+        &AdminCouncil {
+            members: Vec::from_array(&env, members.clone()),
+            threshold: 4,
+        },
+    );
+    assert_eq!(result, Err(Ok(PaymentError::InvalidThreshold)));
+    client.set_admin_council(
+        &admin,
+        &AdminCouncil {
+            members: Vec::from_array(&env, members.clone()),
+            threshold: 2,
+        },
+    );
+
+    // Sensitive actions no longer go through directly
+    let result = client.try_set_fee(&admin, &100, &admin, &token);
+    assert_eq!(result, Err(Ok(PaymentError::ThresholdNotMet)));
+    let result = client.try_grant_role(&admin, &agent, &Role::RefundAgent);
+    assert_eq!(result, Err(Ok(PaymentError::ThresholdNotMet)));
+    let result = client.try_set_payment_cleanup_period(&admin, &86_400);
+    assert_eq!(result, Err(Ok(PaymentError::ThresholdNotMet)));
+
+    // Only members propose, and the proposal waits for a second approval
+    let grant = CouncilAction::GrantRole(agent.clone(), Role::RefundAgent);
+    let result = client.try_propose_council_action(&agent, &grant);
+    assert_eq!(result, Err(Ok(PaymentError::NotASigner)));
+    let id = client.propose_council_action(&members[0], &grant);
+    assert!(!client.has_role(&agent, &Role::RefundAgent));
+    assert_eq!(client.get_open_council_proposals().len(), 1);
+    let result = client.try_approve_council_action(&members[0], &id);
+    assert_eq!(result, Err(Ok(PaymentError::AlreadySigned)));
+
+    assert!(client.approve_council_action(&members[1], &id));
+    assert!(client.has_role(&agent, &Role::RefundAgent));
+    let proposal = client.get_council_proposal(&id);
+    assert!(proposal.executed);
+    assert_eq!(proposal.approvals.len(), 2);
+    assert_eq!(client.get_open_council_proposals().len(), 0);
+    let result = client.try_approve_council_action(&members[2], &id);
+    assert_eq!(result, Err(Ok(PaymentError::AlreadyExecuted)));
+
+    // Proposals lapse if not approved in time
+    let fee = CouncilAction::SetFee(100, admin.clone(), token.clone());
+    let id = client.propose_council_action(&members[0], &fee);
+    env.ledger()
+        .with_mut(|li| li.timestamp += 7 * 24 * 60 * 60 + 1);
+    let result = client.try_approve_council_action(&members[1], &id);
+    assert_eq!(result, Err(Ok(PaymentError::PaymentExpired)));
+
+    // Dissolving the council hands sensitive actions back to the super-admin
+    let id = client.propose_council_action(&members[2], &CouncilAction::DissolveCouncil);
+    client.approve_council_action(&members[0], &id);
+    assert_eq!(client.get_admin_council(), None);
+    client.set_fee(&admin, &100, &admin, &token);
+}
+
+#[test]
+fn test_admin_council_gates_super_admin_entrypoints() {
+    let env = Env::default();
+    let (client, admin, merchant, token, _payer, _merchant_keys) = setup_signed_payment_test(&env);
+    let member = Address::generate(&env);
+    let fee_manager = Address::generate(&env);
+    let other = Address::generate(&env);
+    client.grant_role(&admin, &fee_manager, &Role::FeeManager);
+    client.set_admin_council(
+        &admin,
+        &AdminCouncil {
+            members: Vec::from_array(&env, [member.clone()]),
+            threshold: 1,
+        },
+    );
+
+    // Neither the super-admin's implicit roles nor a delegated fee manager bypass the council
+    let schedule = FeeSchedule::from_rate(200);
+    let scope = FeeScope::Merchant(merchant.clone());
+    let beneficiaries = Vec::from_array(
+        &env,
+        [FeeBeneficiary {
+            address: other.clone(),
+            share_bps: 10_000,
+        }],
+    );
+    for caller in [&admin, &fee_manager] {
+        let result = client.try_set_fee(caller, &200, &other, &token);
+        assert_eq!(result, Err(Ok(PaymentError::ThresholdNotMet)));
+        let result = client.try_set_fee_schedule(caller, &schedule);
+        assert_eq!(result, Err(Ok(PaymentError::ThresholdNotMet)));
+        let result = client.try_set_fee_override(caller, &scope, &Some(schedule.clone()));
+        assert_eq!(result, Err(Ok(PaymentError::ThresholdNotMet)));
+        let result = client.try_set_token_fee(caller, &token, &other, &schedule);
+        assert_eq!(result, Err(Ok(PaymentError::ThresholdNotMet)));
+        let result = client.try_remove_token_fee(caller, &token);
+        assert_eq!(result, Err(Ok(PaymentError::ThresholdNotMet)));
+        let result = client.try_set_fee_custody(caller, &true);
+        assert_eq!(result, Err(Ok(PaymentError::ThresholdNotMet)));
+        let result = client.try_set_fee_beneficiaries(caller, &beneficiaries);
+        assert_eq!(result, Err(Ok(PaymentError::ThresholdNotMet)));
+    }
+    let result = client.try_set_legacy_signature_deadline(&admin, &1_000);
+    assert_eq!(result, Err(Ok(PaymentError::ThresholdNotMet)));
+    let result = client.try_add_arbitrator(&admin, &other);
+    assert_eq!(result, Err(Ok(PaymentError::ThresholdNotMet)));
+    let result = client.try_remove_arbitrator(&admin, &other);
+    assert_eq!(result, Err(Ok(PaymentError::ThresholdNotMet)));
+    let result = client.try_set_swap_router(&admin, &other);
+    assert_eq!(result, Err(Ok(PaymentError::ThresholdNotMet)));
+    let oracle = OracleConfig {
+        oracle: other.clone(),
+        base_currency: Symbol::new(&env, "USD"),
+        max_price_age: 300,
+        max_deviation_bps: 1000,
+    };
+    let result = client.try_set_price_oracle(&admin, &oracle);
+    assert_eq!(result, Err(Ok(PaymentError::ThresholdNotMet)));
+
+    // The council carries them out instead
+    client.propose_council_action(
+        &member,
+        &CouncilAction::SetFeeOverride(scope.clone(), schedule.clone()),
+    );
+    assert_eq!(client.get_fee_override(&scope), Some(schedule));
+    client.propose_council_action(&member, &CouncilAction::RemoveFeeOverride(scope.clone()));
+    assert_eq!(client.get_fee_override(&scope), None);
+    client.propose_council_action(&member, &CouncilAction::AddArbitrator(other.clone()));
+    assert_eq!(
+        client.get_arbitrators(),
+        Vec::from_array(&env, [other.clone()])
+    );
+    client.propose_council_action(&member, &CouncilAction::SetPriceOracle(oracle));
+    assert_eq!(client.get_price_oracle().unwrap().oracle, other);
+}
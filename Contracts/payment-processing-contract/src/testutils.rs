@@ -0,0 +1,104 @@
+#![cfg(any(test, feature = "testutils"))]
+
+//! Helpers for signing payment orders in tests.
+//!
+//! Signatures are produced over exactly the bytes the contract verifies, so
+//! tests exercise the same `ed25519_verify` path as production.
+
+extern crate std;
+
+use ed25519_dalek::{Signer, SigningKey};
+use soroban_sdk::{testutils::BytesN as _, Address, Bytes, BytesN, Env, Vec};
+
+use crate::{
+    create_batch_digest, create_optimized_message, create_relay_message,
+    create_typed_order_message, helper::LEGACY_ORDER_SCHEMA_VERSION, types::PaymentOrder,
+};
+
+/// Ed25519 keypair a merchant registers and signs orders with, or a payer authorizes relayed
+/// payments with
+pub struct MerchantKeypair {
+    signing_key: SigningKey,
+}
+
+impl MerchantKeypair {
+    /// Generate a keypair from a random seed
+    pub fn generate(env: &Env) -> Self {
+        let seed: BytesN<32> = BytesN::random(env);
+        Self::from_seed(&seed.to_array())
+    }
+
+    /// Build a deterministic keypair from a 32-byte seed
+    pub fn from_seed(seed: &[u8; 32]) -> Self {
+        Self {
+            signing_key: SigningKey::from_bytes(seed),
+        }
+    }
+
+    /// Public key in the form expected by `register_signing_key`
+    pub fn public_key(&self, env: &Env) -> BytesN<32> {
+        BytesN::from_array(env, &self.signing_key.verifying_key().to_bytes())
+    }
+
+    /// Sign a payment order over the message the contract at `contract` verifies
+    pub fn sign_order(&self, env: &Env, contract: &Address, order: &PaymentOrder) -> BytesN<64> {
+        self.sign_bytes(env, &order_message(env, contract, order))
+    }
+
+    /// Sign the batch digest covering every order in `orders` that belongs to `merchant`
+    pub fn sign_batch_digest(
+        &self,
+        env: &Env,
+        contract: &Address,
+        merchant: &Address,
+        orders: &Vec<PaymentOrder>,
+    ) -> BytesN<64> {
+        let mut messages = Vec::new(env);
+        for order in orders.iter() {
+            if order.merchant_address == *merchant {
+                messages.push_back(order_message(env, contract, &order));
+            }
+        }
+        self.sign_bytes(
+            env,
+            &create_batch_digest(env, contract, merchant, &messages),
+        )
+    }
+
+    /// Sign a payer's authorization for `relayer` to submit `order` for `relay_fee`
+    pub fn sign_relayed_payment(
+        &self,
+        env: &Env,
+        contract: &Address,
+        relayer: &Address,
+        payer: &Address,
+        relay_fee: i128,
+        order: &PaymentOrder,
+    ) -> BytesN<64> {
+        self.sign_bytes(
+            env,
+            &create_relay_message(
+                env,
+                contract,
+                relayer,
+                payer,
+                relay_fee,
+                &order_message(env, contract, order),
+            ),
+        )
+    }
+
+    /// Sign arbitrary message bytes
+    pub fn sign_bytes(&self, env: &Env, message: &Bytes) -> BytesN<64> {
+        let buf: std::vec::Vec<u8> = message.iter().collect();
+        BytesN::from_array(env, &self.signing_key.sign(&buf).to_bytes())
+    }
+}
+
+fn order_message(env: &Env, contract: &Address, order: &PaymentOrder) -> Bytes {
+    if order.schema_version == LEGACY_ORDER_SCHEMA_VERSION {
+        create_optimized_message(env, order)
+    } else {
+        create_typed_order_message(env, contract, order)
+    }
+}
@@ -1,4 +1,79 @@
-use soroban_sdk::{contracttype, Address, Map, String, Symbol, Vec};
+use crate::helper::{BASIS_POINTS_DENOMINATOR, DAILY_SETTLEMENT_PERIOD, WEEKLY_SETTLEMENT_PERIOD};
+use crate::storage::PaymentKey;
+use soroban_sdk::{contracttype, Address, BytesN, Map, String, Symbol, Vec};
+
+/// Operational roles the super-admin can delegate. The super-admin holds all of them.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Role {
+    /// Fee schedules, overrides, custody and beneficiaries
+    FeeManager,
+    /// Pausing and unpausing the contract
+    PauseGuardian,
+    /// Refund approvals, escrow disputes and payment status corrections
+    RefundAgent,
+    /// Merchant risk controls: escrow mode, rolling reserves and volume limits
+    MerchantVerifier,
+    /// Archiving and cleaning up payment records
+    Archiver,
+}
+
+/// A pending super-admin handover, which lapses if not accepted by `expires_at`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminHandover {
+    pub new_admin: Address,
+    pub expires_at: u64,
+}
+
+/// M-of-N council whose approval sensitive admin actions need once it is set
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminCouncil {
+    pub members: Vec<Address>,
+    pub threshold: u32,
+}
+
+/// Sensitive admin action carried out by an approved council proposal
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CouncilAction {
+    /// Fee rate in basis points, fee collector, fee token, as for `set_fee`
+    SetFee(u32, Address, Address),
+    SetPaymentCleanupPeriod(u64),
+    GrantRole(Address, Role),
+    RevokeRole(Address, Role),
+    /// Start a super-admin handover, which the new admin still has to accept
+    ProposeSuperAdmin(Address),
+    SetFeeSchedule(FeeSchedule),
+    SetFeeOverride(FeeScope, FeeSchedule),
+    RemoveFeeOverride(FeeScope),
+    /// Token, fee collector and fee schedule, as for `set_token_fee`
+    SetTokenFee(Address, Address, FeeSchedule),
+    RemoveTokenFee(Address),
+    SetFeeCustody(bool),
+    SetFeeBeneficiaries(Vec<FeeBeneficiary>),
+    SetLegacySignatureDeadline(u64),
+    AddArbitrator(Address),
+    RemoveArbitrator(Address),
+    SetSwapRouter(Address),
+    SetPriceOracle(OracleConfig),
+    UpdateCouncil(AdminCouncil),
+    DissolveCouncil,
+}
+
+/// A council proposal and the members who approved it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CouncilProposal {
+    pub id: u64,
+    pub action: CouncilAction,
+    pub proposer: Address,
+    pub approvals: Vec<Address>,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub executed: bool,
+}
 
 /// Merchant category enumeration
 #[contracttype]
@@ -24,7 +99,11 @@ pub struct Merchant {
     pub registration_timestamp: u64,
     pub last_activity_timestamp: u64,
     pub category: MerchantCategory,
+    /// Per-payment limit in units of `LIMIT_REFERENCE_DECIMALS` decimals, scaled to each token's
+    /// decimals; applies to tokens without their own limit
     pub max_transaction_limit: i128,
+    /// Per-payment limits in the token's own base units
+    pub token_limits: Map<Address, i128>,
 }
 
 #[contracttype]
@@ -78,6 +157,32 @@ impl Merchant {
     }
 }
 
+/// Ed25519 key a merchant uses to sign payment orders
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MerchantSigningKey {
+    pub public_key: BytesN<32>,
+    pub registered_at: u64,
+    /// Key is accepted from this timestamp (inclusive)
+    pub valid_from: u64,
+    /// Key is accepted until this timestamp (inclusive), None means no expiry
+    pub valid_until: Option<u64>,
+    /// Set when the merchant revokes the key
+    pub revoked_at: Option<u64>,
+}
+
+impl MerchantSigningKey {
+    pub fn is_active(&self, now: u64) -> bool {
+        if self.revoked_at.is_some() || now < self.valid_from {
+            return false;
+        }
+        match self.valid_until {
+            Some(valid_until) => now <= valid_until,
+            None => true,
+        }
+    }
+}
+
 /// Optimized payment order with efficient data types
 #[contracttype]
 #[derive(Clone)]
@@ -94,6 +199,35 @@ pub struct PaymentOrder {
     /// Use compact string representation
     pub order_id: String,
     pub fee_amount: i128,
+    /// Signed message format: 0 = legacy, 1 = domain-separated typed message,
+    /// 2 = typed message committing to `splits`, 3 = also committing to the swap terms,
+    /// 4 = also committing to `currency`
+    pub schema_version: u32,
+    /// Revenue shares of the net amount paid to other recipients; the merchant keeps the rest
+    pub splits: Vec<SplitRecipient>,
+    /// Token the payer pays in when it differs from `token`, swapped through the registered router
+    pub input_token: Option<Address>,
+    /// Most of `input_token` the payer spends on the swap (slippage limit, 0 without a swap)
+    pub max_input_amount: i128,
+    /// Fiat currency `amount` is denominated in, converted to `token` at payment time
+    pub currency: Option<Symbol>,
+}
+
+/// Recipient of a share of an order's net amount
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SplitRecipient {
+    pub recipient: Address,
+    /// Share of the net amount in basis points
+    pub share_bps: u32,
+}
+
+/// Amount a split recipient received from a payment
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SplitPayout {
+    pub recipient: Address,
+    pub amount: i128,
 }
 
 /// Batch operation structures for gas optimization
@@ -110,13 +244,46 @@ pub struct BatchTokenAddition {
     pub tokens: Vec<Address>,
 }
 
+/// How a payment batch handles an order that fails validation or settlement
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BatchMode {
+    /// Any failing order reverts the whole batch
+    Atomic,
+    /// Failing orders are skipped and reported; the rest settle. A bad signature still
+    /// aborts the batch.
+    BestEffort,
+}
+
+/// How the orders in a payment batch are signed
+#[contracttype]
+#[derive(Clone)]
+pub enum BatchSignatures {
+    /// One signature per order, in the same order as `BatchPayment.orders`
+    PerOrder(Vec<soroban_sdk::BytesN<64>>),
+    /// One signature per merchant over the digest of all that merchant's orders in the batch
+    PerMerchant(Map<Address, soroban_sdk::BytesN<64>>),
+}
+
 #[contracttype]
 #[derive(Clone)]
 pub struct BatchPayment {
     pub payer: Address,
     pub orders: Vec<PaymentOrder>,
-    pub signatures: Vec<soroban_sdk::BytesN<64>>,
-    pub merchant_public_key: soroban_sdk::BytesN<32>,
+    pub signatures: BatchSignatures,
+    /// Registered signing key to verify each merchant's orders against
+    pub merchant_public_keys: Map<Address, soroban_sdk::BytesN<32>>,
+    pub mode: BatchMode,
+}
+
+/// Outcome of a single order within a payment batch
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchOrderResult {
+    pub order_id: String,
+    pub success: bool,
+    /// `PaymentError` code when the order was not settled
+    pub error_code: Option<u32>,
 }
 
 /// Gas estimation structures
@@ -166,10 +333,91 @@ impl NonceTracker {
     }
 }
 
+/// Per-transaction fee: a basis-point rate plus a fixed component, clamped to [min, max]
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeSchedule {
+    /// Rate in basis points (1 bps = 0.01%)
+    pub rate_bps: u32,
+    /// Flat amount added to every transaction
+    pub fixed_fee: i128,
+    pub min_fee: i128,
+    pub max_fee: Option<i128>,
+}
+
+impl FeeSchedule {
+    pub fn from_rate(rate_bps: u32) -> Self {
+        Self {
+            rate_bps,
+            fixed_fee: 0,
+            min_fee: 0,
+            max_fee: None,
+        }
+    }
+
+    /// Fee for `amount`, never more than the amount itself
+    pub fn calculate(&self, amount: i128) -> i128 {
+        let rate = i128::from(self.rate_bps);
+        let quotient = amount / BASIS_POINTS_DENOMINATOR;
+        let remainder = amount % BASIS_POINTS_DENOMINATOR;
+        let mut fee =
+            quotient * rate + (remainder * rate) / BASIS_POINTS_DENOMINATOR + self.fixed_fee;
+
+        if fee < self.min_fee {
+            fee = self.min_fee;
+        }
+        if let Some(max_fee) = self.max_fee {
+            if fee > max_fee {
+                fee = max_fee;
+            }
+        }
+        fee.min(amount)
+    }
+}
+
+/// Scope a fee schedule override applies to
+/// Resolution order: merchant, then token, then merchant category, then the default schedule
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FeeScope {
+    Merchant(Address),
+    Token(Address),
+    Category(MerchantCategory),
+}
+
+/// Fee settings that apply to payments in one token
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenFeeInfo {
+    pub token: Address,
+    pub fee_collector: Address,
+    pub schedule: FeeSchedule,
+}
+
+/// Recipient of a share of custodied fees
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeBeneficiary {
+    pub address: Address,
+    /// Share of each fee in basis points; all shares total 10000
+    pub share_bps: u32,
+}
+
+/// Who bears the processing fee on a merchant's payments
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FeeMode {
+    /// The fee is taken out of the order amount the merchant receives
+    Deduct,
+    /// The fee is added on top of the order amount and paid by the payer
+    Surcharge,
+}
+
 #[contracttype]
 #[derive(Clone)]
 pub struct Fee {
-    pub fee_rate: u64,
+    /// Default schedule applied when no override matches
+    pub schedule: FeeSchedule,
     pub fee_collector: Address,
     pub fee_token: Address,
 }
@@ -205,9 +453,46 @@ pub struct PaymentRecord {
     pub merchant_address: Address,
     pub payer_address: Address,
     pub token: Address,
+    /// Order amount as signed by the merchant
     pub amount: i128,
     pub paid_at: u64,
     pub refunded_amount: i128,
+    /// Total paid by the payer
+    pub gross_amount: i128,
+    pub fee_amount: i128,
+    /// Amount received by the merchant
+    pub net_amount: i128,
+    pub escrow_status: EscrowStatus,
+    /// When a held escrow can be released without the payer's confirmation (0 if not escrowed)
+    pub escrow_release_at: u64,
+    /// Invoice this payment paid toward, if any
+    pub invoice_id: Option<u64>,
+    /// Revenue shares paid out of `net_amount` to recipients other than the merchant
+    pub splits: Vec<SplitPayout>,
+    /// Token the payer paid in when it was swapped into `token`
+    pub input_token: Option<Address>,
+    /// Amount of `input_token` the swap consumed (0 without a swap)
+    pub input_amount: i128,
+    /// Fiat currency the order was priced in
+    pub fiat_currency: Option<Symbol>,
+    /// Order amount in `fiat_currency` (0 for token-denominated orders)
+    pub fiat_amount: i128,
+    /// Applied price of one `token` unit in `fiat_currency`, scaled by 10^14 (0 if not converted)
+    pub fiat_rate: i128,
+}
+
+/// Escrow state of a payment's net amount
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EscrowStatus {
+    /// Paid straight to the merchant
+    None,
+    /// Held by the contract until delivery is confirmed or the timeout passes
+    Held,
+    /// Frozen by the payer until the dispute is resolved
+    Disputed,
+    /// Paid out to the merchant and/or refunded to the payer
+    Released,
 }
 
 #[contracttype]
@@ -254,6 +539,7 @@ pub struct MerchantDeactivatedEvent {
 pub struct LimitsUpdatedEvent {
     pub merchant: Address,
     pub max_transaction_limit: i128,
+    pub token_limits: Map<Address, i128>,
     pub timestamp: u64,
 }
 
@@ -317,6 +603,384 @@ pub struct RefundRequest {
     pub approved_by: Option<Address>,
 }
 
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DisputeStatus {
+    /// Awaiting evidence and an arbitrator's ruling
+    Open,
+    Refunded,
+    PartiallyRefunded,
+    Rejected,
+}
+
+/// Arbitrator's decision on a dispute
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DisputeRuling {
+    /// Refund the full disputed amount
+    Full,
+    /// Refund part of the disputed amount
+    Partial(i128),
+    /// Refund nothing
+    Denied,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dispute {
+    pub order_id: String,
+    pub merchant_address: Address,
+    pub payer_address: Address,
+    pub token: Address,
+    /// Amount the payer asks back
+    pub amount: i128,
+    pub reason: String,
+    pub payer_evidence: Vec<BytesN<32>>,
+    pub merchant_evidence: Vec<BytesN<32>>,
+    pub opened_at: u64,
+    /// Last timestamp evidence is accepted; rulings are made after it
+    pub evidence_deadline: u64,
+    pub status: DisputeStatus,
+    pub refund_amount: i128,
+    pub arbitrator: Option<Address>,
+    pub resolved_at: u64,
+}
+
+/// Recurring billing terms defined by a merchant
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubscriptionPlan {
+    pub plan_id: u64,
+    pub merchant_address: Address,
+    pub token: Address,
+    /// Charged once per period
+    pub amount: i128,
+    /// Seconds between charges
+    pub period: u64,
+    /// Seconds after subscribing before the first charge
+    pub trial_period: u64,
+    /// Number of charges before the subscription completes (0 = until cancelled)
+    pub max_cycles: u32,
+    /// Whether payers can still subscribe
+    pub active: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SubscriptionStatus {
+    Active,
+    Paused,
+    Cancelled,
+    /// All `max_cycles` charges were collected
+    Completed,
+}
+
+/// A payer's subscription, with the plan's terms fixed at subscribe time
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Subscription {
+    pub subscription_id: u64,
+    pub plan_id: u64,
+    pub merchant_address: Address,
+    pub payer_address: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub period: u64,
+    pub max_cycles: u32,
+    pub cycles_charged: u32,
+    /// Earliest timestamp `charge_due` collects the next cycle
+    pub next_charge_at: u64,
+    pub status: SubscriptionStatus,
+    pub created_at: u64,
+}
+
+/// Terms of an invoice a merchant issues on-chain
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct InvoiceTerms {
+    pub token: Address,
+    pub amount: i128,
+    /// Last timestamp the invoice can be paid
+    pub due_date: u64,
+    /// Only this payer may pay, if set
+    pub payer: Option<Address>,
+    pub memo: String,
+    /// Hash of the off-chain line items
+    pub line_items_hash: BytesN<32>,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InvoiceStatus {
+    Open,
+    PartiallyPaid,
+    Paid,
+    Cancelled,
+    /// Past its due date without being paid in full
+    Expired,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Invoice {
+    pub invoice_id: u64,
+    pub merchant_address: Address,
+    pub terms: InvoiceTerms,
+    pub amount_paid: i128,
+    pub payment_count: u32,
+    pub status: InvoiceStatus,
+    pub created_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AuthorizationStatus {
+    /// Funds are held and can be captured
+    Active,
+    /// Closed by a final capture, or by capturing the full amount
+    Captured,
+    /// The uncaptured remainder was returned to the payer before expiry
+    Voided,
+    /// The hold lapsed and the uncaptured remainder was returned to the payer
+    Expired,
+}
+
+/// A payer's pre-authorized hold that a merchant captures from
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaymentAuthorization {
+    pub authorization_id: u64,
+    pub merchant_address: Address,
+    pub payer_address: Address,
+    pub token: Address,
+    /// Amount held by the contract
+    pub max_amount: i128,
+    /// Total taken from the hold so far, fees included
+    pub captured_amount: i128,
+    pub capture_count: u32,
+    /// Last timestamp the hold can be captured from
+    pub expires_at: u64,
+    pub status: AuthorizationStatus,
+    pub created_at: u64,
+}
+
+/// Scope a rolling reserve applies to; a merchant's own setting takes precedence over its category's
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReserveScope {
+    Merchant(Address),
+    Category(MerchantCategory),
+}
+
+/// Share of each payment held back from the merchant, and for how long
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RollingReserveConfig {
+    pub rate_bps: u32,
+    /// Seconds each held amount stays in the reserve
+    pub hold_period: u64,
+}
+
+/// An amount held back from one payment until `release_at`
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReserveTranche {
+    pub order_id: String,
+    pub token: Address,
+    pub amount: i128,
+    pub release_at: u64,
+}
+
+/// What a set of volume limits caps
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LimitScope {
+    /// A merchant's volume in a token
+    Merchant(Address, Address),
+    /// Volume in a token across all merchants
+    Token(Address),
+    /// Each payer's volume in a token (velocity)
+    Payer(Address),
+    /// Each payer's volume with a merchant in a token
+    PayerMerchant(Address, Address),
+}
+
+/// Cap on the amount and/or number of payments within a fixed window
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VolumeLimit {
+    /// Window length in seconds; windows are aligned to multiples of it
+    pub window: u64,
+    pub max_amount: Option<i128>,
+    pub max_payments: Option<u32>,
+}
+
+/// Running totals a volume limit is checked against
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum VolumeCounter {
+    /// Merchant, token
+    Merchant(Address, Address),
+    Token(Address),
+    /// Payer, token
+    Payer(Address, Address),
+    /// Payer, merchant, token
+    PayerMerchant(Address, Address, Address),
+}
+
+/// Totals of one counter within the current window
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct VolumeUsage {
+    pub amount: i128,
+    pub payments: u32,
+}
+
+/// How often `settle` can pay out a merchant's internal balance
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SettlementSchedule {
+    Daily,
+    Weekly,
+}
+
+impl SettlementSchedule {
+    /// Minimum time between scheduled settlements of one token
+    pub fn period(&self) -> u64 {
+        match self {
+            SettlementSchedule::Daily => DAILY_SETTLEMENT_PERIOD,
+            SettlementSchedule::Weekly => WEEKLY_SETTLEMENT_PERIOD,
+        }
+    }
+}
+
+/// Payout settings for a merchant whose payments are credited to an internal balance
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SettlementConfig {
+    /// Where scheduled settlements are paid
+    pub payout_address: Address,
+    pub schedule: SettlementSchedule,
+    /// Smallest balance a scheduled settlement pays out
+    pub min_payout: i128,
+    /// Last scheduled settlement per token
+    pub last_settled_at: Map<Address, u64>,
+}
+
+/// A payer's off-chain authorization for a relayer to submit an order on their behalf
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RelayedPayment {
+    pub payer: Address,
+    /// Paid by the payer to the relayer on top of the order, from the same allowance
+    pub relay_fee: i128,
+    /// Payer's signature over the relay message, made with their registered relay key
+    pub payer_signature: BytesN<64>,
+}
+
+/// Price oracle used to convert fiat-denominated orders into token amounts
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OracleConfig {
+    pub oracle: Address,
+    /// Currency the oracle quotes prices in, e.g. `USD`
+    pub base_currency: Symbol,
+    /// Oldest price, in seconds, a conversion accepts
+    pub max_price_age: u64,
+    /// Largest move from the previous price, in basis points, a conversion accepts
+    pub max_deviation_bps: u32,
+}
+
+/// Asset an oracle quotes (SEP-40)
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OracleAsset {
+    Stellar(Address),
+    Other(Symbol),
+}
+
+/// An oracle price and when it was recorded (SEP-40)
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceData {
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct SigningKeyRegisteredEvent {
+    pub merchant: Address,
+    pub public_key: BytesN<32>,
+    pub valid_from: u64,
+    pub valid_until: Option<u64>,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct SigningKeyRotatedEvent {
+    pub merchant: Address,
+    pub old_key: BytesN<32>,
+    pub new_key: BytesN<32>,
+    pub old_key_valid_until: u64,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct SigningKeyRevokedEvent {
+    pub merchant: Address,
+    pub public_key: BytesN<32>,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct PaymentProcessedEvent {
+    pub order_id: String,
+    pub merchant: Address,
+    pub payer: Address,
+    pub token: Address,
+    pub gross_amount: i128,
+    pub fee_amount: i128,
+    pub net_amount: i128,
+    pub fee_mode: FeeMode,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct EscrowReleasedEvent {
+    pub order_id: String,
+    pub merchant_amount: i128,
+    pub refund_amount: i128,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct DisputeResolvedEvent {
+    pub order_id: String,
+    pub arbitrator: Address,
+    pub status: DisputeStatus,
+    pub refund_amount: i128,
+    /// Whether the refund came from escrow rather than the merchant's reserve
+    pub from_escrow: bool,
+    pub timestamp: u64,
+}
+
+/// A payment that breached a volume limit, or took a counter past the warning level of one
+#[contracttype]
+#[derive(Clone)]
+pub struct VolumeLimitEvent {
+    pub order_id: String,
+    pub counter: VolumeCounter,
+    pub limit: VolumeLimit,
+    /// Usage within the window including the payment
+    pub usage: VolumeUsage,
+    pub timestamp: u64,
+}
+
 // Event topics
 pub fn merchant_registered_topic(env: &soroban_sdk::Env) -> Symbol {
     Symbol::new(env, "merchant_reg")
@@ -338,6 +1002,62 @@ pub fn multisig_topic(env: &soroban_sdk::Env) -> Symbol {
     Symbol::new(env, "multisig_op")
 }
 
+pub fn signing_key_topic(env: &soroban_sdk::Env) -> Symbol {
+    Symbol::new(env, "signing_key")
+}
+
+pub fn payment_topic(env: &soroban_sdk::Env) -> Symbol {
+    Symbol::new(env, "payment")
+}
+
+pub fn escrow_topic(env: &soroban_sdk::Env) -> Symbol {
+    Symbol::new(env, "escrow")
+}
+
+pub fn dispute_topic(env: &soroban_sdk::Env) -> Symbol {
+    Symbol::new(env, "dispute")
+}
+
+pub fn reserve_topic(env: &soroban_sdk::Env) -> Symbol {
+    Symbol::new(env, "reserve")
+}
+
+pub fn subscription_topic(env: &soroban_sdk::Env) -> Symbol {
+    Symbol::new(env, "subscription")
+}
+
+pub fn invoice_topic(env: &soroban_sdk::Env) -> Symbol {
+    Symbol::new(env, "invoice")
+}
+
+pub fn authorization_topic(env: &soroban_sdk::Env) -> Symbol {
+    Symbol::new(env, "authorization")
+}
+
+pub fn swap_topic(env: &soroban_sdk::Env) -> Symbol {
+    Symbol::new(env, "swap")
+}
+
+pub fn oracle_topic(env: &soroban_sdk::Env) -> Symbol {
+    Symbol::new(env, "oracle")
+}
+
+pub fn settlement_topic(env: &soroban_sdk::Env) -> Symbol {
+    Symbol::new(env, "settlement")
+}
+
+pub fn volume_limit_topic(env: &soroban_sdk::Env) -> Symbol {
+    Symbol::new(env, "volume_limit")
+}
+
+pub fn role_topic(env: &soroban_sdk::Env) -> Symbol {
+    Symbol::new(env, "role")
+}
+
+pub fn council_topic(env: &soroban_sdk::Env) -> Symbol {
+    Symbol::new(env, "council")
+}
+
 // Payment History Query Types
 
 /// Derived payment status based on refunded_amount
@@ -351,6 +1071,35 @@ pub enum PaymentRecordStatus {
 }
 
 impl PaymentRecord {
+    /// The storage key of the payment; order IDs are only unique per merchant
+    pub fn key(&self) -> PaymentKey {
+        (self.merchant_address.clone(), self.order_id.clone())
+    }
+
+    /// Whether the record settled the same order for the same payer
+    pub fn matches_order(&self, payer: &Address, order: &PaymentOrder) -> bool {
+        // Fiat orders settle a converted token amount, so compare the signed fiat amount
+        let amount_matches = match &order.currency {
+            Some(currency) => {
+                self.fiat_currency.as_ref() == Some(currency)
+                    && self.fiat_amount == i128::from(order.amount)
+            }
+            None => self.fiat_currency.is_none() && self.amount == i128::from(order.amount),
+        };
+        self.merchant_address == order.merchant_address
+            && self.payer_address == *payer
+            && self.token == order.token
+            && amount_matches
+    }
+
+    /// Whether the contract still holds this payment's funds in escrow
+    pub fn is_escrow_open(&self) -> bool {
+        matches!(
+            self.escrow_status,
+            EscrowStatus::Held | EscrowStatus::Disputed
+        )
+    }
+
     /// Derive status from refunded_amount
     pub fn get_status(&self) -> PaymentRecordStatus {
         if self.refunded_amount == 0 {
@@ -363,6 +1112,17 @@ impl PaymentRecord {
     }
 }
 
+/// Settlement status of an order as seen by its merchant
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum OrderStatus {
+    Unpaid,            // No payment recorded for the order_id
+    Paid,              // Settled with no refunds
+    PartiallyRefunded, // Settled and partially refunded
+    Refunded,          // Settled and fully refunded
+    Expired,           // Record archived after the cleanup period
+}
+
 /// Sort order for query results
 #[contracttype]
 #[derive(Clone, Debug, PartialEq)]
@@ -403,8 +1163,10 @@ pub struct PaymentQueryFilter {
 pub struct PaymentQueryResult {
     /// Payment records matching the query
     pub records: Vec<PaymentRecord>,
-    /// Cursor for next page (None if no more results)
+    /// Cursor for next page (None if no more results): the order_id of the last record
     pub next_cursor: Option<String>,
+    /// Merchant of the last record; pass `(next_cursor_merchant, next_cursor)` as the cursor
+    pub next_cursor_merchant: Option<Address>,
     /// Total count of records matching filters (may be approximate for large datasets)
     pub total_count: u32,
 }